@@ -2,7 +2,9 @@
 
 #[allow(clippy::module_inception)]
 mod cell;
+mod ref_cell;
 mod unsafe_cell;
 
 pub use self::cell::Cell;
+pub use self::ref_cell::{Ref, RefCell, RefMut};
 pub use self::unsafe_cell::{ConstPtr, MutPtr, UnsafeCell};
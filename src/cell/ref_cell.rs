@@ -0,0 +1,171 @@
+use super::{ConstPtr, MutPtr, UnsafeCell};
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+/// A checked version of [`std::cell::RefCell`], implemented on top of
+/// [`loom::cell::UnsafeCell`][unsafecell].
+///
+/// Conflicting borrows panic, as with the standard counterpart. In addition,
+/// each borrow is tracked as an access to the underlying
+/// [`UnsafeCell`][unsafecell] for as long as its guard lives, so borrows from
+/// different threads that are not ordered by happens-before are reported even
+/// if they do not overlap in time.
+///
+/// [unsafecell]: crate::cell::UnsafeCell
+pub struct RefCell<T> {
+    /// Number of live `Ref`s, or -1 while a `RefMut` is live.
+    borrow: std::cell::Cell<isize>,
+    value: UnsafeCell<T>,
+}
+
+/// Wraps a borrowed reference to a value in a [`RefCell`].
+pub struct Ref<'b, T> {
+    borrow: &'b std::cell::Cell<isize>,
+    ptr: ConstPtr<T>,
+}
+
+/// A wrapper type for a mutably borrowed value from a [`RefCell`].
+pub struct RefMut<'b, T> {
+    borrow: &'b std::cell::Cell<isize>,
+    ptr: MutPtr<T>,
+}
+
+impl<T> RefCell<T> {
+    /// Creates a new `RefCell` containing `value`.
+    #[track_caller]
+    pub fn new(value: T) -> RefCell<T> {
+        RefCell {
+            borrow: std::cell::Cell::new(0),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Immutably borrows the wrapped value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is currently mutably borrowed, or if the borrow
+    /// races with an access from another thread.
+    #[track_caller]
+    pub fn borrow(&self) -> Ref<'_, T> {
+        let borrow = self.borrow.get();
+        assert!(borrow >= 0, "already mutably borrowed");
+
+        self.borrow.set(borrow + 1);
+
+        Ref {
+            borrow: &self.borrow,
+            ptr: self.value.get(),
+        }
+    }
+
+    /// Mutably borrows the wrapped value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is currently borrowed, or if the borrow races with
+    /// an access from another thread.
+    #[track_caller]
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        assert_eq!(0, self.borrow.get(), "already borrowed");
+
+        self.borrow.set(-1);
+
+        RefMut {
+            borrow: &self.borrow,
+            ptr: self.value.get_mut(),
+        }
+    }
+
+    /// Replaces the wrapped value with a new one, returning the old value.
+    #[track_caller]
+    pub fn replace(&self, value: T) -> T {
+        std::mem::replace(&mut *self.borrow_mut(), value)
+    }
+
+    /// Takes the wrapped value, leaving `Default::default()` in its place.
+    #[track_caller]
+    pub fn take(&self) -> T
+    where
+        T: Default,
+    {
+        self.replace(T::default())
+    }
+
+    /// Consumes the `RefCell`, returning the wrapped value.
+    #[track_caller]
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: Default> Default for RefCell<T> {
+    #[track_caller]
+    fn default() -> RefCell<T> {
+        RefCell::new(T::default())
+    }
+}
+
+impl<T> From<T> for RefCell<T> {
+    #[track_caller]
+    fn from(src: T) -> RefCell<T> {
+        RefCell::new(src)
+    }
+}
+
+impl<T> fmt::Debug for RefCell<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Reading the value would count as an access.
+        fmt.debug_struct("RefCell").finish_non_exhaustive()
+    }
+}
+
+impl<T> Deref for Ref<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the borrow flag rules out a live `RefMut`.
+        unsafe { self.ptr.deref() }
+    }
+}
+
+impl<T> Drop for Ref<'_, T> {
+    fn drop(&mut self) {
+        self.borrow.set(self.borrow.get() - 1);
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Ref<'_, T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(fmt)
+    }
+}
+
+impl<T> Deref for RefMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the borrow flag rules out any other live guard.
+        unsafe { self.ptr.deref() }
+    }
+}
+
+impl<T> DerefMut for RefMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the borrow flag rules out any other live guard.
+        unsafe { self.ptr.deref() }
+    }
+}
+
+impl<T> Drop for RefMut<'_, T> {
+    fn drop(&mut self) {
+        self.borrow.set(0);
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RefMut<'_, T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(fmt)
+    }
+}
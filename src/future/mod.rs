@@ -1,8 +1,11 @@
 //! Future related synchronization primitives.
 
 mod atomic_waker;
+mod mutex;
 
 pub use self::atomic_waker::AtomicWaker;
+pub use self::mutex::{Mutex, MutexGuard};
+pub use std::future::poll_fn;
 
 use crate::rt;
 use crate::sync::Arc;
@@ -25,7 +28,7 @@ where
     let waker = unsafe {
         mem::ManuallyDrop::new(Waker::from_raw(RawWaker::new(
             &*notify as *const _ as *const (),
-            waker_vtable(),
+            &WAKER_VTABLE,
         )))
     };
 
@@ -41,15 +44,51 @@ where
     }
 }
 
-pub(super) fn waker_vtable() -> &'static RawWakerVTable {
-    &RawWakerVTable::new(
-        clone_arc_raw,
-        wake_arc_raw,
-        wake_by_ref_arc_raw,
-        drop_arc_raw,
-    )
+/// Spawn a task that drives `f` to completion.
+///
+/// Each task runs on its own loom thread, so every poll and wake-up is a
+/// scheduling point and loom explores the orders in which tasks are polled.
+/// Tasks count towards [`MAX_THREADS`](crate::MAX_THREADS).
+#[track_caller]
+pub fn spawn<F>(f: F) -> crate::thread::JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    crate::thread::spawn(move || block_on(f))
 }
 
+/// Yields execution back to the caller of `poll`, letting other tasks run.
+///
+/// The first poll wakes the current task and returns `Pending`, and the
+/// yield is a scheduling point, so loom explores other tasks running before
+/// this one is polled again.
+pub async fn yield_now() {
+    let mut yielded = false;
+
+    poll_fn(|cx| {
+        if yielded {
+            return Poll::Ready(());
+        }
+
+        yielded = true;
+        cx.waker().wake_by_ref();
+        crate::thread::yield_now();
+
+        Poll::Pending
+    })
+    .await
+}
+
+/// `Waker::will_wake` compares vtable addresses, so every waker must point at
+/// this one static rather than a promoted constant, which may be duplicated.
+static WAKER_VTABLE: RawWakerVTable = RawWakerVTable::new(
+    clone_arc_raw,
+    wake_arc_raw,
+    wake_by_ref_arc_raw,
+    drop_arc_raw,
+);
+
 unsafe fn increase_refcount(data: *const ()) {
     // Retain Arc, but don't touch refcount by wrapping in ManuallyDrop
     let arc = mem::ManuallyDrop::new(Arc::<rt::Notify>::from_raw(data as *const _));
@@ -59,7 +98,7 @@ unsafe fn increase_refcount(data: *const ()) {
 
 unsafe fn clone_arc_raw(data: *const ()) -> RawWaker {
     increase_refcount(data);
-    RawWaker::new(data, waker_vtable())
+    RawWaker::new(data, &WAKER_VTABLE)
 }
 
 unsafe fn wake_arc_raw(data: *const ()) {
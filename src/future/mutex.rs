@@ -0,0 +1,133 @@
+use crate::sync;
+
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::future::poll_fn;
+use std::ops::{Deref, DerefMut};
+use std::task::{Poll, Waker};
+
+/// An asynchronous mutex, modeled after `tokio::sync::Mutex`.
+///
+/// A task that finds the mutex locked registers its waker and is woken when
+/// the mutex is unlocked. Every waiting task is woken, so loom explores which
+/// one acquires the mutex next.
+pub struct Mutex<T: ?Sized> {
+    /// Unlocking the state makes everything the previous holder did
+    /// happen-before the next acquisition.
+    state: sync::Mutex<State>,
+    data: UnsafeCell<T>,
+}
+
+/// A handle to a held [`Mutex`], releasing it when dropped.
+pub struct MutexGuard<'a, T: ?Sized> {
+    lock: &'a Mutex<T>,
+}
+
+#[derive(Debug)]
+struct State {
+    locked: bool,
+    waiters: Vec<Waker>,
+}
+
+// The data is only accessed while holding the lock.
+unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+unsafe impl<T: ?Sized + Sync> Sync for MutexGuard<'_, T> {}
+
+impl<T> Mutex<T> {
+    /// Creates a new lock in an unlocked state ready for use.
+    #[track_caller]
+    pub fn new(data: T) -> Mutex<T> {
+        Mutex {
+            state: sync::Mutex::new(State {
+                locked: false,
+                waiters: Vec::new(),
+            }),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// Consumes the mutex, returning the underlying data.
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    /// Locks the mutex, waiting until it is available.
+    pub async fn lock(&self) -> MutexGuard<'_, T> {
+        poll_fn(|cx| {
+            let mut state = self.state.lock().unwrap();
+
+            if state.locked {
+                state.waiters.push(cx.waker().clone());
+                Poll::Pending
+            } else {
+                state.locked = true;
+                Poll::Ready(MutexGuard { lock: self })
+            }
+        })
+        .await
+    }
+
+    /// Attempts to acquire the lock without waiting.
+    #[track_caller]
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        let mut state = self.state.lock().unwrap();
+
+        if state.locked {
+            None
+        } else {
+            state.locked = true;
+            Some(MutexGuard { lock: self })
+        }
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for Mutex<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Mutex").finish_non_exhaustive()
+    }
+}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the guard holds the lock.
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the guard holds the lock.
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        let waiters = {
+            let mut state = self.lock.state.lock().unwrap();
+            state.locked = false;
+            std::mem::take(&mut state.waiters)
+        };
+
+        for waiter in waiters {
+            waiter.wake();
+        }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for MutexGuard<'_, T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(fmt)
+    }
+}
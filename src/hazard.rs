@@ -0,0 +1,194 @@
+//! Hazard pointer based memory reclamation.
+//!
+//! A reader publishes the pointer it is about to dereference in its hazard
+//! slot with [`Domain::protect`]. A writer that unlinks a node hands it to
+//! [`Domain::retire`], and [`Domain::reclaim`] frees the retired nodes that
+//! no hazard slot protects.
+//!
+//! Nodes are allocated with [`loom::alloc::alloc`], so loom reports nodes
+//! that are never reclaimed as leaks. Loom also tracks which node each thread
+//! has protected. Freeing a protected node, or protecting a node that was
+//! already freed, fails the model as a use after free. This makes it possible
+//! to check a custom hazard scan with [`Domain::reclaim_with`].
+//!
+//! [`loom::alloc::alloc`]: crate::alloc::alloc
+
+use crate::alloc::{self, Layout};
+use crate::rt;
+use crate::sync::atomic::{fence, AtomicPtr};
+use crate::sync::Mutex;
+
+use std::cell::Cell;
+use std::ptr;
+use std::sync::atomic::Ordering::{Relaxed, SeqCst};
+
+/// A set of hazard slots, one per thread, and the nodes retired against them.
+#[derive(Debug)]
+pub struct Domain<T> {
+    hazards: Vec<AtomicPtr<T>>,
+
+    /// Nodes waiting to be freed.
+    retired: Mutex<Vec<*mut T>>,
+
+    /// Pointer each thread has protected, as tracked by loom. Each entry is
+    /// only accessed by its own thread, so it is not modeled as shared
+    /// memory.
+    protected: Vec<Cell<*mut T>>,
+}
+
+// The domain only hands out pointers; ownership of the nodes moves between
+// threads through `retire`.
+unsafe impl<T: Send> Send for Domain<T> {}
+unsafe impl<T: Send> Sync for Domain<T> {}
+
+/// Allocates a node that can be retired to a [`Domain`].
+///
+/// The node must be freed by [`Domain::reclaim`] or [`free()`].
+#[track_caller]
+pub fn alloc<T>(value: T) -> *mut T {
+    unsafe {
+        let ptr = alloc::alloc(Layout::new::<T>()) as *mut T;
+        assert!(!ptr.is_null(), "allocation failed");
+        ptr.write(value);
+        ptr
+    }
+}
+
+/// Drops and frees a node allocated with [`alloc()`].
+///
+/// # Safety
+///
+/// `ptr` must come from [`alloc()`] and must not be used afterwards.
+#[track_caller]
+pub unsafe fn free<T>(ptr: *mut T) {
+    ptr::drop_in_place(ptr);
+    alloc::dealloc(ptr as *mut u8, Layout::new::<T>());
+}
+
+impl<T> Domain<T> {
+    /// Creates a domain with a hazard slot for each thread of the model.
+    pub fn new() -> Domain<T> {
+        let max_threads = rt::execution(|execution| execution.threads.max());
+
+        Domain {
+            hazards: (0..max_threads)
+                .map(|_| AtomicPtr::new(ptr::null_mut()))
+                .collect(),
+            retired: Mutex::new(Vec::new()),
+            protected: (0..max_threads)
+                .map(|_| Cell::new(ptr::null_mut()))
+                .collect(),
+        }
+    }
+
+    /// Loads `src` and protects the loaded pointer from reclamation.
+    ///
+    /// The pointer is published in the current thread's hazard slot, then
+    /// `src` is loaded again to check the pointer was not unlinked in the
+    /// meantime. The returned pointer stays protected until the next call to
+    /// `protect` or [`clear`](Domain::clear) on this thread.
+    #[track_caller]
+    pub fn protect(&self, src: &AtomicPtr<T>) -> *mut T {
+        self.untrack();
+
+        let hazard = &self.hazards[current()];
+        let mut ptr = src.load(Relaxed);
+
+        loop {
+            hazard.store(ptr, Relaxed);
+            fence(SeqCst);
+
+            let validated = src.load(Relaxed);
+
+            if validated == ptr {
+                if !ptr.is_null() {
+                    rt::protect(ptr as *mut u8, true, location!());
+                }
+
+                self.protected[current()].set(ptr);
+                return ptr;
+            }
+
+            ptr = validated;
+        }
+    }
+
+    /// Clears the current thread's hazard slot.
+    #[track_caller]
+    pub fn clear(&self) {
+        self.untrack();
+        self.hazards[current()].store(ptr::null_mut(), Relaxed);
+    }
+
+    /// Hands an unlinked node over to the domain to be freed once it is no
+    /// longer protected.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must come from [`alloc()`], must no longer be reachable by threads
+    /// that did not protect it, and must not be retired twice.
+    #[track_caller]
+    pub unsafe fn retire(&self, ptr: *mut T) {
+        self.retired.lock().unwrap().push(ptr);
+    }
+
+    /// Frees every retired node that no hazard slot protects.
+    #[track_caller]
+    pub fn reclaim(&self) {
+        self.reclaim_with(|hazards| hazards.iter().map(|hazard| hazard.load(Relaxed)).collect());
+    }
+
+    /// Frees every retired node not returned by `scan`.
+    ///
+    /// `scan` receives the hazard slots of all threads and returns the
+    /// pointers they protect. It runs after a `SeqCst` fence. If it misses a
+    /// protected pointer and that node gets freed, the model fails.
+    #[track_caller]
+    pub fn reclaim_with<F>(&self, scan: F)
+    where
+        F: FnOnce(&[AtomicPtr<T>]) -> Vec<*mut T>,
+    {
+        fence(SeqCst);
+
+        let hazards = scan(&self.hazards);
+
+        let unprotected: Vec<_> = {
+            let mut retired = self.retired.lock().unwrap();
+            let (protected, unprotected) = retired.drain(..).partition(|ptr| hazards.contains(ptr));
+            *retired = protected;
+            unprotected
+        };
+
+        for ptr in unprotected {
+            unsafe { free(ptr) };
+        }
+    }
+
+    /// Stops tracking the pointer the current thread protected last.
+    #[track_caller]
+    fn untrack(&self) {
+        let prev = self.protected[current()].replace(ptr::null_mut());
+
+        if !prev.is_null() {
+            rt::protect(prev as *mut u8, false, location!());
+        }
+    }
+}
+
+impl<T> Default for Domain<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Domain<T> {
+    fn drop(&mut self) {
+        for ptr in self.retired.get_mut().unwrap().drain(..) {
+            unsafe { free(ptr) };
+        }
+    }
+}
+
+fn current() -> usize {
+    rt::execution(|execution| execution.threads.active_id().as_usize())
+}
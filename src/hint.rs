@@ -2,13 +2,21 @@
 
 /// Signals the processor that it is entering a busy-wait spin-loop.
 ///
-/// For loom, this is an alias of [`yield_now`] but is provided as a reflection
-/// of the [`core::hint::spin_loop`] function. See the [`yield_now`]
+/// For loom, this behaves like [`yield_now`], letting other threads run
+/// before the loop checks its condition again. See the [`yield_now`]
 /// documentation for more information on what effect this has.
 ///
+/// A thread that spins more than
+/// [`Builder::max_spins`](crate::model::Builder::max_spins) times in a row
+/// without progress fails the model with
+/// [`LoomError::Livelock`](crate::model::LoomError::Livelock). Reading a new
+/// atomic value or operating on another synchronization object counts as
+/// progress, unless every other thread is blocked or terminated.
+///
 /// [`yield_now`]: crate::thread::yield_now
+#[track_caller]
 pub fn spin_loop() {
-    crate::sync::atomic::spin_loop_hint();
+    crate::rt::spin_loop(location!());
 }
 
 /// Informs the compiler that this point in the code is not reachable, enabling
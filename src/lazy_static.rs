@@ -43,18 +43,27 @@ impl<T: 'static> Lazy<T> {
         match unsafe { self.try_get() } {
             Some(v) => v,
             None => {
-                // Init the value out of the `rt::execution`
-                let sv = crate::rt::lazy_static::StaticValue::new((self.init)());
+                // Like `std::sync::Once`, initializers are serialized: the
+                // first thread runs `init` while the others wait for it.
+                let lock = rt::execution(|execution| execution.lazy_statics.init_lock(self));
+                let lock = lock.unwrap_or_else(|| {
+                    let lock = rt::Mutex::new(false);
+                    rt::execution(|execution| execution.lazy_statics.set_init_lock(self, lock));
+                    lock
+                });
+
+                lock.acquire_lock(location!());
 
-                // While calling init, we may have yielded to the scheduler, in which case some
-                // _other_ thread may have initialized the static. The real lazy_static does not
-                // have this issue, since it takes a lock before initializing the new value, and
-                // readers wait on that lock if they encounter it. We could implement that here
-                // too, but for simplicity's sake, we just do another try_get here for now.
+                // Another thread may have initialized the static while this
+                // one was waiting.
                 if let Some(v) = unsafe { self.try_get() } {
+                    lock.release_lock();
                     return v;
                 }
 
+                // Init the value out of the `rt::execution`
+                let sv = crate::rt::lazy_static::StaticValue::new((self.init)());
+
                 rt::execution(|execution| {
                     let sv = execution.lazy_statics.init_static(self, sv);
 
@@ -62,6 +71,8 @@ impl<T: 'static> Lazy<T> {
                     sv.sync.sync_store(&mut execution.threads, Ordering::AcqRel);
                 });
 
+                lock.release_lock();
+
                 unsafe { self.try_get() }.expect("bug")
             }
         }
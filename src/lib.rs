@@ -291,6 +291,14 @@
 //! LOOM_MAX_PREEMPTIONS=3 RUSTFLAGS="--cfg loom" cargo test --test loom_my_struct --release
 //! ```
 //!
+//! For lock-based code, exploration can be narrowed further by only preempting threads at
+//! synchronization points such as locks, condition variables and channels, treating the code
+//! between them (including atomic operations) as if it ran uninterrupted. This is enabled with
+//! the `LOOM_SYNC_POINTS_ONLY` environment variable (or
+//! [`Builder::sync_points_only`](model::Builder::sync_points_only)). Note that this is **not**
+//! sound for code relying on atomics, so a full exploration should still be run before
+//! considering the code correct.
+//!
 //! ## Relaxed Memory Ordering
 //!
 //! The [`Relaxed` memory ordering](std::sync::atomic::Ordering::Relaxed) allows particularly
@@ -372,8 +380,10 @@ pub use rt::MAX_THREADS;
 
 pub mod alloc;
 pub mod cell;
+pub mod hazard;
 pub mod hint;
 pub mod lazy_static;
+pub mod litmus;
 pub mod model;
 pub mod sync;
 pub mod thread;
@@ -0,0 +1,69 @@
+//! Helpers for writing memory model litmus tests.
+//!
+//! A litmus test runs a few short threads against shared atomics and checks
+//! which combinations of values the threads may observe. For example, the
+//! store buffering test allows both loads to miss the other thread's store
+//! when all accesses are `Relaxed`:
+//!
+//! ```
+//! use loom::sync::atomic::AtomicUsize;
+//! use std::sync::atomic::Ordering::Relaxed;
+//!
+//! loom::litmus::two_thread(
+//!     |(x, y): &(AtomicUsize, AtomicUsize)| {
+//!         x.store(1, Relaxed);
+//!         y.load(Relaxed)
+//!     },
+//!     |(x, y)| {
+//!         y.store(1, Relaxed);
+//!         x.load(Relaxed)
+//!     },
+//!     |outcomes| assert!(outcomes.contains(&(0, 0))),
+//! );
+//! ```
+
+use crate::sync::Arc;
+use crate::thread;
+
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::sync::Mutex;
+
+/// Runs `thread_a` and `thread_b` concurrently in every interleaving loom
+/// explores, then passes the set of observed `(a, b)` results to `check`.
+///
+/// Both threads share a state of type `S`, created with `S::default()` at
+/// the start of each execution. Tuples of atomics work well as state.
+pub fn two_thread<S, A, B, RA, RB, C>(thread_a: A, thread_b: B, check: C)
+where
+    S: Default + Send + Sync + 'static,
+    A: Fn(&S) -> RA + Send + Sync + 'static,
+    B: Fn(&S) -> RB + Send + Sync + 'static,
+    RA: Eq + Hash + Send + 'static,
+    RB: Eq + Hash + Send + 'static,
+    C: FnOnce(&HashSet<(RA, RB)>),
+{
+    let outcomes = std::sync::Arc::new(Mutex::new(HashSet::new()));
+    let thread_a = std::sync::Arc::new(thread_a);
+
+    {
+        let outcomes = outcomes.clone();
+
+        crate::model(move || {
+            let state = Arc::new(S::default());
+
+            let th = {
+                let state = state.clone();
+                let thread_a = thread_a.clone();
+                thread::spawn(move || thread_a(&state))
+            };
+
+            let b = thread_b(&state);
+            let a = th.join().unwrap();
+
+            outcomes.lock().unwrap().insert((a, b));
+        });
+    }
+
+    check(&outcomes.lock().unwrap());
+}
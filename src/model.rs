@@ -1,16 +1,29 @@
 //! Model concurrent programs.
 
-use crate::rt::{self, Execution, Scheduler};
+mod error;
+
+pub use self::error::{Failure, LoomError};
+
+use crate::rt::{self, Choice, Execution, Scheduler};
+use std::any::Any;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::Relaxed;
 use std::sync::Arc;
+use std::thread;
 use std::time::{Duration, Instant};
 
 use tracing::{info, subscriber};
-use tracing_subscriber::{fmt, EnvFilter};
+use tracing_subscriber::EnvFilter;
 
 const DEFAULT_MAX_THREADS: usize = 5;
 const DEFAULT_MAX_BRANCHES: usize = 1_000;
 
+/// Maximum number of variants replayed while shrinking a failure.
+const MAX_SHRINK_REPLAYS: usize = 1_000;
+
 /// Configure a model
 #[derive(Debug)]
 #[non_exhaustive] // Support adding more fields in the future
@@ -26,11 +39,59 @@ pub struct Builder {
     /// Defaults to `LOOM_MAX_BRANCHES` environment variable.
     pub max_branches: usize,
 
+    /// Number of most recent stores to an atomic that a load may read from.
+    ///
+    /// Lowering this prunes executions where loads read stale values. It must
+    /// be at least 1 and at most 7, the number of stores loom tracks per
+    /// atomic.
+    ///
+    /// Defaults to `LOOM_MAX_HISTORY` environment variable.
+    pub max_history: usize,
+
+    /// Number of consecutive [`hint::spin_loop`](crate::hint::spin_loop)
+    /// calls without progress after which a thread is reported as
+    /// livelocked.
+    ///
+    /// This catches spin loops waiting on a condition that the explored
+    /// interleaving never satisfies, which would otherwise run until
+    /// `max_branches` is exceeded. `thread::yield_now` is not checked.
+    ///
+    /// Defaults to `LOOM_MAX_SPINS` environment variable.
+    pub max_spins: usize,
+
+    /// Maximum number of loom objects (atomics, mutexes, cells, ...) a single
+    /// permutation may create.
+    ///
+    /// Exceeding the bound fails the check. This catches models that create
+    /// objects in an unbounded loop before they exhaust memory.
+    ///
+    /// Defaults to `LOOM_MAX_OBJECTS` environment variable.
+    pub max_objects: Option<usize>,
+
+    /// Maximum number of loom operations a single permutation may perform,
+    /// across all threads.
+    ///
+    /// Exceeding the bound fails the check with a message pointing at an
+    /// infinite loop in the model. Every operation is also a branch, so set
+    /// this below `max_branches` when raising that limit for a model that
+    /// legitimately runs long permutations.
+    ///
+    /// Defaults to `LOOM_MAX_OPERATIONS` environment variable.
+    pub max_operations: Option<usize>,
+
     /// Maximum number of permutations to explore.
     ///
     /// Defaults to `LOOM_MAX_PERMUTATIONS` environment variable.
     pub max_permutations: Option<usize>,
 
+    /// Fail the check if the model has more than this many permutations.
+    ///
+    /// Unlike `max_permutations`, which silently stops the exploration, this
+    /// guards against a change that accidentally blows up the state space.
+    ///
+    /// Defaults to `LOOM_ASSERT_MAX_PERMUTATIONS` environment variable.
+    pub assert_max_permutations: Option<usize>,
+
     /// Maximum amount of time to spend on checking
     ///
     /// Defaults to `LOOM_MAX_DURATION` environment variable.
@@ -44,6 +105,9 @@ pub struct Builder {
     /// When doing an exhaustive check, uses the file to store and load the
     /// check progress
     ///
+    /// If the check fails, the failing permutation is stored instead, so the
+    /// next run starts by replaying it.
+    ///
     /// Defaults to `LOOM_CHECKPOINT_FILE` environment variable.
     pub checkpoint_file: Option<PathBuf>,
 
@@ -52,6 +116,20 @@ pub struct Builder {
     /// Defaults to `LOOM_CHECKPOINT_INTERVAL` environment variable.
     pub checkpoint_interval: usize,
 
+    /// When set, the happens-before graph of the last explored permutation
+    /// is written to the file as JSON. If the check fails, this is the
+    /// failing permutation.
+    ///
+    /// The graph lists every synchronization edge: which thread's prior
+    /// operations became visible to which thread, through what (an acquire,
+    /// an unpark, a spawn, ...) and, if `location` is set, at which call site.
+    /// An acquire from an atomic also names the store it read, by object
+    /// index and store index. Recording the edges is slow, and exporting them
+    /// requires the `checkpoint` feature.
+    ///
+    /// Defaults to `LOOM_HAPPENS_BEFORE_GRAPH` environment variable.
+    pub happens_before_graph: Option<PathBuf>,
+
     /// When `true` loom won't start state exploration until `explore_state` is
     /// called.
     pub expect_explicit_explore: bool,
@@ -69,6 +147,188 @@ pub struct Builder {
     ///
     /// Defaults to existence of `LOOM_LOG` environment variable.
     pub log: bool,
+
+    /// When `true`, threads are only preempted at synchronization points
+    /// (mutexes, condition variables, channels, joins, ...) and never at
+    /// atomic operations.
+    ///
+    /// Code between two synchronization points then runs without being
+    /// interrupted, which greatly reduces the number of permutations to
+    /// explore. This is **unsound** for code relying on atomics: bugs that
+    /// require another thread to run between two atomic operations will not
+    /// be found. It is meant for quickly checking lock-based code before
+    /// running a full exploration.
+    ///
+    /// Defaults to existence of `LOOM_SYNC_POINTS_ONLY` environment variable.
+    pub sync_points_only: bool,
+
+    /// When `true`, a `with_mut` call on an atomic that does not happen-after
+    /// every other access to it is logged as a warning instead of failing the
+    /// check. The execution then continues, which helps when diagnosing
+    /// several such accesses at once.
+    ///
+    /// Defaults to existence of `LOOM_LENIENT_WITH_MUT` environment variable.
+    pub lenient_with_mut: bool,
+
+    /// When `true`, `thread::park` may return without the thread having been
+    /// unparked, as `std::thread::park` is allowed to. The same goes for
+    /// `Condvar::wait`. Code must then re-check its wake-up condition in a
+    /// loop.
+    ///
+    /// At most one spurious return from each of `park` and `Condvar::wait` is
+    /// explored per thread and execution.
+    ///
+    /// Defaults to existence of `LOOM_SPURIOUS_PARK` environment variable.
+    pub spurious_park: bool,
+
+    /// When `true`, every `SeqCst` load is checked against a single total
+    /// order of `SeqCst` operations, the order in which the permutation ran
+    /// them. A load that reads a store older than the last `SeqCst` store to
+    /// the same atomic fails the check.
+    ///
+    /// This is a debugging aid for loom's memory model. Models that only use
+    /// loom's atomics never fail it.
+    ///
+    /// Defaults to existence of `LOOM_CHECK_SEQ_CST` environment variable.
+    pub check_seq_cst: bool,
+
+    /// When set, atomics follow a TSO-like memory model, as on x86, with
+    /// store buffers of the given depth.
+    ///
+    /// `Relaxed` loads then act as `Acquire`, `Relaxed` stores as `Release`
+    /// and read-modify-writes as `SeqCst`. Each thread buffers up to `depth`
+    /// stores per atomic, so a load may read an older store only while every
+    /// thread wrote at most `depth` of the newer ones. Once another thread
+    /// read a newer store, it has left the buffer and older stores can no
+    /// longer be read. With a depth of 0, every load reads the most recent
+    /// store.
+    ///
+    /// Defaults to `LOOM_STORE_BUFFER_DEPTH` environment variable.
+    pub store_buffer_depth: Option<usize>,
+
+    /// When `true`, loads and read-modify-writes with acquire semantics
+    /// (`Acquire`, `AcqRel` or `SeqCst`) that never synchronized with a store
+    /// that did not already happen-before the loading thread are logged as
+    /// warnings once the model completes. Their ordering could drop acquire
+    /// semantics as far as the explored executions are concerned.
+    ///
+    /// This implies `location`, which makes checking slower.
+    ///
+    /// Defaults to existence of `LOOM_REPORT_REDUNDANT_SYNC` environment
+    /// variable.
+    pub report_redundant_sync: bool,
+
+    /// When set, runs this many executions with randomly picked thread
+    /// switches and atomic load values instead of exploring every
+    /// permutation.
+    ///
+    /// This does not prove the model correct, but scales to models too large
+    /// for an exhaustive check. On failure, the seed is printed to stderr so
+    /// the run can be reproduced with `random_seed`.
+    ///
+    /// Defaults to `LOOM_RANDOM_ITERATIONS` environment variable.
+    pub random_iterations: Option<usize>,
+
+    /// Seed for `random_iterations`. When unset, a seed is derived from the
+    /// current time.
+    ///
+    /// Defaults to `LOOM_RANDOM_SEED` environment variable.
+    pub random_seed: Option<u64>,
+
+    /// Number of OS threads exploring the model in parallel.
+    ///
+    /// The choices at the first point where several threads may run are
+    /// split between the workers, each exploring everything below its share.
+    /// The first failure stops every worker. A parallel run may explore more
+    /// permutations than a serial one, as only a serial run can prune
+    /// choices at that point. Limits such as `max_permutations` apply to
+    /// each worker.
+    ///
+    /// Cannot be combined with `checkpoint_file` or `random_iterations`.
+    ///
+    /// Defaults to `LOOM_WORKER_THREADS` environment variable, or 1.
+    pub worker_threads: usize,
+
+    /// When `true`, a thread is not scheduled where running it would only
+    /// reorder independent operations of a permutation already explored.
+    /// Operations are independent when they touch different objects, or are
+    /// loads of the same atomic.
+    ///
+    /// Every outcome is still reached, through fewer permutations. Has no
+    /// effect with `preemption_bound` or `random_iterations`.
+    ///
+    /// Defaults to existence of `LOOM_SLEEP_SETS` environment variable.
+    pub sleep_sets: bool,
+
+    /// When `true`, a failing permutation is simplified before it is
+    /// reported. Variants of it with fewer preemptions, thread switches or
+    /// non-default load values are replayed, and the simplest one that still
+    /// fails with the same kind of error and the same first line of its
+    /// message is reported instead.
+    ///
+    /// The model runs again for each variant, and each failing variant panics
+    /// through the panic hook. Shrinking stops after 1000 variants, or once
+    /// `max_duration` has elapsed since the check started.
+    ///
+    /// Defaults to existence of `LOOM_SHRINK` environment variable.
+    pub shrink: bool,
+
+    /// When `true`, the thread scheduled at each step of the failing
+    /// permutation, the operation it was about to run and the values read by
+    /// atomic loads are printed to stderr once the check fails.
+    ///
+    /// Set `location` as well to include the call site of each operation.
+    ///
+    /// Defaults to existence of `LOOM_TRACE` environment variable.
+    pub trace: bool,
+
+    /// Called after each permutation that completes without failing.
+    on_iteration: Option<OnIteration>,
+}
+
+/// Progress of a check, passed to [`Builder::on_iteration`] and returned by
+/// [`Builder::check_counting`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Stats {
+    /// Number of permutations explored so far, including the one that just
+    /// completed.
+    pub iteration: usize,
+
+    /// Total number of branches taken by the permutations explored so far.
+    pub branches: usize,
+
+    /// Largest number of branches taken by a single permutation so far.
+    pub max_depth: usize,
+
+    /// Number of `branches` that picked the thread to run next.
+    pub schedule_branches: usize,
+
+    /// Number of `branches` that picked the store an atomic load reads. The
+    /// remaining branches picked whether a wait returns spuriously.
+    pub load_branches: usize,
+
+    /// Deepest nesting of critical sections, opened with
+    /// [`stop_exploring`](crate::stop_exploring), reached so far. A
+    /// surprisingly deep nesting hides preemptions that could expose bugs.
+    pub max_critical_depth: usize,
+
+    /// Largest number of loom operations each thread performed in a single
+    /// permutation so far, indexed by thread with the main thread first.
+    /// This shows the shape of the model, e.g. whether a producer and a
+    /// consumer do balanced work.
+    pub max_thread_operations: Vec<usize>,
+
+    /// Time spent on the check so far.
+    pub elapsed: Duration,
+}
+
+struct OnIteration(Box<dyn Fn(&Stats) + Send + Sync>);
+
+impl fmt::Debug for OnIteration {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("OnIteration")
+    }
 }
 
 impl Builder {
@@ -87,10 +347,57 @@ impl Builder {
             .map(|v| v.parse().expect("invalid value for `LOOM_MAX_BRANCHES`"))
             .unwrap_or(DEFAULT_MAX_BRANCHES);
 
+        let max_history = env::var("LOOM_MAX_HISTORY")
+            .map(|v| v.parse().expect("invalid value for `LOOM_MAX_HISTORY`"))
+            .unwrap_or(rt::MAX_ATOMIC_HISTORY);
+        check_max_history(max_history);
+
+        let max_spins = env::var("LOOM_MAX_SPINS")
+            .map(|v| v.parse().expect("invalid value for `LOOM_MAX_SPINS`"))
+            .unwrap_or(rt::MAX_SPINS);
+
         let location = env::var("LOOM_LOCATION").is_ok();
 
         let log = env::var("LOOM_LOG").is_ok();
 
+        let sync_points_only = env::var("LOOM_SYNC_POINTS_ONLY").is_ok();
+
+        let lenient_with_mut = env::var("LOOM_LENIENT_WITH_MUT").is_ok();
+
+        let spurious_park = env::var("LOOM_SPURIOUS_PARK").is_ok();
+
+        let check_seq_cst = env::var("LOOM_CHECK_SEQ_CST").is_ok();
+
+        let store_buffer_depth = env::var("LOOM_STORE_BUFFER_DEPTH")
+            .map(|v| {
+                v.parse()
+                    .expect("invalid value for `LOOM_STORE_BUFFER_DEPTH`")
+            })
+            .ok();
+
+        let report_redundant_sync = env::var("LOOM_REPORT_REDUNDANT_SYNC").is_ok();
+
+        let sleep_sets = env::var("LOOM_SLEEP_SETS").is_ok();
+
+        let shrink = env::var("LOOM_SHRINK").is_ok();
+
+        let trace = env::var("LOOM_TRACE").is_ok();
+
+        let random_iterations = env::var("LOOM_RANDOM_ITERATIONS")
+            .map(|v| {
+                v.parse()
+                    .expect("invalid value for `LOOM_RANDOM_ITERATIONS`")
+            })
+            .ok();
+
+        let random_seed = env::var("LOOM_RANDOM_SEED")
+            .map(|v| v.parse().expect("invalid value for `LOOM_RANDOM_SEED`"))
+            .ok();
+
+        let worker_threads = env::var("LOOM_WORKER_THREADS")
+            .map(|v| v.parse().expect("invalid value for `LOOM_WORKER_THREADS`"))
+            .unwrap_or(1);
+
         let max_duration = env::var("LOOM_MAX_DURATION")
             .map(|v| {
                 let secs = v.parse().expect("invalid value for `LOOM_MAX_DURATION`");
@@ -98,6 +405,14 @@ impl Builder {
             })
             .ok();
 
+        let max_objects = env::var("LOOM_MAX_OBJECTS")
+            .map(|v| v.parse().expect("invalid value for `LOOM_MAX_OBJECTS`"))
+            .ok();
+
+        let max_operations = env::var("LOOM_MAX_OPERATIONS")
+            .map(|v| v.parse().expect("invalid value for `LOOM_MAX_OPERATIONS`"))
+            .ok();
+
         let max_permutations = env::var("LOOM_MAX_PERMUTATIONS")
             .map(|v| {
                 v.parse()
@@ -105,6 +420,13 @@ impl Builder {
             })
             .ok();
 
+        let assert_max_permutations = env::var("LOOM_ASSERT_MAX_PERMUTATIONS")
+            .map(|v| {
+                v.parse()
+                    .expect("invalid value for `LOOM_ASSERT_MAX_PERMUTATIONS`")
+            })
+            .ok();
+
         let preemption_bound = env::var("LOOM_MAX_PREEMPTIONS")
             .map(|v| v.parse().expect("invalid value for `LOOM_MAX_PREEMPTIONS`"))
             .ok();
@@ -113,40 +435,278 @@ impl Builder {
             .map(|v| v.parse().expect("invalid value for `LOOM_CHECKPOINT_FILE`"))
             .ok();
 
+        let happens_before_graph = env::var("LOOM_HAPPENS_BEFORE_GRAPH")
+            .map(|v| {
+                v.parse()
+                    .expect("invalid value for `LOOM_HAPPENS_BEFORE_GRAPH`")
+            })
+            .ok();
+
         Builder {
             max_threads: DEFAULT_MAX_THREADS,
             max_branches,
+            max_history,
+            max_spins,
+            max_objects,
+            max_operations,
             max_duration,
             max_permutations,
+            assert_max_permutations,
             preemption_bound,
             checkpoint_file,
             checkpoint_interval,
+            happens_before_graph,
             expect_explicit_explore: false,
             location,
             log,
+            sync_points_only,
+            lenient_with_mut,
+            spurious_park,
+            check_seq_cst,
+            store_buffer_depth,
+            report_redundant_sync,
+            random_iterations,
+            random_seed,
+            worker_threads,
+            sleep_sets,
+            shrink,
+            trace,
+            on_iteration: None,
         }
     }
 
+    /// Set the maximum number of threads.
+    pub fn max_threads(&mut self, max_threads: usize) -> &mut Self {
+        assert!(
+            max_threads <= rt::MAX_THREADS,
+            "max_threads must be at most {}",
+            rt::MAX_THREADS
+        );
+
+        self.max_threads = max_threads;
+        self
+    }
+
+    /// Set the maximum number of branches per permutation.
+    pub fn max_branches(&mut self, max_branches: usize) -> &mut Self {
+        self.max_branches = max_branches;
+        self
+    }
+
+    /// Set the maximum number of thread preemptions to explore.
+    pub fn max_preemptions(&mut self, max_preemptions: usize) -> &mut Self {
+        self.preemption_bound = Some(max_preemptions);
+        self
+    }
+
+    /// Set the number of most recent stores an atomic load may read from.
+    pub fn max_history(&mut self, max_history: usize) -> &mut Self {
+        check_max_history(max_history);
+        self.max_history = max_history;
+        self
+    }
+
+    /// Set the number of consecutive `spin_loop` calls without progress after
+    /// which a thread is reported as livelocked.
+    pub fn max_spins(&mut self, max_spins: usize) -> &mut Self {
+        self.max_spins = max_spins;
+        self
+    }
+
+    /// Let `thread::park` and `Condvar::wait` return spuriously. See
+    /// [`spurious_park`](Builder::spurious_park).
+    pub fn spurious_wakeups(&mut self, enabled: bool) -> &mut Self {
+        self.spurious_park = enabled;
+        self
+    }
+
     /// Set the checkpoint file.
     pub fn checkpoint_file(&mut self, file: &str) -> &mut Self {
         self.checkpoint_file = Some(file.into());
         self
     }
 
+    /// Fail the check if the model has more than `max` permutations.
+    pub fn assert_max_permutations(&mut self, max: usize) -> &mut Self {
+        self.assert_max_permutations = Some(max);
+        self
+    }
+
+    /// Run `iterations` random executions instead of exploring every
+    /// permutation.
+    pub fn random_iterations(&mut self, iterations: usize) -> &mut Self {
+        self.random_iterations = Some(iterations);
+        self
+    }
+
+    /// Set the seed used by `random_iterations`.
+    pub fn random_seed(&mut self, seed: u64) -> &mut Self {
+        self.random_seed = Some(seed);
+        self
+    }
+
+    /// Explore the model on `threads` OS threads in parallel. See
+    /// [`worker_threads`](Builder::worker_threads).
+    pub fn threads(&mut self, threads: usize) -> &mut Self {
+        assert!(threads > 0, "threads must be at least 1");
+
+        self.worker_threads = threads;
+        self
+    }
+
+    /// Register a callback invoked after each permutation that completes
+    /// without failing, e.g. to log progress.
+    ///
+    /// The callback only observes the check. To abort it early, panic.
+    pub fn on_iteration<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(&Stats) + Send + Sync + 'static,
+    {
+        self.on_iteration = Some(OnIteration(Box::new(f)));
+        self
+    }
+
     /// Check the provided model.
+    ///
+    /// Panics on the first failure found.
     pub fn check<F>(&self, f: F)
+    where
+        F: Fn() + Sync + Send + 'static,
+    {
+        self.check_counting(f);
+    }
+
+    /// Check the provided model like [`check`](Builder::check), returning
+    /// statistics about the exploration.
+    ///
+    /// Asserting on [`Stats::iteration`] catches changes that unexpectedly
+    /// grow or shrink the explored state space.
+    pub fn check_counting<F>(&self, f: F) -> Stats
+    where
+        F: Fn() + Sync + Send + 'static,
+    {
+        match self.run(f) {
+            Ok(stats) => stats,
+            Err((payload, _)) => {
+                LoomError::take();
+                panic::resume_unwind(payload);
+            }
+        }
+    }
+
+    /// Check the provided model, returning the first failure found instead of
+    /// panicking.
+    ///
+    /// Failures are still printed by the panic hook as they happen.
+    pub fn try_check<F>(&self, f: F) -> Result<(), LoomError>
+    where
+        F: Fn() + Sync + Send + 'static,
+    {
+        self.run(f)
+            .map(|_| ())
+            .map_err(|(payload, schedule)| LoomError::from_panic(&*payload, schedule))
+    }
+
+    /// Explore the model, returning the panic of the first failing execution
+    /// along with the schedule that led to it.
+    fn run<F>(&self, f: F) -> Result<Stats, (Box<dyn Any + Send>, Vec<usize>)>
+    where
+        F: Fn() + Sync + Send + 'static,
+    {
+        let f = Arc::new(f);
+        let cancelled = AtomicBool::new(false);
+
+        if self.worker_threads <= 1 {
+            return self.explore(&f, None, &cancelled);
+        }
+
+        assert!(
+            self.checkpoint_file.is_none() && self.random_iterations.is_none(),
+            "`worker_threads` cannot be combined with `checkpoint_file` or `random_iterations`"
+        );
+
+        let start = Instant::now();
+        let dispatch = tracing::dispatcher::get_default(Clone::clone);
+
+        let results: Vec<_> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.worker_threads)
+                .map(|worker| {
+                    let (f, cancelled, dispatch) = (&f, &cancelled, &dispatch);
+
+                    scope.spawn(move || {
+                        tracing::dispatcher::with_default(dispatch, || {
+                            let partition = Some((worker, self.worker_threads));
+                            let result = self.explore(f, partition, cancelled);
+
+                            if result.is_err() {
+                                cancelled.store(true, Relaxed);
+                            }
+
+                            // The error raised by the runtime is recorded on
+                            // the worker thread.
+                            (result, LoomError::take())
+                        })
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+                .collect()
+        });
+
+        let mut stats = Stats {
+            iteration: 0,
+            branches: 0,
+            max_depth: 0,
+            schedule_branches: 0,
+            load_branches: 0,
+            max_critical_depth: 0,
+            max_thread_operations: vec![],
+            elapsed: start.elapsed(),
+        };
+
+        for (result, error) in results {
+            match result {
+                Ok(worker) => {
+                    stats.iteration += worker.iteration;
+                    stats.branches += worker.branches;
+                    stats.max_depth = stats.max_depth.max(worker.max_depth);
+                    stats.schedule_branches += worker.schedule_branches;
+                    stats.load_branches += worker.load_branches;
+                    stats.max_critical_depth =
+                        stats.max_critical_depth.max(worker.max_critical_depth);
+                    max_each(
+                        &mut stats.max_thread_operations,
+                        &worker.max_thread_operations,
+                    );
+                }
+                Err(failure) => {
+                    LoomError::restore(error);
+                    return Err(failure);
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Explore the share of the model given by `partition`, as `(worker,
+    /// workers)`, or all of it. Stops early once `cancelled` is set.
+    fn explore<F>(
+        &self,
+        f: &Arc<F>,
+        partition: Option<(usize, usize)>,
+        cancelled: &AtomicBool,
+    ) -> Result<Stats, (Box<dyn Any + Send>, Vec<usize>)>
     where
         F: Fn() + Sync + Send + 'static,
     {
         let mut i = 1;
         let mut _span = tracing::info_span!("iter", message = i).entered();
 
-        let mut execution = Execution::new(
-            self.max_threads,
-            self.max_branches,
-            self.preemption_bound,
-            !self.expect_explicit_explore,
-        );
+        let mut execution = self.execution(self.preemption_bound);
         let mut scheduler = Scheduler::new(self.max_threads);
 
         if let Some(ref path) = self.checkpoint_file {
@@ -156,13 +716,47 @@ impl Builder {
             }
         }
 
-        execution.log = self.log;
-        execution.location = self.location;
+        let seed = self.random_iterations.map(|iterations| {
+            let seed = self.random_seed.unwrap_or_else(|| {
+                use std::time::SystemTime;
 
-        let f = Arc::new(f);
+                SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .map(|elapsed| elapsed.as_nanos() as u64)
+                    .unwrap_or_default()
+            });
+
+            info!(parent: None, "Running {} random iterations; seed = {}", iterations, seed);
+            execution.path.set_random(iterations, seed);
+            seed
+        });
+
+        if let Some((worker, workers)) = partition {
+            execution.path.set_partition(worker, workers);
+        }
+
+        if self.sleep_sets {
+            execution.path.set_sleep_sets();
+        }
 
         let start = Instant::now();
+        let mut stats = Stats {
+            iteration: 0,
+            branches: 0,
+            max_depth: 0,
+            schedule_branches: 0,
+            load_branches: 0,
+            max_critical_depth: 0,
+            max_thread_operations: vec![],
+            elapsed: Duration::ZERO,
+        };
         loop {
+            if cancelled.load(Relaxed) {
+                // Another worker failed.
+                stats.elapsed = start.elapsed();
+                return Ok(stats);
+            }
+
             if i % self.checkpoint_interval == 0 {
                 info!(parent: None, "");
                 info!(
@@ -177,46 +771,275 @@ impl Builder {
 
                 if let Some(max_permutations) = self.max_permutations {
                     if i >= max_permutations {
-                        return;
+                        stats.elapsed = start.elapsed();
+                        return Ok(stats);
                     }
                 }
 
                 if let Some(max_duration) = self.max_duration {
                     if start.elapsed() >= max_duration {
-                        return;
+                        stats.elapsed = start.elapsed();
+                        return Ok(stats);
                     }
                 }
             }
 
-            let f = f.clone();
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                run_execution(&mut scheduler, &mut execution, f);
 
-            scheduler.run(&mut execution, move || {
-                f();
+                if let Some(max) = self.assert_max_permutations {
+                    if i > max {
+                        LoomError::PermutationLimitExceeded(Failure::new(format!(
+                            "Model explored {} permutations, more than the expected \
+                             maximum of {}.",
+                            i, max
+                        )))
+                        .raise();
+                    }
+                }
+            }));
 
-                let lazy_statics = rt::execution(|execution| execution.lazy_statics.drop());
+            if let Err(mut payload) = result {
+                if let Some(seed) = seed {
+                    eprintln!("Random iteration {} failed; seed = {}", i, seed);
+                }
 
-                // drop outside of execution
-                drop(lazy_statics);
+                if self.shrink {
+                    (execution, payload) =
+                        self.shrink(f, &mut scheduler, execution, payload, start);
+                }
 
-                rt::thread_done();
-            });
+                let graph = &execution.threads.happens_before;
+                if let (Some(graph), Some(path)) = (graph, &self.happens_before_graph) {
+                    checkpoint::store_happens_before_graph(graph, path);
+                }
 
-            execution.check_for_leaks();
+                if let Some(trace) = &execution.trace {
+                    eprint!("{}", trace);
+                }
+
+                let schedule = execution.path.schedule_trace();
+
+                if let Some(ref path) = self.checkpoint_file {
+                    execution.path.rewind();
+                    checkpoint::store_execution_path(&execution.path, path);
+                }
+
+                return Err((payload, schedule));
+            }
+
+            // A run below an empty share of a parallel split belongs to
+            // another worker, and is not counted.
+            if !execution.path.is_idle() {
+                stats.iteration = i;
+                stats.branches += execution.path.pos();
+                stats.max_depth = stats.max_depth.max(execution.path.pos());
+
+                let (schedules, loads) = execution.path.branch_kinds();
+                stats.schedule_branches += schedules;
+                stats.load_branches += loads;
+                stats.max_critical_depth = execution.path.max_critical_depth();
 
-            i += 1;
+                let op_counts: Vec<_> = execution
+                    .threads
+                    .iter()
+                    .map(|(_, thread)| thread.op_count)
+                    .collect();
+                max_each(&mut stats.max_thread_operations, &op_counts);
+                stats.elapsed = start.elapsed();
+
+                if let Some(OnIteration(on_iteration)) = &self.on_iteration {
+                    on_iteration(&stats);
+                }
+
+                i += 1;
+            }
 
             // Create the next iteration's `tracing` span before trying to step to the next
             // execution, as the `Execution` will capture the current span when
             // it's reset.
             _span = tracing::info_span!(parent: None, "iter", message = i).entered();
+            // Acquire sites are tracked across all executions.
+            let redundant_sync = execution.redundant_sync.take();
+            let happens_before = execution.threads.happens_before.take();
             if let Some(next) = execution.step() {
                 execution = next;
+                execution.redundant_sync = redundant_sync;
+                execution.threads.happens_before = happens_before.map(|_| Default::default());
             } else {
-                info!(parent: None, "Completed in {} iterations", i - 1);
-                return;
+                if let (Some(graph), Some(path)) = (&happens_before, &self.happens_before_graph) {
+                    checkpoint::store_happens_before_graph(graph, path);
+                }
+
+                if let Some(sites) = &redundant_sync {
+                    sites.report();
+                }
+
+                stats.elapsed = start.elapsed();
+
+                info!(
+                    parent: None,
+                    "Completed in {} iterations; deepest critical section: {}",
+                    i - 1,
+                    stats.max_critical_depth
+                );
+                info!(
+                    parent: None,
+                    "Most operations per thread in one iteration: {:?}",
+                    stats.max_thread_operations
+                );
+                return Ok(stats);
             }
         }
     }
+
+    /// Creates an execution configured by the builder.
+    fn execution(&self, preemption_bound: Option<usize>) -> Execution {
+        let mut execution = Execution::new(
+            self.max_threads,
+            self.max_branches,
+            preemption_bound,
+            !self.expect_explicit_explore,
+        );
+
+        check_max_history(self.max_history);
+        execution.max_history = self.max_history;
+        execution.max_spins = self.max_spins;
+        execution.log = self.log;
+        execution.location = self.location || self.report_redundant_sync;
+        execution.sync_points_only = self.sync_points_only;
+        execution.lenient_with_mut = self.lenient_with_mut;
+        execution.spurious_park = self.spurious_park;
+        execution.store_buffer_depth = self.store_buffer_depth;
+        execution.max_operations = self.max_operations;
+        if self.check_seq_cst {
+            execution.seq_cst = Some(Default::default());
+        }
+        if self.report_redundant_sync {
+            execution.redundant_sync = Some(Default::default());
+        }
+        if self.trace {
+            execution.trace = Some(Default::default());
+        }
+        execution.set_max_objects(self.max_objects);
+        if self.happens_before_graph.is_some() {
+            execution.threads.happens_before = Some(Default::default());
+        }
+
+        execution
+    }
+
+    /// Replays simpler variants of the failed `execution`, returning the
+    /// simplest one that fails with the same error, along with its panic.
+    fn shrink<F>(
+        &self,
+        f: &Arc<F>,
+        scheduler: &mut Scheduler,
+        execution: Execution,
+        payload: Box<dyn Any + Send>,
+        start: Instant,
+    ) -> (Execution, Box<dyn Any + Send>)
+    where
+        F: Fn() + Sync + Send + 'static,
+    {
+        let error = LoomError::from_panic(&*payload, vec![]);
+
+        // A variant must fail the same way, not just with the same kind of
+        // error. The first line of the message names e.g. the assertion; the
+        // rest may hold values that differ between variants.
+        let headline = |error: &LoomError| error.to_string().lines().next().map(str::to_string);
+        let same = |other: &LoomError| {
+            std::mem::discriminant(other) == std::mem::discriminant(&error)
+                && headline(other) == headline(&error)
+        };
+
+        let mut replays = 0;
+        let exhausted = |replays: usize| {
+            replays >= MAX_SHRINK_REPLAYS
+                || self
+                    .max_duration
+                    .is_some_and(|max_duration| start.elapsed() >= max_duration)
+        };
+
+        let cost = |execution: &Execution| {
+            let (preemptions, choices) = execution.path.complexity();
+            (preemptions, choices, execution.path.pos())
+        };
+
+        let initial = cost(&execution);
+        let mut best = (execution, payload, error.clone());
+
+        'shrink: loop {
+            let choices = best.0.path.choices();
+
+            // Leave everything after a point to the defaults, then single
+            // choices.
+            let truncated = (0..choices.len()).map(|len| {
+                let mut choices = choices.clone();
+                choices.truncate(len);
+                choices
+            });
+
+            let reset = (0..choices.len())
+                .filter(|&pos| choices[pos] != Choice::Default)
+                .map(|pos| {
+                    let mut choices = choices.clone();
+                    choices[pos] = Choice::Default;
+                    choices
+                });
+
+            // Dropping a choice moves the later ones up to the branches
+            // before them.
+            let removed = (0..choices.len()).map(|pos| {
+                let mut choices = choices.clone();
+                choices.remove(pos);
+                choices
+            });
+
+            for candidate in truncated.chain(reset).chain(removed) {
+                if exhausted(replays) {
+                    break 'shrink;
+                }
+
+                replays += 1;
+
+                let mut execution = self.execution(None);
+                execution.path.set_replay(candidate);
+
+                let payload = match panic::catch_unwind(AssertUnwindSafe(|| {
+                    run_execution(scheduler, &mut execution, f)
+                })) {
+                    Ok(()) => continue,
+                    Err(payload) => payload,
+                };
+
+                let error = LoomError::from_panic(&*payload, vec![]);
+
+                if same(&error) && cost(&execution) < cost(&best.0) {
+                    best = (execution, payload, error);
+                    continue 'shrink;
+                }
+            }
+
+            break;
+        }
+
+        info!(
+            parent: None,
+            "Shrunk the failing permutation from {} to {} preemptions and {} to {} branches \
+             in {} replays",
+            initial.0,
+            cost(&best.0).0,
+            initial.2,
+            cost(&best.0).2,
+            replays
+        );
+
+        let (execution, payload, error) = best;
+        LoomError::restore(Some(error));
+
+        (execution, payload)
+    }
 }
 
 impl Default for Builder {
@@ -225,6 +1048,48 @@ impl Default for Builder {
     }
 }
 
+/// Panics unless a load can read between 1 and all of the stores loom tracks
+/// per atomic.
+fn check_max_history(max_history: usize) {
+    assert!(
+        (1..=rt::MAX_ATOMIC_HISTORY).contains(&max_history),
+        "max_history must be between 1 and {}",
+        rt::MAX_ATOMIC_HISTORY
+    );
+}
+
+/// Raises each count in `max` to the matching one in `counts`.
+fn max_each(max: &mut Vec<usize>, counts: &[usize]) {
+    if max.len() < counts.len() {
+        max.resize(counts.len(), 0);
+    }
+
+    for (max, count) in max.iter_mut().zip(counts) {
+        *max = (*max).max(*count);
+    }
+}
+
+/// Runs `f` once under `execution`, then checks for leaked objects.
+fn run_execution<F>(scheduler: &mut Scheduler, execution: &mut Execution, f: &Arc<F>)
+where
+    F: Fn() + Sync + Send + 'static,
+{
+    let f = f.clone();
+
+    scheduler.run(execution, move || {
+        f();
+
+        let lazy_statics = rt::execution(|execution| execution.lazy_statics.drop());
+
+        // drop outside of execution
+        drop(lazy_statics);
+
+        rt::thread_done();
+    });
+
+    execution.check_for_leaks();
+}
+
 /// Run all concurrent permutations of the provided closure.
 ///
 /// Uses a default [`Builder`] which can be affected by environment variables.
@@ -232,7 +1097,7 @@ pub fn model<F>(f: F)
 where
     F: Fn() + Sync + Send + 'static,
 {
-    let subscriber = fmt::Subscriber::builder()
+    let subscriber = tracing_subscriber::fmt::Subscriber::builder()
         .with_env_filter(EnvFilter::from_env("LOOM_LOG"))
         .with_test_writer()
         .without_time()
@@ -262,6 +1127,16 @@ mod checkpoint {
         let mut file = File::create(fs_path).unwrap();
         file.write_all(serialized.as_bytes()).unwrap();
     }
+
+    pub(crate) fn store_happens_before_graph(
+        graph: &crate::rt::HappensBeforeGraph,
+        fs_path: &Path,
+    ) {
+        let serialized = serde_json::to_string(graph).unwrap();
+
+        let mut file = File::create(fs_path).unwrap();
+        file.write_all(serialized.as_bytes()).unwrap();
+    }
 }
 
 #[cfg(not(feature = "checkpoint"))]
@@ -275,4 +1150,11 @@ mod checkpoint {
     pub(crate) fn store_execution_path(_path: &crate::rt::Path, _fs_path: &Path) {
         panic!("not compiled with `checkpoint` feature")
     }
+
+    pub(crate) fn store_happens_before_graph(
+        _graph: &crate::rt::HappensBeforeGraph,
+        _fs_path: &Path,
+    ) {
+        panic!("not compiled with `checkpoint` feature")
+    }
 }
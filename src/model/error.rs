@@ -0,0 +1,208 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt;
+
+/// A failure detected while checking a model.
+///
+/// By default, [`Builder::check`](super::Builder::check) panics with the
+/// error's `Display` output. [`Builder::try_check`](super::Builder::try_check)
+/// returns it instead, so a harness can route different failures to different
+/// handlers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LoomError {
+    /// All remaining threads are blocked.
+    Deadlock(Failure),
+
+    /// Two accesses to the same memory location are not ordered by
+    /// happens-before and at least one of them is a write.
+    DataRace(Failure),
+
+    /// An object was still alive at the end of the execution.
+    Leak(Failure),
+
+    /// An object was released more than once.
+    DoubleFree(Failure),
+
+    /// An object was accessed after being released.
+    UseAfterFree(Failure),
+
+    /// A pointer loom does not track was deallocated, either because it was
+    /// already deallocated or because it was not allocated through
+    /// [`alloc::alloc`](crate::alloc::alloc).
+    UntrackedDealloc(Failure),
+
+    /// The execution exceeded [`Builder::max_branches`](super::Builder::max_branches).
+    BranchLimitExceeded(Failure),
+
+    /// A single execution ran more than
+    /// [`Builder::max_operations`](super::Builder::max_operations) operations.
+    OperationLimitExceeded(Failure),
+
+    /// A single execution created more than
+    /// [`Builder::max_objects`](super::Builder::max_objects) loom objects.
+    ObjectLimitExceeded(Failure),
+
+    /// A condition variable was waited on with two different mutexes.
+    CondvarMutexMismatch(Failure),
+
+    /// A thread called [`hint::spin_loop`](crate::hint::spin_loop) more than
+    /// [`Builder::max_spins`](super::Builder::max_spins) times in a row
+    /// without progress.
+    Livelock(Failure),
+
+    /// A `SeqCst` load read a store that is inconsistent with a single total
+    /// order of `SeqCst` operations. Only checked with
+    /// [`Builder::check_seq_cst`](super::Builder::check_seq_cst).
+    SeqCstViolated(Failure),
+
+    /// The model has more permutations than
+    /// [`Builder::assert_max_permutations`](super::Builder::assert_max_permutations).
+    PermutationLimitExceeded(Failure),
+
+    /// The model itself panicked, e.g. through a failed `assert!`. The first
+    /// field is the panic message.
+    InvariantViolated(String, Failure),
+}
+
+/// Details shared by every [`LoomError`] variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    message: String,
+    object: Option<usize>,
+    schedule: Vec<usize>,
+}
+
+thread_local! {
+    /// Error raised by the runtime and not yet collected by the builder.
+    static RAISED: RefCell<Option<LoomError>> = const { RefCell::new(None) };
+}
+
+impl LoomError {
+    /// Returns the details of the failure.
+    pub fn failure(&self) -> &Failure {
+        match self {
+            LoomError::Deadlock(failure)
+            | LoomError::DataRace(failure)
+            | LoomError::Leak(failure)
+            | LoomError::DoubleFree(failure)
+            | LoomError::UseAfterFree(failure)
+            | LoomError::UntrackedDealloc(failure)
+            | LoomError::BranchLimitExceeded(failure)
+            | LoomError::OperationLimitExceeded(failure)
+            | LoomError::ObjectLimitExceeded(failure)
+            | LoomError::CondvarMutexMismatch(failure)
+            | LoomError::Livelock(failure)
+            | LoomError::SeqCstViolated(failure)
+            | LoomError::PermutationLimitExceeded(failure)
+            | LoomError::InvariantViolated(_, failure) => failure,
+        }
+    }
+
+    fn failure_mut(&mut self) -> &mut Failure {
+        match self {
+            LoomError::Deadlock(failure)
+            | LoomError::DataRace(failure)
+            | LoomError::Leak(failure)
+            | LoomError::DoubleFree(failure)
+            | LoomError::UseAfterFree(failure)
+            | LoomError::UntrackedDealloc(failure)
+            | LoomError::BranchLimitExceeded(failure)
+            | LoomError::OperationLimitExceeded(failure)
+            | LoomError::ObjectLimitExceeded(failure)
+            | LoomError::CondvarMutexMismatch(failure)
+            | LoomError::Livelock(failure)
+            | LoomError::SeqCstViolated(failure)
+            | LoomError::PermutationLimitExceeded(failure)
+            | LoomError::InvariantViolated(_, failure) => failure,
+        }
+    }
+
+    /// Report the error by panicking with its `Display` output.
+    ///
+    /// The error is recorded so that the builder can recover it once the
+    /// panic unwinds out of the execution.
+    #[track_caller]
+    pub(crate) fn raise(self) -> ! {
+        let message = self.to_string();
+        RAISED.with(|raised| *raised.borrow_mut() = Some(self));
+        panic!("{}", message);
+    }
+
+    /// Recover the error behind a panic that unwound out of an execution.
+    ///
+    /// Panics that were not raised by the runtime come from the model itself.
+    pub(crate) fn from_panic(payload: &(dyn Any + Send), schedule: Vec<usize>) -> LoomError {
+        let mut error = Self::take().unwrap_or_else(|| {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "Box<dyn Any>".to_string());
+
+            LoomError::InvariantViolated(message.clone(), Failure::new(message))
+        });
+
+        error.failure_mut().schedule = schedule;
+        error
+    }
+
+    /// Record an error raised on another OS thread, e.g. by a parallel
+    /// worker, so that `from_panic` recovers it on this one.
+    pub(crate) fn restore(error: Option<LoomError>) {
+        RAISED.with(|raised| *raised.borrow_mut() = error);
+    }
+
+    /// Discard any error recorded by `raise`.
+    pub(crate) fn take() -> Option<LoomError> {
+        RAISED.with(|raised| raised.borrow_mut().take())
+    }
+
+    /// Returns `true` if an error was raised and not taken yet.
+    pub(crate) fn is_raised() -> bool {
+        RAISED.with(|raised| raised.borrow().is_some())
+    }
+}
+
+impl fmt::Display for LoomError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(&self.failure().message)
+    }
+}
+
+impl std::error::Error for LoomError {}
+
+impl Failure {
+    pub(crate) fn new(message: impl Into<String>) -> Failure {
+        Failure {
+            message: message.into(),
+            object: None,
+            schedule: Vec::new(),
+        }
+    }
+
+    pub(crate) fn object(mut self, index: usize) -> Failure {
+        self.object = Some(index);
+        self
+    }
+
+    /// Returns the failure description.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the index of the loom object involved in the failure, if any.
+    ///
+    /// This is the same index reported in leak messages.
+    pub fn object_index(&self) -> Option<usize> {
+        self.object
+    }
+
+    /// Returns the thread scheduled at each scheduling point of the failing
+    /// execution, up to the failure.
+    ///
+    /// Thread numbers match the ones printed by `ThreadId`'s `Debug` impl.
+    pub fn schedule(&self) -> &[usize] {
+        &self.schedule
+    }
+}
@@ -1,5 +1,7 @@
+use crate::model::{Failure, LoomError};
 use crate::rt;
-use crate::rt::{object, Location};
+use crate::rt::location::{self, Location};
+use crate::rt::object;
 
 use tracing::trace;
 
@@ -13,6 +15,9 @@ pub(crate) struct Allocation {
 pub(super) struct State {
     is_dropped: bool,
     allocated: Location,
+
+    /// Bitmask of the threads holding the allocation in a hazard slot.
+    hazards: u8,
 }
 
 /// Track a raw allocation
@@ -21,33 +26,75 @@ pub(crate) fn alloc(ptr: *mut u8, location: Location) {
         let state = execution.objects.insert(State {
             is_dropped: false,
             allocated: location,
+            hazards: 0,
         });
 
-        let allocation = Allocation { state };
-
-        trace!(?allocation.state, ?ptr, %location, "alloc");
+        trace!(?state, ?ptr, %location, "alloc");
 
-        let prev = execution.raw_allocations.insert(ptr as usize, allocation);
+        let prev = execution.raw_allocations.insert(ptr as usize, state);
         assert!(prev.is_none(), "pointer already tracked");
     });
 }
 
 /// Track a raw deallocation
 pub(crate) fn dealloc(ptr: *mut u8, location: Location) {
-    let allocation =
-        rt::execution(
-            |execution| match execution.raw_allocations.remove(&(ptr as usize)) {
-                Some(allocation) => {
-                    trace!(state = ?allocation.state, ?ptr, %location, "dealloc");
+    rt::execution(|execution| {
+        let state = match execution.raw_allocations.remove(&(ptr as usize)) {
+            Some(state) => state,
+            None => {
+                let message = location::panic("Deallocated a pointer that is not tracked")
+                    .thread("dealloc", execution.threads.active_id(), location)
+                    .message();
+
+                LoomError::UntrackedDealloc(Failure::new(message)).raise()
+            }
+        };
+
+        trace!(?state, ?ptr, %location, "dealloc");
 
-                    allocation
+        let state = state.get_mut(&mut execution.objects);
+
+        if state.hazards != 0 {
+            let message = location::panic(format!(
+                "Hazard pointer freed while protected by thread #{}",
+                state.hazards.trailing_zeros()
+            ))
+            .thread("dealloc", execution.threads.active_id(), location)
+            .message();
+
+            LoomError::UseAfterFree(Failure::new(message)).raise()
+        }
+
+        state.is_dropped = true;
+    });
+}
+
+/// Track that the active thread holds `ptr` in its hazard slot, or no longer
+/// does if `protected` is false.
+pub(crate) fn protect(ptr: *mut u8, protected: bool, location: Location) {
+    rt::execution(|execution| {
+        let bit = 1 << execution.threads.active_id().as_usize();
+
+        match execution.raw_allocations.get(&(ptr as usize)) {
+            Some(state) => {
+                let state = state.get_mut(&mut execution.objects);
+
+                if protected {
+                    state.hazards |= bit;
+                } else {
+                    state.hazards &= !bit;
                 }
-                None => panic!("pointer not tracked"),
-            },
-        );
+            }
+            None if protected => {
+                let message = location::panic("Protected a pointer that was already freed")
+                    .thread("protect", execution.threads.active_id(), location)
+                    .message();
 
-    // Drop outside of the `rt::execution` block
-    drop(allocation);
+                LoomError::UseAfterFree(Failure::new(message)).raise()
+            }
+            None => {}
+        }
+    });
 }
 
 impl Allocation {
@@ -56,6 +103,7 @@ impl Allocation {
             let state = execution.objects.insert(State {
                 is_dropped: false,
                 allocated: location,
+                hazards: 0,
             });
 
             trace!(?state, %location, "Allocation::new");
@@ -83,12 +131,19 @@ impl State {
     pub(super) fn check_for_leaks(&self, index: usize) {
         if !self.is_dropped {
             if self.allocated.is_captured() {
-                panic!(
-                    "Allocation leaked.\n  Allocated: {}\n      Index: {}",
-                    self.allocated, index
-                );
+                LoomError::Leak(
+                    Failure::new(format!(
+                        "Allocation leaked.\n  Allocated: {}\n      Index: {}",
+                        self.allocated, index
+                    ))
+                    .object(index),
+                )
+                .raise();
             } else {
-                panic!("Allocation leaked.\n  Index: {}", index);
+                LoomError::Leak(
+                    Failure::new(format!("Allocation leaked.\n  Index: {}", index)).object(index),
+                )
+                .raise();
             }
         }
     }
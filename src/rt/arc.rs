@@ -1,3 +1,4 @@
+use crate::model::{Failure, LoomError};
 use crate::rt::object;
 use crate::rt::{self, Access, Location, Synchronize, VersionVec};
 
@@ -14,6 +15,9 @@ pub(super) struct State {
     /// Reference count
     ref_cnt: usize,
 
+    /// Number of `Weak` pointers
+    weak_cnt: usize,
+
     /// Location where the arc was allocated
     allocated: Location,
 
@@ -63,6 +67,7 @@ impl Arc {
         rt::execution(|execution| {
             let state = execution.objects.insert(State {
                 ref_cnt: 1,
+                weak_cnt: 0,
                 allocated: location,
                 synchronize: Synchronize::new(),
                 last_ref_inc: None,
@@ -88,19 +93,75 @@ impl Arc {
         })
     }
 
+    /// Creates a `Weak` pointer
+    pub(crate) fn weak_inc(&self, location: Location) {
+        self.branch(Action::RefInc, location);
+
+        rt::execution(|execution| {
+            let state = self.state.get_mut(&mut execution.objects);
+            state.weak_cnt = state.weak_cnt.checked_add(1).expect("overflow");
+
+            trace!(state = ?self.state, weak_cnt = ?state.weak_cnt, %location, "Arc::weak_inc");
+        })
+    }
+
+    /// Drops a `Weak` pointer
+    pub(crate) fn weak_dec(&self, location: Location) {
+        self.branch(Action::RefDec, location);
+
+        rt::execution(|execution| {
+            let state = self.state.get_mut(&mut execution.objects);
+            state.weak_cnt -= 1;
+
+            trace!(state = ?self.state, weak_cnt = ?state.weak_cnt, %location, "Arc::weak_dec");
+
+            state
+                .synchronize
+                .sync_store(&mut execution.threads, Release);
+        })
+    }
+
+    /// Upgrades a `Weak` pointer. Returns false if the value was already
+    /// dropped.
+    pub(crate) fn upgrade(&self, location: Location) -> bool {
+        // Upgrading races with the final drop, so it is dependent with it.
+        self.branch(Action::RefDec, location);
+
+        rt::execution(|execution| {
+            let state = self.state.get_mut(&mut execution.objects);
+
+            if state.ref_cnt == 0 {
+                trace!(state = ?self.state, %location, "Arc::upgrade; released");
+                return false;
+            }
+
+            state.ref_cnt = state.ref_cnt.checked_add(1).expect("overflow");
+            state.synchronize.sync_load(&mut execution.threads, Acquire);
+
+            trace!(state = ?self.state, ref_cnt = ?state.ref_cnt, %location, "Arc::upgrade");
+
+            true
+        })
+    }
+
     /// Validate a `get_mut` call
+    ///
+    /// Like `std`, this requires that there are no `Weak` pointers either.
     pub(crate) fn get_mut(&self, location: Location) -> bool {
         self.branch(Action::RefDec, location);
 
         rt::execution(|execution| {
             let state = self.state.get_mut(&mut execution.objects);
 
-            assert!(state.ref_cnt >= 1, "Arc is released");
+            if state.ref_cnt == 0 {
+                LoomError::UseAfterFree(Failure::new("Arc is released").object(self.state.index()))
+                    .raise();
+            }
 
             // Synchronize the threads
             state.synchronize.sync_load(&mut execution.threads, Acquire);
 
-            let is_only_ref = state.ref_cnt == 1;
+            let is_only_ref = state.ref_cnt == 1 && state.weak_cnt == 0;
 
             trace!(state = ?self.state, ?is_only_ref, %location, "Arc::get_mut");
 
@@ -108,6 +169,36 @@ impl Arc {
         })
     }
 
+    /// Releases the only strong reference, if it is the only one. Returns
+    /// true if the value may be moved out.
+    ///
+    /// Unlike `get_mut`, outstanding `Weak` pointers are allowed; they fail
+    /// to upgrade afterwards.
+    pub(crate) fn try_unwrap(&self, location: Location) -> bool {
+        self.branch(Action::RefDec, location);
+
+        rt::execution(|execution| {
+            let state = self.state.get_mut(&mut execution.objects);
+
+            if state.ref_cnt == 0 {
+                LoomError::UseAfterFree(Failure::new("Arc is released").object(self.state.index()))
+                    .raise();
+            }
+
+            state.synchronize.sync_load(&mut execution.threads, Acquire);
+
+            let is_only_ref = state.ref_cnt == 1;
+
+            if is_only_ref {
+                state.ref_cnt = 0;
+            }
+
+            trace!(state = ?self.state, ?is_only_ref, %location, "Arc::try_unwrap");
+
+            is_only_ref
+        })
+    }
+
     /// Returns true if the memory should be dropped.
     pub(crate) fn ref_dec(&self, location: Location) -> bool {
         self.branch(Action::RefDec, location);
@@ -115,7 +206,12 @@ impl Arc {
         rt::execution(|execution| {
             let state = self.state.get_mut(&mut execution.objects);
 
-            assert!(state.ref_cnt >= 1, "Arc is already released");
+            if state.ref_cnt == 0 {
+                LoomError::DoubleFree(
+                    Failure::new("Arc is already released").object(self.state.index()),
+                )
+                .raise();
+            }
 
             // Decrement the ref count
             state.ref_cnt -= 1;
@@ -146,7 +242,12 @@ impl Arc {
 
         rt::execution(|execution| {
             let state = self.state.get_mut(&mut execution.objects);
-            assert!(state.ref_cnt > 0, "Arc is already released");
+            if state.ref_cnt == 0 {
+                LoomError::UseAfterFree(
+                    Failure::new("Arc is already released").object(self.state.index()),
+                )
+                .raise();
+            }
 
             // Synchronize the threads.
             state.synchronize.sync_load(&mut execution.threads, SeqCst);
@@ -155,6 +256,20 @@ impl Arc {
         })
     }
 
+    #[track_caller]
+    pub(crate) fn weak_count(&self) -> usize {
+        self.branch(Action::Inspect, location!());
+
+        rt::execution(|execution| {
+            let state = self.state.get_mut(&mut execution.objects);
+
+            // Synchronize the threads.
+            state.synchronize.sync_load(&mut execution.threads, SeqCst);
+
+            state.weak_cnt
+        })
+    }
+
     fn branch(&self, action: Action, location: Location) {
         let r = self.state;
         r.branch_action(action, location);
@@ -171,12 +286,19 @@ impl State {
     pub(super) fn check_for_leaks(&self, index: usize) {
         if self.ref_cnt != 0 {
             if self.allocated.is_captured() {
-                panic!(
-                    "Arc leaked.\n  Allocated: {}\n      Index: {}",
-                    self.allocated, index
-                );
+                LoomError::Leak(
+                    Failure::new(format!(
+                        "Arc leaked.\n  Allocated: {}\n      Index: {}",
+                        self.allocated, index
+                    ))
+                    .object(index),
+                )
+                .raise();
             } else {
-                panic!("Arc leaked.\n  Index: {}", index);
+                LoomError::Leak(
+                    Failure::new(format!("Arc leaked.\n  Index: {}", index)).object(index),
+                )
+                .raise();
             }
         }
     }
@@ -54,17 +54,18 @@
 //! - Fence Synchronization (Collapsed Store)
 //! - Fence Synchronization (Collapsed Load)
 
+use crate::model::{Failure, LoomError};
 use crate::rt::execution::Execution;
 use crate::rt::location::{self, Location, LocationSet};
 use crate::rt::object;
 use crate::rt::{
-    self, thread, Access, Numeric, Synchronize, VersionVec, MAX_ATOMIC_HISTORY, MAX_THREADS,
+    self, thread, Access, Numeric, SeqCstOrder, StoreId, Synchronize, VersionVec,
+    MAX_ATOMIC_HISTORY, MAX_THREADS,
 };
 
 use std::cmp;
 use std::marker::PhantomData;
 use std::sync::atomic::Ordering;
-use std::u16;
 
 use tracing::trace;
 
@@ -118,12 +119,23 @@ pub(super) struct State {
     /// Last time the atomic was accessed for a store or rmw operation.
     last_non_load_access: Option<Access>,
 
+    /// The only thread that has accessed the cell so far. Set to `None` as
+    /// soon as a second thread accesses the cell.
+    sole_accessor: Option<thread::Id>,
+
     /// Currently tracked stored values. This is the `MAX_ATOMIC_HISTORY` most
     /// recent stores to the atomic cell in loom execution order.
     stores: [Store; MAX_ATOMIC_HISTORY],
 
     /// The total number of stores to the cell.
     cnt: u16,
+
+    /// `true` while every access since the initial store used `Relaxed`
+    /// ordering, without a prior release fence. The stores then release
+    /// nothing beyond the atomic's creation, so synchronizing with them is
+    /// skipped. The stores themselves are kept, so stale reads are still
+    /// explored, and a stronger ordering clears the flag for good.
+    relaxed_only: bool,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -136,6 +148,10 @@ pub(super) enum Action {
 
     /// Atomic read-modify-write
     Rmw,
+
+    /// Atomic compare-and-exchange, expecting the given value. When no store
+    /// it may read holds that value, it fails and only loads.
+    CompareExchange(u64),
 }
 
 #[derive(Debug)]
@@ -158,6 +174,19 @@ struct Store {
 
     /// True when the store was done with `SeqCst` ordering
     seq_cst: bool,
+
+    /// Thread that wrote the value, `None` for unused history slots.
+    writer: Option<Writer>,
+}
+
+/// Identifies the operation that wrote a store, for tracing which store a
+/// load observed.
+#[derive(Debug, Clone, Copy)]
+struct Writer {
+    thread: thread::Id,
+
+    /// The writing thread's own clock at the time of the store.
+    op: u16,
 }
 
 #[derive(Debug)]
@@ -175,19 +204,63 @@ pub(crate) fn fence(ordering: Ordering) {
     });
 }
 
+/// Records a `SeqCst` load of the given store in the total order of `SeqCst`
+/// operations, failing the execution if the order forbids reading it.
+fn check_seq_cst_load(
+    order: &mut SeqCstOrder,
+    thread: thread::Id,
+    object: usize,
+    store: u16,
+    ordered: bool,
+    location: Location,
+) {
+    if let Err(last) = order.load(thread, object, store, ordered) {
+        let message = location::panic(format!(
+            "SeqCst total order violated: a SeqCst load read store #{} of atomic #{}, but \
+             store #{} was the last SeqCst store to it before the load.\n\n{}",
+            store, object, last, order
+        ))
+        .thread("load", thread, location)
+        .message();
+
+        LoomError::SeqCstViolated(Failure::new(message)).raise();
+    }
+}
+
+/// Implements compiler fence behavior.
+///
+/// Unlike `fence`, a compiler fence only restricts how the compiler may
+/// reorder the current thread's own accesses and never synchronizes with
+/// other threads. Loom already runs each thread's operations in program
+/// order, so there is nothing to model beyond validating the ordering.
+pub(crate) fn compiler_fence(ordering: Ordering) {
+    if ordering == Ordering::Relaxed {
+        panic!("there is no such thing as a relaxed compiler fence");
+    }
+
+    trace!(?ordering, "compiler_fence");
+}
+
 fn fence_acq(execution: &mut Execution) {
     // Find all stores for all atomic objects and, if they have been read by
     // the current thread, establish an acquire synchronization.
-    for state in execution.objects.iter_mut::<State>() {
+    for (object, state) in execution.objects.iter_mut::<State>() {
+        if state.relaxed_only {
+            // None of the stores released anything to acquire.
+            continue;
+        }
+
         // Iterate all the stores
-        for store in state.stores_mut() {
+        for (index, store) in state.stores_mut() {
             if !store.first_seen.is_seen_by_current(&execution.threads) {
                 continue;
             }
 
-            store
-                .sync
-                .sync_load(&mut execution.threads, Ordering::Acquire);
+            store.sync.sync_load_store(
+                &mut execution.threads,
+                Ordering::Acquire,
+                Some(StoreId::new(object, index)),
+            );
         }
     }
 }
@@ -229,23 +302,117 @@ impl<T: Numeric> Atomic<T> {
         self.branch(Action::Load, location);
 
         super::synchronize(|execution| {
+            let depth = execution.store_buffer_depth;
+            let history = execution.max_history;
             let state = self.state.get_mut(&mut execution.objects);
 
-            // If necessary, generate the list of stores to permute through
-            if execution.path.is_traversed() {
-                let mut seed = [0; MAX_ATOMIC_HISTORY];
+            let index = if state.is_private(&execution.threads) {
+                // All stores were made by the current thread, so the most
+                // recent one is the only store the load may observe. There is
+                // no need to branch.
+                index(state.cnt - 1)
+            } else {
+                // If necessary, generate the list of stores to permute through
+                if execution.path.is_traversed() {
+                    let mut seed = [0; MAX_ATOMIC_HISTORY];
+
+                    let n = state.match_load_to_stores(
+                        &execution.threads,
+                        &mut seed[..],
+                        ordering,
+                        depth,
+                        history,
+                    );
+
+                    execution.path.push_load(&seed[..n], &execution.threads);
+                }
+
+                // Get the store to return from this load.
+                execution.path.branch_load()
+            };
 
-                let n = state.match_load_to_stores(&execution.threads, &mut seed[..], ordering);
+            trace!(state = ?self.state, ?ordering, store_index = index, "Atomic::load");
 
-                execution.path.push_load(&seed[..n]);
+            if is_acquire(ordering) {
+                if let Some(sites) = &mut execution.redundant_sync {
+                    sites.track(
+                        location,
+                        ordering,
+                        state.stores[index].sync.is_implied(&execution.threads),
+                    );
+                }
             }
 
-            // Get the store to return from this load.
-            let index = execution.path.branch_load();
+            if let Some(order) = execution.seq_cst.as_mut().filter(|_| is_seq_cst(ordering)) {
+                check_seq_cst_load(
+                    order,
+                    execution.threads.active_id(),
+                    self.state.index(),
+                    state.store_number(index),
+                    state.is_seq_cst_ordered(index),
+                    location,
+                );
+            }
+
+            let value = state.load(
+                &mut execution.threads,
+                StoreId::new(self.state.index(), index),
+                location,
+                tso_ordering(depth, ordering, Ordering::Acquire),
+            );
+
+            execution.threads.active_mut().observe(value);
+
+            if let Some(trace) = &mut execution.trace {
+                let writer = state.stores[index].writer();
+                trace.load(execution.threads.active_id(), value, writer);
+            }
+
+            T::from_u64(value)
+        })
+    }
+
+    /// Returns the values a load with the given ordering could observe at
+    /// this point, in modification order. Does not branch or synchronize.
+    pub(crate) fn load_candidates(&self, ordering: Ordering) -> Vec<T> {
+        rt::execution(|execution| {
+            let state = self.state.get(&execution.objects);
+
+            let mut candidates = [0; MAX_ATOMIC_HISTORY];
+
+            let n = if state.is_private(&execution.threads) {
+                candidates[0] = index(state.cnt - 1) as u8;
+                1
+            } else {
+                state.match_load_to_stores(
+                    &execution.threads,
+                    &mut candidates[..],
+                    ordering,
+                    execution.store_buffer_depth,
+                    execution.max_history,
+                )
+            };
 
-            trace!(state = ?self.state, ?ordering, "Atomic::load");
+            state
+                .in_modification_order(candidates[..n].iter().map(|&i| i as usize))
+                .into_iter()
+                .map(|i| T::from_u64(state.stores[i].value))
+                .collect()
+        })
+    }
+
+    /// Returns the values of the tracked stores, in the modification order
+    /// loom has established so far. Does not branch or synchronize.
+    pub(crate) fn coherence_order(&self) -> Vec<T> {
+        rt::execution(|execution| {
+            let state = self.state.get(&execution.objects);
+            let (start, end) = range(state.cnt);
 
-            T::from_u64(state.load(&mut execution.threads, index, location, ordering))
+            state
+                .in_modification_order((start..end).chain(0..start))
+                .into_iter()
+                .map(|i| T::from_u64(state.stores[i].value))
+                .collect()
         })
     }
 
@@ -282,14 +449,24 @@ impl<T: Numeric> Atomic<T> {
             // cell.
             state.track_store(&execution.threads);
 
-            trace!(state = ?self.state, ?ordering, "Atomic::store");
-
             // Do the store
             state.store(
                 &mut execution.threads,
                 Synchronize::new(),
                 val.into_u64(),
-                ordering,
+                tso_ordering(execution.store_buffer_depth, ordering, Ordering::Release),
+            );
+
+            if let Some(order) = execution.seq_cst.as_mut().filter(|_| is_seq_cst(ordering)) {
+                let thread = execution.threads.active_id();
+                order.store(thread, self.state.index(), state.cnt - 1);
+            }
+
+            trace!(
+                state = ?self.state,
+                ?ordering,
+                store_index = index(state.cnt - 1),
+                "Atomic::store"
             );
         })
     }
@@ -304,7 +481,62 @@ impl<T: Numeric> Atomic<T> {
     where
         F: FnOnce(T) -> Result<T, E>,
     {
-        self.branch(Action::Rmw, location);
+        self.rmw_or_spur(location, Action::Rmw, success, failure, |_| false, f)
+            .map_err(|e| e.expect("[loom internal bug] rmw failed spuriously"))
+    }
+
+    /// Compares the value with `current` and, if equal, replaces it with
+    /// `new`.
+    ///
+    /// When `weak` is set, an exchange that would succeed may instead fail
+    /// spuriously. Loom explores both outcomes, for at most one exchange per
+    /// thread and execution.
+    pub(crate) fn compare_exchange(
+        &self,
+        location: Location,
+        weak: bool,
+        current: T,
+        new: T,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<T, T>
+    where
+        T: PartialEq,
+    {
+        self.rmw_or_spur(
+            location,
+            Action::CompareExchange(current.into_u64()),
+            success,
+            failure,
+            |actual| weak && actual == current,
+            |actual| {
+                if actual == current {
+                    Ok(new)
+                } else {
+                    Err(actual)
+                }
+            },
+        )
+        .map_err(|actual| actual.unwrap_or(current))
+    }
+
+    /// Performs a read-modify-write. If `may_spur` returns `true` for the
+    /// value read, a branch is created in which the operation fails without
+    /// calling `f`, reporting the failure as `Err(None)`.
+    fn rmw_or_spur<S, F, E>(
+        &self,
+        location: Location,
+        action: Action,
+        success: Ordering,
+        failure: Ordering,
+        may_spur: S,
+        f: F,
+    ) -> Result<T, Option<E>>
+    where
+        S: FnOnce(T) -> bool,
+        F: FnOnce(T) -> Result<T, E>,
+    {
+        self.branch(action, location);
 
         super::synchronize(|execution| {
             let state = self.state.get_mut(&mut execution.objects);
@@ -314,24 +546,83 @@ impl<T: Numeric> Atomic<T> {
                 let mut seed = [0; MAX_ATOMIC_HISTORY];
 
                 let n = state.match_rmw_to_stores(&mut seed[..]);
-                execution.path.push_load(&seed[..n]);
+                execution.path.push_load(&seed[..n], &execution.threads);
             }
 
             // Get the store to use for the read portion of the rmw operation.
             let index = execution.path.branch_load();
 
-            trace!(state = ?self.state, ?success, ?failure, "Atomic::rmw");
+            trace!(state = ?self.state, ?success, ?failure, store_index = index, "Atomic::rmw");
 
-            state
-                .rmw(
-                    &mut execution.threads,
-                    index,
-                    location,
-                    success,
-                    failure,
-                    |num| f(T::from_u64(num)).map(T::into_u64),
-                )
-                .map(T::from_u64)
+            execution
+                .threads
+                .active_mut()
+                .observe(state.stores[index].value);
+
+            if let Some(trace) = &mut execution.trace {
+                let store = &state.stores[index];
+                trace.load(execution.threads.active_id(), store.value, store.writer());
+            }
+
+            if is_acquire(success) {
+                if let Some(sites) = &mut execution.redundant_sync {
+                    sites.track(
+                        location,
+                        success,
+                        state.stores[index].sync.is_implied(&execution.threads),
+                    );
+                }
+            }
+
+            // Only branch when the failure would be spurious, so exchanges
+            // that fail anyway do not double the explored paths.
+            let spur = may_spur(T::from_u64(state.stores[index].value))
+                && !execution
+                    .threads
+                    .active()
+                    .did_spur(thread::Spur::CompareExchange)
+                && execution.path.branch_spurious(&execution.threads);
+
+            if spur {
+                execution
+                    .threads
+                    .active_mut()
+                    .set_spurred(thread::Spur::CompareExchange);
+            }
+
+            // Read-modify-writes are full barriers on TSO hardware.
+            let depth = execution.store_buffer_depth;
+
+            let read = state.store_number(index);
+            let ordered = state.is_seq_cst_ordered(index);
+
+            let res = state.rmw(
+                &mut execution.threads,
+                StoreId::new(self.state.index(), index),
+                location,
+                tso_ordering(depth, success, Ordering::SeqCst),
+                tso_ordering(depth, failure, Ordering::SeqCst),
+                |num| {
+                    if spur {
+                        Err(None)
+                    } else {
+                        f(T::from_u64(num)).map(T::into_u64).map_err(Some)
+                    }
+                },
+            );
+
+            let ordering = if res.is_ok() { success } else { failure };
+
+            if let Some(order) = execution.seq_cst.as_mut().filter(|_| is_seq_cst(ordering)) {
+                let (thread, object) = (execution.threads.active_id(), self.state.index());
+                check_seq_cst_load(order, thread, object, read, ordered, location);
+
+                if res.is_ok() {
+                    order.store(thread, object, state.cnt - 1);
+                }
+            }
+
+            res.map(T::from_u64)
         })
     }
 
@@ -346,7 +637,7 @@ impl<T: Numeric> Atomic<T> {
                 .unsync_mut_locations
                 .track(location, &execution.threads);
             // Verify the mutation may happen
-            state.track_unsync_mut(&execution.threads);
+            state.track_unsync_mut(&execution.threads, !execution.lenient_with_mut);
             state.is_mutating = true;
 
             trace!(state = ?self.state, "Atomic::with_mut");
@@ -373,7 +664,7 @@ impl<T: Numeric> Atomic<T> {
                     state.stores[index].value = T::into_u64(self.0);
 
                     if !std::thread::panicking() {
-                        state.track_unsync_mut(&execution.threads);
+                        state.track_unsync_mut(&execution.threads, !execution.lenient_with_mut);
                     }
                 });
             }
@@ -386,7 +677,15 @@ impl<T: Numeric> Atomic<T> {
 
     fn branch(&self, action: Action, location: Location) {
         let r = self.state;
-        r.branch_action(action, location);
+
+        // Atomic operations are not scheduling points when only exploring
+        // preemptions at synchronization points. They are still recorded.
+        if rt::execution(|execution| execution.sync_points_only) {
+            r.record_action(action, location);
+        } else {
+            r.branch_action(action, location);
+        }
+
         assert!(
             r.ref_eq(self.state),
             "Internal state mutated during branch. This is \
@@ -413,12 +712,14 @@ impl State {
             is_mutating: false,
             last_access: None,
             last_non_load_access: None,
+            sole_accessor: Some(threads.active_id()),
             stores: Default::default(),
             cnt: 0,
+            relaxed_only: false,
         };
 
         // All subsequent accesses must happen-after.
-        state.track_unsync_mut(threads);
+        state.track_unsync_mut(threads, true);
 
         // Store the initial thread
         //
@@ -429,17 +730,24 @@ impl State {
         // This is verified using `cell`.
         state.store(threads, Synchronize::new(), value, Ordering::Release);
 
+        // Releasing the creating thread's causality is implied, as every
+        // access must happen-after the creation.
+        state.relaxed_only = true;
+
         state
     }
 
     fn load(
         &mut self,
         threads: &mut thread::Set,
-        index: usize,
+        store_id: StoreId,
         location: Location,
         ordering: Ordering,
     ) -> u64 {
+        let index = store_id.store_index();
         self.loaded_locations.track(location, threads);
+        self.relaxed_only &= ordering == Ordering::Relaxed;
+
         // Validate memory safety
         self.track_load(threads);
 
@@ -448,8 +756,11 @@ impl State {
 
         let store = &mut self.stores[index];
 
+        store.trace_observed(threads, "load");
         store.first_seen.touch(threads);
-        store.sync.sync_load(threads, ordering);
+        store
+            .sync
+            .sync_load_store(threads, ordering, Some(store_id));
         store.value
     }
 
@@ -465,6 +776,8 @@ impl State {
         // Increment the count
         self.cnt += 1;
 
+        threads.active_mut().observe_store();
+
         // The modification order is initialized to the thread's current
         // causality. All reads / writes that happen before this store are
         // ordered before the store.
@@ -482,7 +795,14 @@ impl State {
             }
         }
 
-        sync.sync_store(threads, ordering);
+        // A relaxed store releases the view of the thread's last release
+        // fence, if any.
+        self.relaxed_only &=
+            ordering == Ordering::Relaxed && threads.active().released == VersionVec::new();
+
+        if !self.relaxed_only {
+            sync.sync_store(threads, ordering);
+        }
 
         let mut first_seen = FirstSeen::new();
         first_seen.touch(threads);
@@ -495,19 +815,25 @@ impl State {
             sync,
             first_seen,
             seq_cst: is_seq_cst(ordering),
+            writer: Some(Writer {
+                thread: threads.active_id(),
+                op: threads.active_atomic_version(),
+            }),
         };
     }
 
     fn rmw<E>(
         &mut self,
         threads: &mut thread::Set,
-        index: usize,
+        store_id: StoreId,
         location: Location,
         success: Ordering,
         failure: Ordering,
         f: impl FnOnce(u64) -> Result<u64, E>,
     ) -> Result<u64, E> {
+        let index = store_id.store_index();
         self.loaded_locations.track(location, threads);
+        self.relaxed_only &= success == Ordering::Relaxed && failure == Ordering::Relaxed;
 
         // Track the load is happening in order to ensure correct
         // synchronization to the underlying cell.
@@ -516,6 +842,7 @@ impl State {
         // Apply coherence rules.
         self.apply_load_coherence(threads, index);
 
+        self.stores[index].trace_observed(threads, "rmw");
         self.stores[index].first_seen.touch(threads);
 
         let prev = self.stores[index].value;
@@ -527,7 +854,9 @@ impl State {
                 self.track_store(threads);
 
                 // Perform load synchronization using the `success` ordering.
-                self.stores[index].sync.sync_load(threads, success);
+                self.stores[index]
+                    .sync
+                    .sync_load_store(threads, success, Some(store_id));
 
                 // Store the new value, initializing with the `sync` value from
                 // the load. This is our (hacky) way to establish a release
@@ -538,7 +867,9 @@ impl State {
                 Ok(prev)
             }
             Err(e) => {
-                self.stores[index].sync.sync_load(threads, failure);
+                self.stores[index]
+                    .sync
+                    .sync_load_store(threads, failure, Some(store_id));
                 Err(e)
             }
         }
@@ -580,6 +911,7 @@ impl State {
         }
 
         self.loaded_at.join(current);
+        self.track_accessor(threads);
     }
 
     /// Track an unsynchronized load
@@ -613,6 +945,7 @@ impl State {
         }
 
         self.unsync_loaded_at.join(current);
+        self.track_accessor(threads);
     }
 
     /// Track an atomic store
@@ -648,10 +981,12 @@ impl State {
         }
 
         self.stored_at.join(current);
+        self.track_accessor(threads);
     }
 
     /// Track an unsynchronized mutation
-    fn track_unsync_mut(&mut self, threads: &thread::Set) {
+    /// Violations only panic if `fatal` is set, and are logged otherwise.
+    fn track_unsync_mut(&mut self, threads: &thread::Set, fatal: bool) {
         assert!(!self.is_mutating, "atomic cell is in `with_mut` call");
 
         let current = &threads.active().causality;
@@ -665,7 +1000,7 @@ impl State {
                     threads.active_id(),
                     self.unsync_mut_locations[threads],
                 )
-                .fire();
+                .report(fatal);
         }
 
         if let Some(loaded) = current.ahead(&self.unsync_loaded_at) {
@@ -679,7 +1014,7 @@ impl State {
                 threads.active_id(),
                 self.unsync_mut_locations[threads],
             )
-            .fire();
+            .report(fatal);
         }
 
         if let Some(stored) = current.ahead(&self.stored_at) {
@@ -693,7 +1028,7 @@ impl State {
                 threads.active_id(),
                 self.unsync_mut_locations[threads],
             )
-            .fire();
+            .report(fatal);
         }
 
         if let Some(mut_at) = current.ahead(&self.unsync_mut_at) {
@@ -705,18 +1040,37 @@ impl State {
                     threads.active_id(),
                     self.unsync_mut_locations[threads],
                 )
-                .fire();
+                .report(fatal);
         }
 
         self.unsync_mut_at.join(current);
+        self.track_accessor(threads);
+    }
+
+    /// Track the thread accessing the cell
+    fn track_accessor(&mut self, threads: &thread::Set) {
+        if self.sole_accessor != Some(threads.active_id()) {
+            self.sole_accessor = None;
+        }
+    }
+
+    /// Returns `true` if the current thread is the only one to ever have
+    /// accessed the cell.
+    fn is_private(&self, threads: &thread::Set) -> bool {
+        self.sole_accessor == Some(threads.active_id())
     }
 
     /// Find all stores that could be returned by an atomic load.
+    ///
+    /// With a `depth`, only stores with at most `depth` newer stores in
+    /// modification order may be returned.
     fn match_load_to_stores(
         &self,
         threads: &thread::Set,
         dst: &mut [u8],
         ordering: Ordering,
+        depth: Option<usize>,
+        history: usize,
     ) -> usize {
         let mut n = 0;
         let cnt = self.cnt as usize;
@@ -763,13 +1117,59 @@ impl State {
                         continue 'outer;
                     }
 
-                    if is_seq_cst(ordering) && store_i.seq_cst && store_j.seq_cst {
-                        // There is a newer SeqCst store
+                    if is_seq_cst(ordering)
+                        && store_j.seq_cst
+                        && (store_i.seq_cst || store_i.happens_before <= store_j.happens_before)
+                    {
+                        // There is a newer SeqCst store, and this one is
+                        // either SeqCst too or happens-before it, e.g. the
+                        // initial value.
                         continue 'outer;
                     }
                 }
             }
 
+            let newer = (0..cnt.min(self.stores.len()))
+                .filter(|&j| store_i.modification_order < self.stores[j].modification_order)
+                .count();
+
+            if newer >= history {
+                // Too old to be explored.
+                continue;
+            }
+
+            if let Some(depth) = depth {
+                // Every newer store must still sit in its writer's store
+                // buffer, and each thread buffers at most `depth` stores.
+                let newer_writers = || {
+                    self.stores[..cnt.min(self.stores.len())]
+                        .iter()
+                        .filter(|store| store_i.modification_order < store.modification_order)
+                        .filter_map(|store| store.writer.map(|writer| writer.thread))
+                };
+                let overflows = newer_writers()
+                    .any(|writer| newer_writers().filter(|&w| w == writer).count() > depth);
+
+                if overflows {
+                    // A newer store has left its writer's store buffer.
+                    continue;
+                }
+
+                let flushed = self.stores[..cnt.min(self.stores.len())]
+                    .iter()
+                    .filter(|store| store_i.modification_order < store.modification_order)
+                    .any(|store| match store.writer {
+                        Some(writer) => store.first_seen.is_seen_by_other(writer.thread),
+                        None => false,
+                    });
+
+                if flushed {
+                    // Another thread already read a newer store, so it left
+                    // its writer's store buffer and is visible to everyone.
+                    continue;
+                }
+            }
+
             // The load may return this store
             dst[n] = i as u8;
             n += 1;
@@ -778,6 +1178,33 @@ impl State {
         n
     }
 
+    /// Returns the position of the store in history slot `slot` among all
+    /// stores to the atomic.
+    fn store_number(&self, slot: usize) -> u16 {
+        let newest = self.cnt - 1;
+        let age = (index(newest) + MAX_ATOMIC_HISTORY - slot) % MAX_ATOMIC_HISTORY;
+
+        newest - age as u16
+    }
+
+    /// Returns `false` if the store at `index` is not `SeqCst` and does not
+    /// happen before any newer `SeqCst` store. A `SeqCst` load may then read
+    /// it even though a newer `SeqCst` store exists.
+    fn is_seq_cst_ordered(&self, index: usize) -> bool {
+        let cnt = (self.cnt as usize).min(self.stores.len());
+        let read = &self.stores[index];
+        let number = self.store_number(index);
+
+        read.seq_cst
+            || (0..cnt).any(|j| {
+                let store = &self.stores[j];
+
+                store.seq_cst
+                    && self.store_number(j) > number
+                    && read.happens_before <= store.happens_before
+            })
+    }
+
     fn match_rmw_to_stores(&self, dst: &mut [u8]) -> usize {
         let mut n = 0;
         let cnt = self.cnt as usize;
@@ -818,16 +1245,67 @@ impl State {
         n
     }
 
-    fn stores_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut Store> {
+    /// Sorts the given store indices into a total order consistent with the
+    /// modification order. Stores the modification order does not relate
+    /// keep their relative input order.
+    fn in_modification_order(&self, indices: impl Iterator<Item = usize>) -> Vec<usize> {
+        let mut remaining: Vec<_> = indices.collect();
+        let mut sorted = Vec::with_capacity(remaining.len());
+
+        while !remaining.is_empty() {
+            let pos = remaining
+                .iter()
+                .position(|&i| {
+                    let mo_i = self.stores[i].modification_order;
+                    remaining.iter().all(|&j| {
+                        self.stores[j].modification_order.partial_cmp(&mo_i)
+                            != Some(cmp::Ordering::Less)
+                    })
+                })
+                .expect("[loom internal bug] modification order has a cycle");
+
+            sorted.push(remaining.remove(pos));
+        }
+
+        sorted
+    }
+
+    /// Iterates the tracked stores, oldest first, with their index.
+    fn stores_mut(&mut self) -> impl Iterator<Item = (usize, &mut Store)> {
         let (start, end) = range(self.cnt);
         let (two, one) = self.stores[..end].split_at_mut(start);
 
-        one.iter_mut().chain(two.iter_mut())
+        (start..)
+            .zip(one.iter_mut())
+            .chain((0..).zip(two.iter_mut()))
+    }
+
+    /// Returns what `action` amounts to if it runs now. A compare-and-exchange
+    /// that cannot succeed is a load, which does not race with other loads.
+    ///
+    /// It still reads the newest store, as a successful one would.
+    fn resolve(&self, action: Action) -> Action {
+        match action {
+            Action::CompareExchange(current) => {
+                let mut seed = [0; MAX_ATOMIC_HISTORY];
+                let n = self.match_rmw_to_stores(&mut seed[..]);
+
+                if seed[..n]
+                    .iter()
+                    .any(|&i| self.stores[i as usize].value == current)
+                {
+                    Action::Rmw
+                } else {
+                    Action::Load
+                }
+            }
+            action => action,
+        }
     }
 
     /// Returns the last dependent access
     pub(super) fn last_dependent_access(&self, action: Action) -> Option<&Access> {
-        match action {
+        match self.resolve(action) {
             Action::Load => self.last_non_load_access.as_ref(),
             _ => self.last_access.as_ref(),
         }
@@ -838,7 +1316,7 @@ impl State {
         // Always set `last_access`
         Access::set_or_create(&mut self.last_access, path_id, version);
 
-        match action {
+        match self.resolve(action) {
             Action::Load => {}
             _ => {
                 // Stores / RMWs
@@ -859,6 +1337,26 @@ impl Default for Store {
             sync: Synchronize::new(),
             first_seen: FirstSeen::new(),
             seq_cst: false,
+            writer: None,
+        }
+    }
+}
+
+impl Store {
+    /// The thread that wrote the store, and its clock at the time.
+    fn writer(&self) -> Option<(thread::Id, u16)> {
+        self.writer.map(|writer| (writer.thread, writer.op))
+    }
+
+    fn trace_observed(&self, threads: &thread::Set, op: &str) {
+        if let Some(writer) = self.writer {
+            trace!(
+                "T{} {} observed store written by T{} at op {}",
+                threads.active_id(),
+                op,
+                writer.thread,
+                writer.op
+            );
         }
     }
 }
@@ -867,11 +1365,11 @@ impl Default for Store {
 
 impl FirstSeen {
     fn new() -> FirstSeen {
-        FirstSeen([u16::max_value(); MAX_THREADS])
+        FirstSeen([u16::MAX; MAX_THREADS])
     }
 
     fn touch(&mut self, threads: &thread::Set) {
-        if self.0[threads.active_id().as_usize()] == u16::max_value() {
+        if self.0[threads.active_id().as_usize()] == u16::MAX {
             self.0[threads.active_id().as_usize()] = threads.active_atomic_version();
         }
     }
@@ -888,6 +1386,14 @@ impl FirstSeen {
         false
     }
 
+    /// Returns true if a thread other than `writer` saw the value.
+    fn is_seen_by_other(&self, writer: thread::Id) -> bool {
+        self.0
+            .iter()
+            .enumerate()
+            .any(|(thread, &version)| thread != writer.as_usize() && version != u16::MAX)
+    }
+
     fn is_seen_before_yield(&self, threads: &thread::Set) -> bool {
         let thread_id = threads.active_id();
 
@@ -907,6 +1413,13 @@ fn is_seq_cst(order: Ordering) -> bool {
     order == Ordering::SeqCst
 }
 
+fn is_acquire(order: Ordering) -> bool {
+    matches!(
+        order,
+        Ordering::Acquire | Ordering::AcqRel | Ordering::SeqCst
+    )
+}
+
 fn range(cnt: u16) -> (usize, usize) {
     let start = index(cnt.saturating_sub(MAX_ATOMIC_HISTORY as u16));
     let mut end = index(cmp::min(cnt, MAX_ATOMIC_HISTORY as u16));
@@ -926,6 +1439,15 @@ fn range(cnt: u16) -> (usize, usize) {
     (start, end)
 }
 
+/// With a store buffer depth set, relaxed operations are strengthened to
+/// `strong`, as they are on TSO hardware.
+fn tso_ordering(depth: Option<usize>, ordering: Ordering, strong: Ordering) -> Ordering {
+    match depth {
+        Some(_) if ordering == Ordering::Relaxed || strong == Ordering::SeqCst => strong,
+        _ => ordering,
+    }
+}
+
 fn index(cnt: u16) -> usize {
     cnt as usize % MAX_ATOMIC_HISTORY
 }
@@ -1,3 +1,4 @@
+use crate::model::{Failure, LoomError};
 use crate::rt::object;
 use crate::rt::{self, thread, Access, Mutex, VersionVec};
 
@@ -5,7 +6,7 @@ use std::collections::VecDeque;
 
 use tracing::trace;
 
-use super::Location;
+use super::location::{self, Location};
 
 #[derive(Debug, Copy, Clone)]
 pub(crate) struct Condvar {
@@ -19,6 +20,10 @@ pub(super) struct State {
 
     /// Threads waiting on the condvar
     waiters: VecDeque<thread::Id>,
+
+    /// The mutex passed to the first `wait`. Every later `wait` must use the
+    /// same mutex.
+    mutex: Option<Mutex>,
 }
 
 impl Condvar {
@@ -28,6 +33,7 @@ impl Condvar {
             let state = execution.objects.insert(State {
                 last_access: None,
                 waiters: VecDeque::new(),
+                mutex: None,
             });
 
             trace!(?state, "Condvar::new");
@@ -37,7 +43,90 @@ impl Condvar {
     }
 
     /// Blocks the current thread until this condition variable receives a notification.
+    ///
+    /// If the model allows spurious wakeups, the wait may also return without
+    /// a notification.
     pub(crate) fn wait(&self, mutex: &Mutex, location: Location) {
+        let spurious = rt::execution(|execution| execution.spurious_park);
+        self.wait_or_wake_early(mutex, spurious, location);
+    }
+
+    /// Like `wait`, but may also time out. Returns `true` if it did.
+    ///
+    /// There is no time in loom, so timing out is a branch.
+    pub(crate) fn wait_timeout(&self, mutex: &Mutex, location: Location) -> bool {
+        self.wait_or_wake_early(mutex, true, location)
+    }
+
+    /// Waits for a notification. If `may_wake_early`, also explores the wait
+    /// returning without one and returns `true` if it did.
+    ///
+    /// A thread that wakes early stays runnable and only gives up on the
+    /// notification once it is scheduled again, so notifications racing with
+    /// it are explored too. Like a spurious `park`, at most one early wakeup
+    /// is explored per thread and execution, which keeps waiting loops finite.
+    fn wait_or_wake_early(&self, mutex: &Mutex, may_wake_early: bool, location: Location) -> bool {
+        self.release(mutex, location);
+
+        let wake_early = may_wake_early
+            && rt::execution(|execution| {
+                if execution
+                    .threads
+                    .active()
+                    .did_spur(thread::Spur::CondvarWait)
+                {
+                    return false;
+                }
+
+                let wake_early = execution.path.branch_spurious(&execution.threads);
+
+                if wake_early {
+                    execution
+                        .threads
+                        .active_mut()
+                        .set_spurred(thread::Spur::CondvarWait);
+                }
+
+                trace!(state = ?self.state, ?wake_early, "Condvar::wait");
+
+                wake_early
+            });
+
+        let woke_early = if wake_early {
+            rt::yield_now();
+
+            rt::execution(|execution| {
+                let thread_id = execution.threads.active_id();
+                let state = self.state.get_mut(&mut execution.objects);
+
+                match state.waiters.iter().position(|&id| id == thread_id) {
+                    Some(index) => {
+                        // Still waiting, stop.
+                        state.waiters.remove(index);
+                        true
+                    }
+                    None => {
+                        // A notification won the race. It was saved since the
+                        // thread was runnable; consume it, as `park` would.
+                        execution.threads.active_mut().set_runnable();
+                        false
+                    }
+                }
+            })
+        } else {
+            // Disable the current thread
+            rt::park(location);
+            false
+        };
+
+        // Acquire the lock again
+        mutex.acquire_lock(location);
+
+        woke_early
+    }
+
+    /// Registers the current thread as a waiter and releases `mutex`.
+    fn release(&self, mutex: &Mutex, location: Location) {
         self.state.branch_opaque(location);
 
         rt::execution(|execution| {
@@ -45,18 +134,24 @@ impl Condvar {
 
             let state = self.state.get_mut(&mut execution.objects);
 
+            match state.mutex {
+                Some(prev) if !prev.ref_eq(mutex) => {
+                    let message = location::panic("condvar used with two different mutexes")
+                        .thread("wait", execution.threads.active_id(), location)
+                        .message();
+
+                    LoomError::CondvarMutexMismatch(Failure::new(message)).raise();
+                }
+                Some(_) => {}
+                None => state.mutex = Some(*mutex),
+            }
+
             // Track the current thread as a waiter
             state.waiters.push_back(execution.threads.active_id());
         });
 
         // Release the lock
         mutex.release_lock();
-
-        // Disable the current thread
-        rt::park(location);
-
-        // Acquire the lock again
-        mutex.acquire_lock(location);
     }
 
     /// Wakes up one blocked thread on this condvar.
@@ -98,6 +193,10 @@ impl State {
         self.last_access.as_ref()
     }
 
+    pub(super) fn is_waiting(&self, thread: thread::Id) -> bool {
+        self.waiters.contains(&thread)
+    }
+
     pub(crate) fn set_last_access(&mut self, path_id: usize, version: &VersionVec) {
         Access::set_or_create(&mut self.last_access, path_id, version);
     }
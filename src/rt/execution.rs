@@ -1,10 +1,14 @@
-use crate::rt::alloc::Allocation;
-use crate::rt::{lazy_static, object, thread, Path};
+use crate::model::{Failure, LoomError};
+use crate::rt::{
+    alloc, lazy_static, location, object, thread, Location, Path, SeqCstOrder, Trace,
+    MAX_ATOMIC_HISTORY, MAX_SPINS,
+};
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
+use std::sync::atomic::Ordering;
 
-use tracing::info;
+use tracing::{info, trace, warn};
 
 pub(crate) struct Execution {
     /// Uniquely identifies an execution
@@ -20,26 +24,68 @@ pub(crate) struct Execution {
     /// All loom aware objects part of this execution run.
     pub(super) objects: object::Store,
 
-    /// Maps raw allocations to LeakTrack objects
-    pub(super) raw_allocations: HashMap<usize, Allocation>,
+    /// Maps raw allocations to their leak tracking state
+    pub(super) raw_allocations: HashMap<usize, object::Ref<alloc::State>>,
 
     pub(crate) arc_objs: HashMap<*const (), std::sync::Arc<super::Arc>>,
 
     /// Maximum number of concurrent threads
     pub(super) max_threads: usize,
 
-    pub(super) max_history: usize,
+    /// Number of most recent stores an atomic load may read from.
+    pub(crate) max_history: usize,
+
+    /// Consecutive `spin_loop` calls without progress after which a thread
+    /// is livelocked.
+    pub(crate) max_spins: usize,
+
+    /// Total order of `SeqCst` operations, recorded when every `SeqCst` load
+    /// is checked against it.
+    pub(crate) seq_cst: Option<SeqCstOrder>,
 
     /// Capture locations for significant events
     pub(crate) location: bool,
 
     /// Log execution output to STDOUT
     pub(crate) log: bool,
+
+    /// Only preempt threads at synchronization points, not at atomic
+    /// operations.
+    pub(crate) sync_points_only: bool,
+
+    /// Log atomic `with_mut` calls that do not happen-after all other
+    /// accesses instead of panicking.
+    pub(crate) lenient_with_mut: bool,
+
+    /// Let `thread::park` return spuriously.
+    pub(crate) spurious_park: bool,
+
+    /// Number of stores each thread may buffer per atomic, and strengthens
+    /// relaxed operations to TSO.
+    pub(crate) store_buffer_depth: Option<usize>,
+
+    /// Maximum number of operations a single execution may perform.
+    pub(crate) max_operations: Option<usize>,
+
+    /// Number of operations performed so far in this execution.
+    pub(crate) operations: usize,
+
+    /// When set, tracks load sites with acquire semantics to report the ones
+    /// that never synchronized.
+    pub(crate) redundant_sync: Option<AcquireSites>,
+
+    /// When set, records the decisions taken by the execution.
+    pub(crate) trace: Option<Trace>,
 }
 
 #[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
 pub(crate) struct Id(usize);
 
+/// For each load site with acquire semantics, its ordering and whether the
+/// acquire synchronized with anything in at least one execution.
+#[derive(Debug, Default)]
+pub(crate) struct AcquireSites(BTreeMap<String, (Ordering, bool)>);
+
 impl Execution {
     /// Create a new execution.
     ///
@@ -66,20 +112,49 @@ impl Execution {
             raw_allocations: HashMap::new(),
             arc_objs: HashMap::new(),
             max_threads,
-            max_history: 7,
+            max_history: MAX_ATOMIC_HISTORY,
+            max_spins: MAX_SPINS,
+            seq_cst: None,
             location: false,
             log: false,
+            sync_points_only: false,
+            lenient_with_mut: false,
+            spurious_park: false,
+            store_buffer_depth: None,
+            max_operations: None,
+            operations: 0,
+            redundant_sync: None,
+            trace: None,
         }
     }
 
+    /// Bound the number of objects a single execution may create.
+    pub(crate) fn set_max_objects(&mut self, max_objects: Option<usize>) {
+        self.objects.set_max_len(max_objects);
+    }
+
     /// Create state to track a new thread
-    pub(crate) fn new_thread(&mut self) -> thread::Id {
+    ///
+    /// Unless `inherit_causality` is `false`, everything the spawning thread
+    /// did so far happens-before the new thread starts.
+    pub(crate) fn new_thread(&mut self, inherit_causality: bool) -> thread::Id {
         let thread_id = self.threads.new_thread();
         let active_id = self.threads.active_id();
 
+        if inherit_causality {
+            let spawner = self.threads.active().causality;
+            self.threads
+                .record_happens_before("spawn", None, &spawner, thread_id);
+        }
+
         let (active, new) = self.threads.active2_mut(thread_id);
 
-        new.causality.join(&active.causality);
+        if inherit_causality {
+            new.causality.join(&active.causality);
+        }
+
+        // The new thread cannot run before it is spawned, whether or not this
+        // is a synchronization.
         new.dpor_vv.join(&active.dpor_vv);
 
         // Bump causality in order to ensure CausalCell accurately detects
@@ -95,8 +170,16 @@ impl Execution {
         let id = Id::new();
         let max_threads = self.max_threads;
         let max_history = self.max_history;
+        let max_spins = self.max_spins;
+        let seq_cst = self.seq_cst.map(|_| SeqCstOrder::default());
         let location = self.location;
         let log = self.log;
+        let sync_points_only = self.sync_points_only;
+        let lenient_with_mut = self.lenient_with_mut;
+        let spurious_park = self.spurious_park;
+        let store_buffer_depth = self.store_buffer_depth;
+        let max_operations = self.max_operations;
+        let trace = self.trace.map(|_| Trace::default());
         let mut path = self.path;
         let mut objects = self.objects;
         let mut lazy_statics = self.lazy_statics;
@@ -126,8 +209,18 @@ impl Execution {
             arc_objs,
             max_threads,
             max_history,
+            max_spins,
+            seq_cst,
             location,
             log,
+            sync_points_only,
+            lenient_with_mut,
+            spurious_park,
+            store_buffer_depth,
+            max_operations,
+            operations: 0,
+            redundant_sync: None,
+            trace,
         })
     }
 
@@ -185,7 +278,7 @@ impl Execution {
 
         let path_id = self.path.pos();
 
-        let next = self.path.branch_thread(self.id, {
+        let next = self.path.branch_thread(self.id, &self.threads, {
             self.threads.iter().map(|(i, th)| {
                 if initial.is_none() && th.is_runnable() {
                     initial = Some(i);
@@ -205,6 +298,22 @@ impl Execution {
 
         let switched = Some(self.threads.active_id()) != next;
 
+        // Why the previous thread stopped running. This must be read before
+        // yielded threads are made runnable again below.
+        let reason = {
+            let prev = self.threads.active();
+
+            if prev.is_terminated() {
+                "terminated"
+            } else if prev.is_blocked() {
+                "blocked"
+            } else if prev.is_yield() {
+                "yielded"
+            } else {
+                "preempted"
+            }
+        };
+
         self.threads.set_active(next);
 
         // There is no active thread. Unless all threads have terminated, the
@@ -212,33 +321,69 @@ impl Execution {
         if !self.threads.is_active() {
             let terminal = self.threads.iter().all(|(_, th)| th.is_terminated());
 
+            // A deadlock means every remaining thread is blocked. If a thread
+            // could still make progress, the scheduler failed to pick it.
+            let runnable = unscheduled(&self.threads);
+
             assert!(
-                terminal,
-                "deadlock; threads = {:?}",
+                runnable.is_empty(),
+                "[loom internal bug] no thread scheduled but {:?} can make progress; threads = {:?}",
+                runnable,
                 self.threads
                     .iter()
                     .map(|(i, th)| { (i, th.state) })
                     .collect::<Vec<_>>()
             );
 
-            return true;
-        }
+            if !terminal {
+                let mut message = format!(
+                    "deadlock; threads = {:?}",
+                    self.threads
+                        .iter()
+                        .map(|(i, th)| { (i, th.name.as_deref(), th.state) })
+                        .collect::<Vec<_>>()
+                );
+
+                // What each blocked thread waits on, and who holds it.
+                let waits: Vec<_> = self
+                    .threads
+                    .iter()
+                    .filter(|(_, th)| !th.is_terminated())
+                    .map(|(i, th)| (i, self.objects.blocked_on(i, th.operation)))
+                    .collect();
 
-        // TODO: refactor
-        if let Some(operation) = self.threads.active().operation {
-            let threads = &mut self.threads;
-            let th_id = threads.active_id();
+                for (i, wait) in &waits {
+                    message.push_str(&format!("\n  {}", describe_wait(*i, wait)));
+                }
 
-            if let Some(access) = self.objects.last_dependent_access(operation) {
-                threads.active_mut().dpor_vv.join(access.version());
-            }
+                if let Some(cycle) = wait_for_cycle(&waits) {
+                    message.push_str(&format!("\n  cycle: {}", cycle));
+                }
 
-            threads.active_mut().dpor_vv[th_id] += 1;
+                let mut panic = location::panic(message);
 
-            self.objects
-                .set_last_access(operation, path_id, &threads.active().dpor_vv);
+                // Where each thread is stuck, if locations are captured.
+                for (i, th) in self.threads.iter() {
+                    if let Some(location) = th.blocked_location() {
+                        panic.thread("blocked", i, location);
+                    }
+                }
+
+                LoomError::Deadlock(Failure::new(panic.message())).raise();
+            }
+
+            return true;
         }
 
+        trace!(
+            thread_id = self.threads.active_id().as_usize(),
+            operation = ?self.threads.active().operation.map(|operation| operation.action()),
+            path_pos = path_id,
+            "schedule"
+        );
+
+        self.record_access(path_id);
+
         // Reactivate yielded threads, but only if the current active thread is
         // not yielded.
         for (id, th) in self.threads.iter_mut() {
@@ -248,12 +393,68 @@ impl Execution {
         }
 
         if switched {
-            info!("~~~~~~~~ THREAD {} ~~~~~~~~", self.threads.active_id());
+            let name = self
+                .threads
+                .active()
+                .name
+                .as_ref()
+                .map(|name| format!(" {:?}", name))
+                .unwrap_or_default();
+
+            info!(
+                "~~~~~~~~ THREAD {}{} (thread {} {}) ~~~~~~~~",
+                self.threads.active_id(),
+                name,
+                curr_thread,
+                reason
+            );
         }
 
         curr_thread != self.threads.active_id()
     }
 
+    /// Records the active thread's pending operation without creating a
+    /// schedule branch, so the thread keeps running.
+    ///
+    /// The operation still takes part in DPOR. A race with an earlier access
+    /// adds a backtrack point at that access's branch, and the operation is
+    /// attributed to the most recent branch.
+    pub(crate) fn record(&mut self) {
+        let th_id = self.threads.active_id();
+
+        if let Some(operation) = self.threads.active().operation {
+            if let Some(access) = self.objects.last_dependent_access(operation) {
+                if !access.happens_before(&self.threads.active().dpor_vv) {
+                    self.path.backtrack(access.path_id(), th_id);
+                }
+            }
+        }
+
+        self.record_access(self.path.pos().saturating_sub(1));
+    }
+
+    /// Traces the active thread's pending operation and tracks it as the
+    /// last access of its object at `path_id`.
+    fn record_access(&mut self, path_id: usize) {
+        if let Some(trace) = &mut self.trace {
+            trace.schedule(self.threads.active_id(), self.threads.active().operation);
+        }
+
+        if let Some(operation) = self.threads.active().operation {
+            let threads = &mut self.threads;
+            let th_id = threads.active_id();
+
+            if let Some(access) = self.objects.last_dependent_access(operation) {
+                threads.active_mut().dpor_vv.join(access.version());
+            }
+
+            threads.active_mut().dpor_vv[th_id] += 1;
+
+            self.objects
+                .set_last_access(operation, path_id, &threads.active().dpor_vv);
+        }
+    }
+
     /// Panics if any leaks were detected
     pub(crate) fn check_for_leaks(&self) {
         self.objects.check_for_leaks();
@@ -269,6 +470,30 @@ impl fmt::Debug for Execution {
     }
 }
 
+impl AcquireSites {
+    /// Records a load with acquire semantics at `location`. `implied` is `true` if the
+    /// store it read from already happens-before the loading thread.
+    pub(crate) fn track(&mut self, location: Location, ordering: Ordering, implied: bool) {
+        self.0
+            .entry(location.to_string())
+            .or_insert((ordering, false))
+            .1 |= !implied;
+    }
+
+    /// Logs the sites that never synchronized with anything in the explored
+    /// executions.
+    pub(crate) fn report(&self) {
+        for (location, (ordering, _)) in self.0.iter().filter(|(_, (_, used))| !*used) {
+            warn!(
+                "{:?} at {} never synchronized with a store that did not \
+                 already happen-before the loading thread; its ordering could \
+                 be weakened",
+                ordering, location
+            );
+        }
+    }
+}
+
 impl Id {
     pub(crate) fn new() -> Id {
         use std::sync::atomic::AtomicUsize;
@@ -283,3 +508,111 @@ impl Id {
         Id(next)
     }
 }
+
+type Wait = Option<(String, Vec<thread::Id>)>;
+
+/// Formats what a blocked thread waits on, e.g.
+/// `thread #1 waiting on Mutex#3 held by thread #2`.
+/// Threads that could still make progress when none is scheduled.
+fn unscheduled(threads: &thread::Set) -> Vec<thread::Id> {
+    threads
+        .iter()
+        .filter(|(_, th)| th.is_runnable() || th.is_yield())
+        .map(|(i, _)| i)
+        .collect()
+}
+
+fn describe_wait(thread: thread::Id, wait: &Wait) -> String {
+    let (object, holders) = match wait {
+        Some(wait) => wait,
+        None => return format!("thread #{} parked", thread.as_usize()),
+    };
+
+    let mut description = format!("thread #{} waiting on {}", thread.as_usize(), object);
+
+    if !holders.is_empty() {
+        let holders: Vec<_> = holders
+            .iter()
+            .map(|id| format!("#{}", id.as_usize()))
+            .collect();
+
+        description.push_str(&format!(" held by thread {}", holders.join(", ")));
+    }
+
+    description
+}
+
+/// Follows the first holder of each awaited object until a thread repeats,
+/// returning the cycle as a chain of waits.
+fn wait_for_cycle(waits: &[(thread::Id, Wait)]) -> Option<String> {
+    let holder = |thread: thread::Id| {
+        waits
+            .iter()
+            .find(|(id, _)| *id == thread)
+            .and_then(|(_, wait)| wait.as_ref())
+            .and_then(|(object, holders)| Some((object, *holders.first()?)))
+    };
+
+    for &(start, _) in waits {
+        let mut chain = vec![start];
+
+        while let Some((_, next)) = holder(*chain.last().unwrap()) {
+            if let Some(pos) = chain.iter().position(|&id| id == next) {
+                let mut cycle = format!("thread #{}", chain[pos].as_usize());
+
+                for &id in &chain[pos..] {
+                    let (object, next) = holder(id).unwrap();
+
+                    cycle.push_str(&format!(
+                        " waiting on {} held by thread #{}",
+                        object,
+                        next.as_usize()
+                    ));
+                }
+
+                return Some(cycle);
+            }
+
+            chain.push(next);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rt::location::Location;
+
+    #[test]
+    fn blocked_threads_are_deadlocked() {
+        let mut threads = thread::Set::new(Id::new(), 2);
+        threads.new_thread();
+
+        for (_, th) in threads.iter_mut() {
+            th.set_blocked(Location::disabled());
+        }
+        threads.set_active(None);
+
+        assert!(unscheduled(&threads).is_empty());
+    }
+
+    #[test]
+    fn yielding_thread_is_not_deadlocked() {
+        let mut threads = thread::Set::new(Id::new(), 3);
+        let yielding = threads.new_thread();
+        threads.new_thread();
+
+        for (id, th) in threads.iter_mut() {
+            if id == yielding {
+                th.set_yield();
+            } else {
+                th.set_blocked(Location::disabled());
+            }
+        }
+        threads.set_active(None);
+
+        assert_eq!(unscheduled(&threads), [yielding]);
+    }
+}
@@ -0,0 +1,89 @@
+use crate::rt::{thread, Location, VersionVec};
+
+#[cfg(feature = "checkpoint")]
+use serde::Serialize;
+
+/// Synchronization edges established during a single execution.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "checkpoint", derive(Serialize))]
+pub(crate) struct HappensBeforeGraph {
+    edges: Vec<Edge>,
+}
+
+/// Everything thread `from` did up to its version `from_version`
+/// happens-before thread `to` at its version `to_version`.
+#[derive(Debug)]
+#[cfg_attr(feature = "checkpoint", derive(Serialize))]
+#[cfg_attr(not(feature = "checkpoint"), allow(dead_code))]
+struct Edge {
+    from: usize,
+    from_version: u16,
+    to: usize,
+    to_version: u16,
+
+    /// What established the edge, e.g. `acquire` or `unpark`.
+    kind: &'static str,
+
+    /// For an `acquire` from an atomic, the store that was read.
+    store: Option<StoreId>,
+
+    /// Operation of the thread that was running when the edge was
+    /// established: the acquiring load for `acquire`, but the unparking or
+    /// spawning thread for `unpark` and `spawn`. Empty unless locations are
+    /// captured.
+    location: String,
+}
+
+/// Identifies a store by the index of its atomic object and the index it is
+/// logged with as `store_index`.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "checkpoint", derive(Serialize))]
+#[cfg_attr(not(feature = "checkpoint"), allow(dead_code))]
+pub(crate) struct StoreId {
+    object: usize,
+    store_index: usize,
+}
+
+impl StoreId {
+    pub(crate) fn new(object: usize, store_index: usize) -> StoreId {
+        StoreId {
+            object,
+            store_index,
+        }
+    }
+
+    pub(crate) fn store_index(self) -> usize {
+        self.store_index
+    }
+}
+
+impl HappensBeforeGraph {
+    /// Records the edges gained by thread `to`, whose causality is
+    /// `causality`, when it joins `happens_before`.
+    ///
+    /// Must be called before the join. Only threads that `to` did not
+    /// already know about up to the same version produce an edge.
+    pub(crate) fn record(
+        &mut self,
+        kind: &'static str,
+        store: Option<StoreId>,
+        location: Location,
+        happens_before: &VersionVec,
+        to: thread::Id,
+        causality: &VersionVec,
+    ) {
+        for (from, from_version) in happens_before.versions(to.execution_id()) {
+            if from != to && from_version > causality[from] {
+                self.edges.push(Edge {
+                    from: from.as_usize(),
+                    from_version,
+                    to: to.as_usize(),
+                    to_version: causality[to],
+                    kind,
+                    store,
+                    location: location.to_string(),
+                });
+            }
+        }
+    }
+}
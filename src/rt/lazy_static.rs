@@ -1,12 +1,19 @@
 use crate::rt::synchronize::Synchronize;
-use std::{any::Any, collections::HashMap};
+use crate::rt::Mutex;
+use std::{any::Any, collections::BTreeMap};
 
 pub(crate) struct Set {
     /// Registered statics.
-    statics: Option<HashMap<StaticKeyId, StaticValue>>,
+    ///
+    /// Ordered so that statics are dropped in the same order in every
+    /// execution.
+    statics: Option<BTreeMap<StaticKeyId, StaticValue>>,
+
+    /// Serializes the initializers of each static.
+    init_locks: BTreeMap<StaticKeyId, Mutex>,
 }
 
-#[derive(Eq, PartialEq, Hash, Copy, Clone)]
+#[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone)]
 pub(crate) struct StaticKeyId(usize);
 
 pub(crate) struct StaticValue {
@@ -18,7 +25,8 @@ impl Set {
     /// Create an empty statics set.
     pub(crate) fn new() -> Set {
         Set {
-            statics: Some(HashMap::new()),
+            statics: Some(BTreeMap::new()),
+            init_locks: BTreeMap::new(),
         }
     }
 
@@ -27,10 +35,11 @@ impl Set {
             self.statics.is_none(),
             "lazy_static was not dropped during execution"
         );
-        self.statics = Some(HashMap::new());
+        self.statics = Some(BTreeMap::new());
+        self.init_locks.clear();
     }
 
-    pub(crate) fn drop(&mut self) -> HashMap<StaticKeyId, StaticValue> {
+    pub(crate) fn drop(&mut self) -> BTreeMap<StaticKeyId, StaticValue> {
         self.statics
             .take()
             .expect("lazy_statics were dropped twice in one execution")
@@ -46,6 +55,23 @@ impl Set {
             .get_mut(&StaticKeyId::new(key))
     }
 
+    /// Returns the lock serializing the initializers of `key`, if one was
+    /// created in this execution.
+    pub(crate) fn init_lock<T: 'static>(
+        &self,
+        key: &'static crate::lazy_static::Lazy<T>,
+    ) -> Option<Mutex> {
+        self.init_locks.get(&StaticKeyId::new(key)).copied()
+    }
+
+    pub(crate) fn set_init_lock<T: 'static>(
+        &mut self,
+        key: &'static crate::lazy_static::Lazy<T>,
+        lock: Mutex,
+    ) {
+        self.init_locks.insert(StaticKeyId::new(key), lock);
+    }
+
     pub(crate) fn init_static<T: 'static>(
         &mut self,
         key: &'static crate::lazy_static::Lazy<T>,
@@ -57,7 +83,7 @@ impl Set {
             .expect("attempted to access lazy_static during shutdown")
             .entry(StaticKeyId::new(key));
 
-        if let std::collections::hash_map::Entry::Occupied(_) = v {
+        if let std::collections::btree_map::Entry::Occupied(_) = v {
             unreachable!("told to init static, but it was already init'd");
         }
 
@@ -14,6 +14,7 @@ macro_rules! location {
     }};
 }
 
+use crate::model::{Failure, LoomError};
 use crate::rt::{thread, MAX_THREADS};
 
 use std::ops;
@@ -87,6 +88,19 @@ impl PanicBuilder {
     }
 
     pub(super) fn fire(&self) {
+        LoomError::DataRace(Failure::new(self.message())).raise();
+    }
+
+    /// Fire the panic if `fatal` is set, otherwise only log it as a warning.
+    pub(super) fn report(&self, fatal: bool) {
+        if fatal {
+            self.fire();
+        } else {
+            tracing::warn!("{}", self.message());
+        }
+    }
+
+    pub(super) fn message(&self) -> String {
         let mut msg = self.msg.clone();
 
         let width = self
@@ -112,7 +126,7 @@ impl PanicBuilder {
             }
         }
 
-        panic!("{}\n", msg);
+        format!("{}\n", msg)
     }
 }
 
@@ -6,13 +6,13 @@ mod access;
 use self::access::Access;
 
 mod alloc;
-pub(crate) use self::alloc::{alloc, dealloc, Allocation};
+pub(crate) use self::alloc::{alloc, dealloc, protect, Allocation};
 
 mod arc;
 pub(crate) use self::arc::Arc;
 
 mod atomic;
-pub(crate) use self::atomic::{fence, Atomic};
+pub(crate) use self::atomic::{compiler_fence, fence, Atomic};
 
 pub(crate) mod cell;
 pub(crate) use self::cell::Cell;
@@ -23,6 +23,9 @@ pub(crate) use self::condvar::Condvar;
 mod execution;
 pub(crate) use self::execution::Execution;
 
+mod happens_before;
+pub(crate) use self::happens_before::{HappensBeforeGraph, StoreId};
+
 mod notify;
 pub(crate) use self::notify::Notify;
 
@@ -39,7 +42,7 @@ mod mutex;
 pub(crate) use self::mutex::Mutex;
 
 mod path;
-pub(crate) use self::path::Path;
+pub(crate) use self::path::{Choice, Path};
 
 mod rwlock;
 pub(crate) use self::rwlock::RwLock;
@@ -47,9 +50,15 @@ pub(crate) use self::rwlock::RwLock;
 mod scheduler;
 pub(crate) use self::scheduler::Scheduler;
 
+mod seq_cst;
+pub(crate) use self::seq_cst::SeqCstOrder;
+
 mod synchronize;
 pub(crate) use self::synchronize::Synchronize;
 
+mod trace;
+pub(crate) use self::trace::Trace;
+
 pub(crate) mod lazy_static;
 pub(crate) mod thread;
 
@@ -64,11 +73,19 @@ pub const MAX_THREADS: usize = 5;
 /// Maximum number of atomic store history to track per-cell.
 pub(crate) const MAX_ATOMIC_HISTORY: usize = 7;
 
-pub(crate) fn spawn<F>(stack_size: Option<usize>, f: F) -> crate::rt::thread::Id
+/// Number of consecutive `spin_loop` calls without progress after which a
+/// thread is considered livelocked.
+pub(crate) const MAX_SPINS: usize = 100;
+
+pub(crate) fn spawn<F>(
+    stack_size: Option<usize>,
+    inherit_causality: bool,
+    f: F,
+) -> crate::rt::thread::Id
 where
     F: FnOnce() + 'static,
 {
-    let id = execution(|execution| execution.new_thread());
+    let id = execution(|execution| execution.new_thread(inherit_causality));
 
     trace!(thread = ?id, "spawn");
 
@@ -113,6 +130,57 @@ pub(crate) fn park(location: Location) {
     }
 }
 
+/// Like `park`, but may also return spuriously, without having been
+/// unparked, if the model allows it.
+pub(crate) fn park_or_spur(location: Location) {
+    park_or_return_early(false, location);
+}
+
+/// Like `park`, but may also time out. There is no time in loom, so timing out
+/// is a branch, explored at most once per thread and execution.
+pub(crate) fn park_timeout(location: Location) {
+    park_or_return_early(true, location);
+}
+
+fn park_or_return_early(may_time_out: bool, location: Location) {
+    let spurious = execution(|execution| {
+        let active = execution.threads.active();
+
+        // A saved unpark is consumed without blocking, so there is nothing
+        // to wake up from.
+        let unparked = matches!(active.state, thread::State::Runnable { unparked: true });
+
+        if !(may_time_out || execution.spurious_park)
+            || active.did_spur(thread::Spur::Park)
+            || unparked
+        {
+            return false;
+        }
+
+        let spurious = execution.path.branch_spurious(&execution.threads);
+
+        if spurious {
+            execution
+                .threads
+                .active_mut()
+                .set_spurred(thread::Spur::Park);
+        }
+
+        trace!(thread = ?execution.threads.active_id(), ?spurious, "park_or_spur");
+
+        spurious
+    });
+
+    if spurious {
+        // The spurious return establishes no causality. Other threads run
+        // before it returns, so an unpark racing with it is saved for the next
+        // `park`.
+        yield_now();
+    } else {
+        park(location);
+    }
+}
+
 /// Add an execution branch point.
 fn branch<F, R>(f: F) -> R
 where
@@ -155,8 +223,8 @@ where
 /// since each iteration constitutes a branch point which might easily cause a
 /// combinatorial explosion.
 ///
-/// Note that in loom, [`spin_loop`] and [`spin_loop_hint`] is an alias of this
-/// function.
+/// Note that in loom, [`spin_loop`] and [`spin_loop_hint`] behave like this
+/// function, and additionally report spin loops that make no progress.
 ///
 /// [`spin_loop`]: crate::hint::spin_loop
 /// [`spin_loop_hint`]: crate::sync::atomic::spin_loop_hint
@@ -256,6 +324,51 @@ pub fn yield_now() {
     }
 }
 
+/// Yield from a busy-wait loop.
+///
+/// A thread that keeps spinning without progress is waiting for a condition
+/// this interleaving never satisfies. It is reported as livelocked instead of
+/// exhausting the branch limit.
+pub(crate) fn spin_loop(location: Location) {
+    execution(|execution| {
+        let active_id = execution.threads.active_id();
+
+        let others = || {
+            execution
+                .threads
+                .iter()
+                .filter(move |(id, _)| *id != active_id)
+                .map(|(_, th)| th)
+        };
+
+        let stuck = others().all(|th| th.is_blocked() || th.is_terminated());
+        let synced = others().map(|th| th.sync_count()).sum();
+
+        let spins = execution.threads.active_mut().observe_spin(stuck, synced);
+
+        if spins > execution.max_spins {
+            let message = location::panic(format!(
+                "Livelock: thread #{} spun {} times in a row without progress. If the \
+                 loop does make progress, raise `Builder::max_spins`.",
+                active_id.as_usize(),
+                spins
+            ))
+            .thread("spinning", active_id, location)
+            .message();
+
+            crate::model::LoomError::Livelock(crate::model::Failure::new(message)).raise();
+        }
+    });
+
+    yield_now();
+}
+
+/// Returns `true` if the runtime failed the current execution, e.g. because it
+/// detected a data race, rather than the model's code panicking.
+pub(crate) fn is_failing() -> bool {
+    Scheduler::runtime_panicked() || crate::model::LoomError::is_raised()
+}
+
 pub(crate) fn execution<F, R>(f: F) -> R
 where
     F: FnOnce(&mut Execution) -> R,
@@ -295,7 +408,10 @@ pub fn explore() {
 /// Tells loom to stop exploring possible concurrent executions starting at this
 /// point.
 ///
-/// Exploration can be enabled again with `explore`.
+/// Exploration can be enabled again with `explore`. Calls may be nested, in
+/// which case exploration resumes once every `stop_exploring` has been
+/// matched by an `explore`. The deepest nesting reached is logged when the
+/// model completes.
 pub fn stop_exploring() {
     execution(|execution| {
         execution.path.critical();
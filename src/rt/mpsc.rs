@@ -1,3 +1,4 @@
+use crate::model::{Failure, LoomError};
 use crate::rt::{object, Access, Location, Synchronize, VersionVec};
 use std::collections::VecDeque;
 use std::sync::atomic::Ordering::{Acquire, Release};
@@ -12,6 +13,17 @@ pub(super) struct State {
     /// Count of messages in the channel.
     msg_cnt: usize,
 
+    /// Maximum number of messages in the channel, if bounded. Senders block
+    /// while the channel is full.
+    capacity: Option<usize>,
+
+    /// Number of live senders. Once it drops to zero, receiving from an empty
+    /// channel fails instead of blocking.
+    senders: usize,
+
+    /// `false` once the receiver is dropped. Sending then fails.
+    receiver: bool,
+
     /// Last access that was a send operation.
     last_send_access: Option<Access>,
     /// Last access that was a receive operation.
@@ -50,10 +62,13 @@ pub(super) enum Action {
 }
 
 impl Channel {
-    pub(crate) fn new(location: Location) -> Self {
+    pub(crate) fn new(capacity: Option<usize>, location: Location) -> Self {
         super::execution(|execution| {
             let state = execution.objects.insert(State {
                 msg_cnt: 0,
+                capacity,
+                senders: 1,
+                receiver: true,
                 last_send_access: None,
                 last_recv_access: None,
                 sender_synchronize: Synchronize::new(),
@@ -66,10 +81,18 @@ impl Channel {
         })
     }
 
-    pub(crate) fn send(&self, location: Location) {
-        self.state.branch_action(Action::MsgSend, location);
+    /// Sends a message, blocking while a bounded channel is full. Returns
+    /// `false` if the receiver was dropped.
+    pub(crate) fn send(&self, location: Location) -> bool {
+        self.state
+            .branch_disable(Action::MsgSend, self.is_full(), location);
         super::execution(|execution| {
             let state = self.state.get_mut(&mut execution.objects);
+
+            if !state.receiver {
+                return false;
+            }
+
             state.msg_cnt = state.msg_cnt.checked_add(1).expect("overflow");
 
             state
@@ -79,56 +102,87 @@ impl Channel {
                 .receiver_synchronize
                 .push_back(state.sender_synchronize);
 
+            let full = Some(state.msg_cnt) == state.capacity;
+
             if state.msg_cnt == 1 {
                 // Unblock all threads that are blocked waiting on this channel
-                let thread_id = execution.threads.active_id();
-                for (id, thread) in execution.threads.iter_mut() {
-                    if id == thread_id {
-                        continue;
-                    }
-
-                    let obj = thread
-                        .operation
-                        .as_ref()
-                        .map(|operation| operation.object());
+                self.set_waiters(execution, Action::MsgRecv, true);
+            }
 
-                    if obj == Some(self.state.erase()) {
-                        thread.set_runnable();
-                    }
-                }
+            if full {
+                // Block all **other** threads attempting to send on the channel
+                self.set_waiters(execution, Action::MsgSend, false);
             }
+
+            true
         })
     }
 
-    pub(crate) fn recv(&self, location: Location) {
+    /// Receives a message, blocking while the channel is empty. Returns
+    /// `false` if the channel is empty and all senders were dropped.
+    pub(crate) fn recv(&self, location: Location) -> bool {
+        let disable = super::execution(|execution| {
+            let state = self.get_state(&mut execution.objects);
+            state.msg_cnt == 0 && state.senders > 0
+        });
+
         self.state
-            .branch_disable(Action::MsgRecv, self.is_empty(), location);
+            .branch_disable(Action::MsgRecv, disable, location);
         super::execution(|execution| {
             let state = self.state.get_mut(&mut execution.objects);
-            let thread_id = execution.threads.active_id();
+
+            if state.msg_cnt == 0 {
+                return false;
+            }
+
+            let was_full = Some(state.msg_cnt) == state.capacity;
             state.msg_cnt = state
                 .msg_cnt
                 .checked_sub(1)
                 .expect("expected to be able to read the message");
             let mut synchronize = state.receiver_synchronize.pop_front().unwrap();
             dbg!(synchronize.sync_load(&mut execution.threads, Acquire));
-            if state.msg_cnt == 0 {
+            let senders = state.senders;
+
+            if state.msg_cnt == 0 && senders > 0 {
                 // Block all **other** threads attempting to read from the channel
-                for (id, thread) in execution.threads.iter_mut() {
-                    if id == thread_id {
-                        continue;
-                    }
+                self.set_waiters(execution, Action::MsgRecv, false);
+            }
 
-                    if let Some(operation) = thread.operation.as_ref() {
-                        if operation.object() == self.state.erase()
-                            && operation.action() == object::Action::Channel(Action::MsgRecv)
-                        {
-                            let location = operation.location();
-                            thread.set_blocked(location);
-                        }
-                    }
-                }
+            if was_full {
+                // A slot freed up, unblock the threads waiting to send
+                self.set_waiters(execution, Action::MsgSend, true);
             }
+
+            true
+        })
+    }
+
+    /// Registers a new sender.
+    pub(crate) fn add_sender(&self) {
+        super::execution(|execution| self.get_state(&mut execution.objects).senders += 1);
+    }
+
+    /// Drops a sender. Once the last one is gone, blocked receivers wake up
+    /// and fail.
+    pub(crate) fn drop_sender(&self, location: Location) {
+        self.state.branch_action(Action::MsgSend, location);
+        super::execution(|execution| {
+            let state = self.get_state(&mut execution.objects);
+            state.senders -= 1;
+
+            if state.senders == 0 {
+                self.set_waiters(execution, Action::MsgRecv, true);
+            }
+        })
+    }
+
+    /// Drops the receiver. Blocked senders wake up and fail.
+    pub(crate) fn drop_receiver(&self, location: Location) {
+        self.state.branch_action(Action::MsgRecv, location);
+        super::execution(|execution| {
+            self.get_state(&mut execution.objects).receiver = false;
+            self.set_waiters(execution, Action::MsgSend, true);
         })
     }
 
@@ -137,6 +191,45 @@ impl Channel {
         super::execution(|execution| self.get_state(&mut execution.objects).msg_cnt == 0)
     }
 
+    /// Returns `true` if all senders were dropped.
+    pub(crate) fn is_disconnected(&self) -> bool {
+        super::execution(|execution| self.get_state(&mut execution.objects).senders == 0)
+    }
+
+    /// Returns `true` if the channel is bounded, full, and can still be
+    /// received from.
+    pub(crate) fn is_full(&self) -> bool {
+        super::execution(|execution| {
+            let state = self.get_state(&mut execution.objects);
+            state.receiver && Some(state.msg_cnt) == state.capacity
+        })
+    }
+
+    /// Makes the **other** threads about to perform `action` on this channel
+    /// runnable, or blocks them.
+    fn set_waiters(&self, execution: &mut super::Execution, action: Action, runnable: bool) {
+        let thread_id = execution.threads.active_id();
+
+        for (id, thread) in execution.threads.iter_mut() {
+            if id == thread_id {
+                continue;
+            }
+
+            if let Some(operation) = thread.operation.as_ref() {
+                if operation.object() == self.state.erase()
+                    && operation.action() == object::Action::Channel(action)
+                {
+                    if runnable {
+                        thread.set_runnable();
+                    } else {
+                        let location = operation.location();
+                        thread.set_blocked(location);
+                    }
+                }
+            }
+        }
+    }
+
     fn get_state<'a>(&self, objects: &'a mut object::Store) -> &'a mut State {
         self.state.get_mut(objects)
     }
@@ -146,18 +239,26 @@ impl State {
     pub(super) fn check_for_leaks(&self, index: usize) {
         if self.msg_cnt != 0 {
             if self.created.is_captured() {
-                panic!(
-                    "Messages leaked.\n  \
-                    Channel created: {}\n            \
-                    Index: {}\n        \
-                    Messages: {}",
-                    self.created, index, self.msg_cnt
-                );
+                LoomError::Leak(
+                    Failure::new(format!(
+                        "Messages leaked.\n  \
+                        Channel created: {}\n            \
+                        Index: {}\n        \
+                        Messages: {}",
+                        self.created, index, self.msg_cnt
+                    ))
+                    .object(index),
+                )
+                .raise();
             } else {
-                panic!(
-                    "Messages leaked.\n     Index: {}\n  Messages: {}",
-                    index, self.msg_cnt
-                );
+                LoomError::Leak(
+                    Failure::new(format!(
+                        "Messages leaked.\n     Index: {}\n  Messages: {}",
+                        index, self.msg_cnt
+                    ))
+                    .object(index),
+                )
+                .raise();
             }
         }
     }
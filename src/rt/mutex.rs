@@ -1,4 +1,4 @@
-use crate::rt::object;
+use crate::rt::object::{self, Operation};
 use crate::rt::{thread, Access, Location, Synchronize, VersionVec};
 
 use std::sync::atomic::Ordering::{Acquire, Release};
@@ -14,10 +14,16 @@ pub(super) struct State {
     /// If the mutex should establish sequential consistency.
     seq_cst: bool,
 
+    /// If releasing the mutex only wakes the highest-priority waiters.
+    priority_inheritance: bool,
+
     /// `Some` when the mutex is in the locked state. The `thread::Id`
     /// references the thread that currently holds the mutex.
     lock: Option<thread::Id>,
 
+    /// Set when a thread panicked while holding the lock.
+    poisoned: bool,
+
     /// Tracks access to the mutex
     last_access: Option<Access>,
 
@@ -25,17 +31,35 @@ pub(super) struct State {
     synchronize: Synchronize,
 }
 
+/// Actions performed on the mutex that need to be told apart from a blocking
+/// lock, which is an opaque action.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(super) enum Action {
+    /// Attempt to lock the mutex without blocking
+    TryLock,
+}
+
 impl Mutex {
     pub(crate) fn new(seq_cst: bool) -> Mutex {
+        Mutex::with_config(seq_cst, false)
+    }
+
+    pub(crate) fn new_priority_inheritance(seq_cst: bool) -> Mutex {
+        Mutex::with_config(seq_cst, true)
+    }
+
+    fn with_config(seq_cst: bool, priority_inheritance: bool) -> Mutex {
         super::execution(|execution| {
             let state = execution.objects.insert(State {
                 seq_cst,
+                priority_inheritance,
                 lock: None,
+                poisoned: false,
                 last_access: None,
                 synchronize: Synchronize::new(),
             });
 
-            trace!(?state, ?seq_cst, "Mutex::new");
+            trace!(?state, ?seq_cst, ?priority_inheritance, "Mutex::new");
 
             Mutex { state }
         })
@@ -47,7 +71,7 @@ impl Mutex {
     }
 
     pub(crate) fn try_acquire_lock(&self, location: Location) -> bool {
-        self.state.branch_opaque(location);
+        self.state.branch_action(Action::TryLock, location);
         self.post_acquire()
     }
 
@@ -73,18 +97,34 @@ impl Mutex {
             }
 
             let thread_id = execution.threads.active_id();
+            let obj = self.state.erase();
+
+            let is_waiter = |id, thread: &thread::Thread| {
+                id != thread_id
+                    && thread
+                        .operation
+                        .as_ref()
+                        .map(|operation| operation.object())
+                        == Some(obj)
+            };
+
+            // A priority-inheritance mutex hands the lock to its
+            // highest-priority waiters. Lower-priority waiters stay blocked
+            // until a later release.
+            let min_priority = if state.priority_inheritance {
+                execution
+                    .threads
+                    .iter()
+                    .filter(|&(id, thread)| is_waiter(id, thread))
+                    .map(|(_, thread)| thread.priority)
+                    .max()
+                    .unwrap_or(0)
+            } else {
+                0
+            };
 
             for (id, thread) in execution.threads.iter_mut() {
-                if id == thread_id {
-                    continue;
-                }
-
-                let obj = thread
-                    .operation
-                    .as_ref()
-                    .map(|operation| operation.object());
-
-                if obj == Some(self.state.erase()) {
+                if is_waiter(id, thread) && thread.priority >= min_priority {
                     trace!(state = ?self.state, thread = ?id,
                         "Mutex::release_lock");
                     thread.set_runnable();
@@ -112,14 +152,15 @@ impl Mutex {
                 execution.threads.seq_cst();
             }
 
-            // Block all **other** threads attempting to acquire the mutex
+            // Block all **other** threads attempting to acquire the mutex.
+            // `try_lock` does not block, it fails once it runs.
             for (id, thread) in execution.threads.iter_mut() {
                 if id == thread_id {
                     continue;
                 }
 
                 if let Some(operation) = thread.operation.as_ref() {
-                    if operation.object() == self.state.erase() {
+                    if operation.object() == self.state.erase() && !is_try_lock(operation) {
                         let location = operation.location();
                         trace!(state = ?self.state, thread = ?id,
                             "Mutex::post_acquire");
@@ -132,6 +173,32 @@ impl Mutex {
         })
     }
 
+    /// Checks that every release of the mutex happens-before the current
+    /// thread, as `get_mut` and `into_inner` require.
+    pub(crate) fn assert_exclusive(&self, location: Location) {
+        super::execution(|execution| {
+            self.state
+                .get(&execution.objects)
+                .synchronize
+                .assert_exclusive(&execution.threads, "Mutex", location);
+        })
+    }
+
+    /// Marks the mutex as poisoned. Called by the holder while panicking.
+    pub(crate) fn poison(&self) {
+        super::execution(|execution| self.state.get_mut(&mut execution.objects).poisoned = true)
+    }
+
+    /// Resets the poisoned flag.
+    pub(crate) fn clear_poison(&self) {
+        super::execution(|execution| self.state.get_mut(&mut execution.objects).poisoned = false)
+    }
+
+    /// Returns `true` if a thread panicked while holding the lock.
+    pub(crate) fn is_poisoned(&self) -> bool {
+        super::execution(|execution| self.state.get(&execution.objects).poisoned)
+    }
+
     /// Returns `true` if the mutex is currently locked
     fn is_locked(&self) -> bool {
         super::execution(|execution| {
@@ -142,6 +209,15 @@ impl Mutex {
             is_locked
         })
     }
+
+    /// Returns `true` if both handles refer to the same mutex
+    pub(super) fn ref_eq(&self, other: &Mutex) -> bool {
+        self.state.ref_eq(other.state)
+    }
+}
+
+fn is_try_lock(operation: &Operation) -> bool {
+    operation.action() == object::Action::Mutex(Action::TryLock)
 }
 
 impl State {
@@ -149,6 +225,11 @@ impl State {
         self.last_access.as_ref()
     }
 
+    /// The thread holding the lock, if any.
+    pub(super) fn holder(&self) -> Option<thread::Id> {
+        self.lock
+    }
+
     pub(crate) fn set_last_access(&mut self, path_id: usize, version: &VersionVec) {
         Access::set_or_create(&mut self.last_access, path_id, version);
     }
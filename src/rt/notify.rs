@@ -87,7 +87,7 @@ impl Notify {
     pub(crate) fn wait(self, location: Location) {
         let (notified, spurious) = rt::execution(|execution| {
             let spurious = if self.state.get(&execution.objects).might_spur() {
-                execution.path.branch_spurious()
+                execution.path.branch_spurious(&execution.threads)
             } else {
                 false
             };
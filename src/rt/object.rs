@@ -1,5 +1,7 @@
+use crate::model::{Failure, LoomError};
 use crate::rt;
-use crate::rt::{Access, Execution, Location, VersionVec};
+use crate::rt::location::{self, Location};
+use crate::rt::{Access, Execution, VersionVec};
 
 use std::fmt;
 use std::marker::PhantomData;
@@ -15,6 +17,10 @@ use serde::{Deserialize, Serialize};
 pub(super) struct Store<T = Entry> {
     /// Stored state for all objects.
     entries: Vec<T>,
+
+    /// Maximum number of objects the store may hold, if bounded.
+    #[cfg_attr(feature = "checkpoint", serde(skip))]
+    max_len: Option<usize>,
 }
 
 pub(super) trait Object: Sized {
@@ -63,6 +69,9 @@ pub(super) enum Action {
     /// Action on a channel
     Channel(rt::mpsc::Action),
 
+    /// Action on a mutex
+    Mutex(rt::mutex::Action),
+
     /// Action on a RwLock
     RwLock(rt::rwlock::Action),
 
@@ -147,9 +156,14 @@ impl<T> Store<T> {
     pub(super) fn with_capacity(capacity: usize) -> Store<T> {
         Store {
             entries: Vec::with_capacity(capacity),
+            max_len: None,
         }
     }
 
+    pub(super) fn set_max_len(&mut self, max_len: Option<usize>) {
+        self.max_len = max_len;
+    }
+
     pub(super) fn len(&self) -> usize {
         self.entries.len()
     }
@@ -168,6 +182,20 @@ impl<T> Store<T> {
         O: Object<Entry = T>,
     {
         let index = self.entries.len();
+
+        if let Some(max_len) = self.max_len {
+            // Avoid a double panic if objects are created while unwinding.
+            if index >= max_len && !std::thread::panicking() {
+                let message = format!(
+                    "Model created more than {} objects. This is likely caused by \
+                     creating loom objects in an unbounded loop.",
+                    max_len,
+                );
+
+                LoomError::ObjectLimitExceeded(Failure::new(message)).raise();
+            }
+        }
+
         self.entries.push(item.into_entry());
 
         Ref {
@@ -199,11 +227,17 @@ impl<T> Store<T> {
             })
     }
 
-    pub(super) fn iter_mut<'a, O>(&'a mut self) -> impl DoubleEndedIterator<Item = &'a mut O>
+    /// Iterates the objects of type `O` along with their index.
+    pub(super) fn iter_mut<'a, O>(
+        &'a mut self,
+    ) -> impl DoubleEndedIterator<Item = (usize, &'a mut O)>
     where
         O: Object<Entry = T> + 'a,
     {
-        self.entries.iter_mut().filter_map(O::get_mut)
+        self.entries
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, entry)| Some((index, O::get_mut(entry)?)))
     }
 }
 
@@ -246,6 +280,39 @@ impl Store {
         }
     }
 
+    /// Names the object a blocked thread waits on, e.g. `Mutex#3`, and the
+    /// threads holding it, if any.
+    ///
+    /// Parked threads no longer have an operation, so condvar waiters are
+    /// found through the condvars' waiter lists.
+    pub(super) fn blocked_on(
+        &self,
+        thread: rt::thread::Id,
+        operation: Option<Operation>,
+    ) -> Option<(String, Vec<rt::thread::Id>)> {
+        let index = match operation {
+            Some(operation) => operation.obj.index,
+            None => self.entries.iter().position(|entry| match entry {
+                Entry::Condvar(entry) => entry.is_waiting(thread),
+                _ => false,
+            })?,
+        };
+
+        let (kind, holders) = match &self.entries[index] {
+            Entry::Alloc(_) => ("Alloc", vec![]),
+            Entry::Arc(_) => ("Arc", vec![]),
+            Entry::Atomic(_) => ("Atomic", vec![]),
+            Entry::Mutex(entry) => ("Mutex", entry.holder().into_iter().collect()),
+            Entry::Condvar(_) => ("Condvar", vec![]),
+            Entry::Notify(_) => ("Notify", vec![]),
+            Entry::RwLock(entry) => ("RwLock", entry.holders()),
+            Entry::Channel(_) => ("Channel", vec![]),
+            Entry::Cell(_) => ("Cell", vec![]),
+        };
+
+        Some((format!("{}#{}", kind, index), holders))
+    }
+
     /// Panics if any leaks were detected
     pub(crate) fn check_for_leaks(&self) {
         for (index, entry) in self.entries.iter().enumerate() {
@@ -260,6 +327,11 @@ impl Store {
 }
 
 impl<T> Ref<T> {
+    /// Index of the object in the store
+    pub(super) fn index(self) -> usize {
+        self.index
+    }
+
     /// Erase the type marker
     pub(super) fn erase(self) -> Ref<()> {
         Ref {
@@ -310,10 +382,7 @@ impl Ref {
 
 impl<T> Clone for Ref<T> {
     fn clone(&self) -> Ref<T> {
-        Ref {
-            index: self.index,
-            _p: PhantomData,
-        }
+        *self
     }
 }
 
@@ -355,6 +424,20 @@ impl<T: Object<Entry = Entry>> Ref<T> {
         })
     }
 
+    /// Records `action` like `branch_action`, without a scheduling branch.
+    pub(super) fn record_action(
+        self,
+        action: impl Into<Action> + std::fmt::Debug,
+        location: Location,
+    ) {
+        super::execution(|execution| {
+            trace!(obj = ?self, ?action, "Object::record_action");
+
+            self.set_action(execution, action.into(), location);
+            execution.record();
+        })
+    }
+
     pub(super) fn branch_disable(
         self,
         action: impl Into<Action> + std::fmt::Debug,
@@ -384,7 +467,33 @@ impl<T: Object<Entry = Entry>> Ref<T> {
             self
         );
 
-        execution.threads.active_mut().operation = Some(Operation {
+        execution.operations += 1;
+
+        if let Some(max_operations) = execution.max_operations {
+            // Avoid a double panic if `Drop` impls run operations while
+            // unwinding.
+            if execution.operations > max_operations && !std::thread::panicking() {
+                let message = location::panic(format!(
+                    "single permutation exceeded {} operations; possible infinite \
+                     loop in the model",
+                    max_operations,
+                ))
+                .thread("operation", execution.threads.active_id(), location)
+                .message();
+
+                LoomError::OperationLimitExceeded(Failure::new(message)).raise();
+            }
+        }
+
+        let active = execution.threads.active_mut();
+
+        active.op_count += 1;
+
+        if !matches!(action, Action::Atomic(_)) {
+            active.observe_sync();
+        }
+
+        active.operation = Some(Operation {
             obj: self.erase(),
             action,
             location,
@@ -404,6 +513,15 @@ impl Operation {
     pub(super) fn location(&self) -> Location {
         self.location
     }
+
+    /// Returns `true` if running `self` before `other` may not give the same
+    /// result as running them the other way around. Only loads of the same
+    /// atomic, or operations on different objects, commute.
+    pub(super) fn is_dependent(&self, other: &Operation) -> bool {
+        let load = Action::Atomic(rt::atomic::Action::Load);
+
+        self.obj.index == other.obj.index && (self.action != load || other.action != load)
+    }
 }
 
 impl From<Action> for rt::arc::Action {
@@ -451,6 +569,12 @@ impl From<rt::mpsc::Action> for Action {
     }
 }
 
+impl From<rt::mutex::Action> for Action {
+    fn from(action: rt::mutex::Action) -> Self {
+        Action::Mutex(action)
+    }
+}
+
 impl From<rt::rwlock::Action> for Action {
     fn from(action: rt::rwlock::Action) -> Self {
         Action::RwLock(action)
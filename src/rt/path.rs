@@ -32,6 +32,78 @@ pub(crate) struct Path {
 
     /// How to reset the `exploring` state
     exploring_on_start: bool,
+
+    /// Number of `stop_exploring` calls not yet matched by `explore`.
+    critical_depth: usize,
+
+    /// Deepest critical section nesting reached by any execution so far.
+    max_critical_depth: usize,
+
+    /// When set, branches are picked at random instead of being enumerated.
+    #[cfg_attr(feature = "checkpoint", serde(skip))]
+    random: Option<Random>,
+
+    /// When set, only part of the executions are explored, the rest being
+    /// left to other workers.
+    #[cfg_attr(feature = "checkpoint", serde(skip))]
+    partition: Option<Partition>,
+
+    /// When set, a thread whose next operation was already explored from an
+    /// equivalent state is put to sleep rather than explored again.
+    #[cfg_attr(feature = "checkpoint", serde(skip))]
+    sleep_sets: bool,
+
+    /// Threads to put to sleep at the next new schedule branch, one bit per
+    /// thread.
+    #[cfg_attr(feature = "checkpoint", serde(skip))]
+    sleep_next: u8,
+
+    /// Choices to take at new branches instead of the default ones, indexed
+    /// by position in the path.
+    #[cfg_attr(feature = "checkpoint", serde(skip))]
+    replay: Vec<Choice>,
+}
+
+/// The option taken at a branch, see `Path::choices`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Choice {
+    /// Whatever the branch would pick on its own.
+    Default,
+
+    /// Run the thread with the given index.
+    Thread(u8),
+
+    /// Read the store at the given position in the load's candidates.
+    Load(u8),
+
+    /// Return spuriously, or not.
+    Spurious(bool),
+}
+
+/// Splits the first choice between several threads across workers. Each
+/// worker explores its share of the threads there, and everything below them.
+#[derive(Debug)]
+struct Partition {
+    worker: usize,
+
+    workers: usize,
+
+    /// Position of the split branch, once it has been reached.
+    root: Option<usize>,
+
+    /// Set when the worker's share of the split is empty. The execution then
+    /// runs to completion, but nothing below the split is explored.
+    idle: bool,
+}
+
+/// Random exploration state.
+#[derive(Debug)]
+struct Random {
+    /// xorshift64* state. Never zero.
+    state: u64,
+
+    /// Number of executions left to run, including the current one.
+    remaining: usize,
 }
 
 #[derive(Debug)]
@@ -104,24 +176,52 @@ pub(crate) enum Thread {
 
     /// The thread has been explored
     Visited,
+
+    /// Running the thread here is equivalent to an order that was already
+    /// explored, so it is not explored again.
+    Sleep,
 }
 
 macro_rules! assert_path_len {
-    ($branches:expr) => {{
-        assert!(
-            // if we are panicking, we may be performing a branch due to a
-            // `Drop` impl (e.g., for `Arc`, or for a user type that does an
-            // atomic operation in its `Drop` impl). if that's the case,
-            // asserting this again will double panic. therefore, short-circuit
-            // the assertion if the thread is panicking.
-            $branches.len() < $branches.capacity() || std::thread::panicking(),
-            "Model exceeded maximum number of branches. This is often caused \
-             by an algorithm requiring the processor to make progress, e.g. \
-             spin locks.",
-        );
+    ($branches:expr, $threads:expr) => {{
+        // if we are panicking, we may be performing a branch due to a `Drop`
+        // impl (e.g., for `Arc`, or for a user type that does an atomic
+        // operation in its `Drop` impl). if that's the case, raising this
+        // again will double panic. therefore, short-circuit the check if the
+        // thread is panicking.
+        if $branches.len() >= $branches.capacity() && !std::thread::panicking() {
+            branch_limit_exceeded($branches.capacity(), $threads);
+        }
     }};
 }
 
+/// Raises `BranchLimitExceeded`, naming the thread that was about to branch.
+fn branch_limit_exceeded(max_branches: usize, threads: &thread::Set) -> ! {
+    let mut message = format!(
+        "Model exceeded maximum number of branches ({}). This is often caused \
+         by an algorithm requiring the processor to make progress, e.g. spin \
+         locks. Otherwise, raise the limit with `Builder::max_branches` (or \
+         LOOM_MAX_BRANCHES), or bound the exploration with \
+         `Builder::max_preemptions`.",
+        max_branches
+    );
+
+    if threads.is_active() {
+        let id = threads.active_id();
+        message.push_str(&format!("\n  Thread #{}", id.as_usize()));
+
+        if let Some(operation) = threads.active().operation {
+            message.push_str(&format!(": {:?}", operation.action()));
+
+            if operation.location().is_captured() {
+                message.push_str(&format!(" @ {}", operation.location()));
+            }
+        }
+    }
+
+    crate::model::LoomError::BranchLimitExceeded(crate::model::Failure::new(message)).raise()
+}
+
 impl Path {
     /// Create a new, blank, configured to branch at most `max_branches` times
     /// and at most `preemption_bound` thread preemptions.
@@ -133,23 +233,165 @@ impl Path {
             exploring,
             skipping: false,
             exploring_on_start: exploring,
+            critical_depth: 0,
+            max_critical_depth: 0,
+            random: None,
+            partition: None,
+            sleep_sets: false,
+            sleep_next: 0,
+            replay: Vec::new(),
         }
     }
 
+    /// Explore `iterations` random executions, seeded with `seed`, instead of
+    /// enumerating every execution.
+    pub(crate) fn set_random(&mut self, iterations: usize, seed: u64) {
+        self.random = Some(Random::new(iterations, seed));
+    }
+
+    /// Explore only the share of `worker` out of `workers`.
+    pub(crate) fn set_partition(&mut self, worker: usize, workers: usize) {
+        self.partition = Some(Partition {
+            worker,
+            workers,
+            root: None,
+            idle: false,
+        });
+    }
+
+    /// Skip orders of independent operations that are equivalent to an order
+    /// already explored. Has no effect on bounded or random exploration,
+    /// which must be set up first.
+    pub(crate) fn set_sleep_sets(&mut self) {
+        self.sleep_sets = self.preemption_bound.is_none() && self.random.is_none();
+    }
+
+    /// Take `choices` at the branches they line up with, where the choice is
+    /// possible. Other branches pick their default.
+    pub(crate) fn set_replay(&mut self, choices: Vec<Choice>) {
+        self.replay = choices;
+    }
+
+    /// The choice to replay at the branch at `pos`, if any.
+    fn replay(&self, pos: usize) -> Choice {
+        self.replay.get(pos).copied().unwrap_or(Choice::Default)
+    }
+
+    /// Returns the option taken at each branch so far by the current
+    /// execution.
+    pub(crate) fn choices(&self) -> Vec<Choice> {
+        (0..self.pos.min(self.branches.len()))
+            .map(|pos| {
+                let branch = object::Ref::from_usize(pos);
+
+                if let Some(schedule) = branch.downcast::<Schedule>(&self.branches) {
+                    schedule
+                        .get(&self.branches)
+                        .active_thread_index()
+                        .map_or(Choice::Default, Choice::Thread)
+                } else if let Some(load) = branch.downcast::<Load>(&self.branches) {
+                    Choice::Load(load.get(&self.branches).pos)
+                } else if let Some(spurious) = branch.downcast::<Spurious>(&self.branches) {
+                    Choice::Spurious(spurious.get(&self.branches).spur)
+                } else {
+                    unreachable!()
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the number of schedule and load branches taken so far by the
+    /// current execution.
+    pub(crate) fn branch_kinds(&self) -> (usize, usize) {
+        let taken = self.pos.min(self.branches.len());
+        let schedules = (0..taken)
+            .filter(|&pos| {
+                object::Ref::from_usize(pos)
+                    .downcast::<Schedule>(&self.branches)
+                    .is_some()
+            })
+            .count();
+        let loads = (0..taken)
+            .filter(|&pos| {
+                object::Ref::from_usize(pos)
+                    .downcast::<Load>(&self.branches)
+                    .is_some()
+            })
+            .count();
+
+        (schedules, loads)
+    }
+
+    /// Returns how far the current execution strays from the default
+    /// choices: the number of preemptions, then the number of thread
+    /// switches, non-default loads and spurious returns.
+    pub(crate) fn complexity(&self) -> (u8, usize) {
+        let mut preemptions = 0;
+        let mut choices = 0;
+        let mut prev = None;
+
+        for pos in 0..self.pos.min(self.branches.len()) {
+            let branch = object::Ref::from_usize(pos);
+
+            if let Some(schedule) = branch.downcast::<Schedule>(&self.branches) {
+                let schedule = schedule.get(&self.branches);
+                let active = schedule.active_thread_index();
+
+                preemptions = schedule.preemptions();
+
+                if prev.is_some() && active != prev {
+                    choices += 1;
+                }
+
+                prev = active;
+            } else if let Some(load) = branch.downcast::<Load>(&self.branches) {
+                choices += usize::from(load.get(&self.branches).pos != 0);
+            } else if let Some(spurious) = branch.downcast::<Spurious>(&self.branches) {
+                choices += usize::from(spurious.get(&self.branches).spur);
+            }
+        }
+
+        (preemptions, choices)
+    }
+
+    /// Returns `true` if this worker's share of the current split is empty,
+    /// so the execution is not one of the worker's own.
+    pub(crate) fn is_idle(&self) -> bool {
+        self.partition
+            .as_ref()
+            .is_some_and(|partition| partition.idle)
+    }
+
     pub(crate) fn explore_state(&mut self) {
         if !self.skipping {
-            assert!(!self.exploring, "not in critical state");
-            self.exploring = true;
+            if self.critical_depth == 0 {
+                assert!(!self.exploring, "not in critical state");
+                self.exploring = true;
+            } else {
+                // Only the outermost critical section re-enables exploring.
+                self.critical_depth -= 1;
+                self.exploring = self.critical_depth == 0;
+            }
         }
     }
 
     pub(crate) fn critical(&mut self) {
         if !self.skipping {
-            assert!(self.exploring, "not in exploring state");
+            assert!(
+                self.exploring || self.critical_depth > 0,
+                "not in exploring state"
+            );
             self.exploring = false;
+            self.critical_depth += 1;
+            self.max_critical_depth = self.max_critical_depth.max(self.critical_depth);
         }
     }
 
+    /// Deepest nesting of `stop_exploring` calls across all executions.
+    pub(crate) fn max_critical_depth(&self) -> usize {
+        self.max_critical_depth
+    }
+
     pub(crate) fn skip_branch(&mut self) {
         self.exploring = false;
         self.skipping = true;
@@ -166,13 +408,15 @@ impl Path {
         self.pos == self.branches.len()
     }
 
-    pub(super) fn pos(&self) -> usize {
+    pub(crate) fn pos(&self) -> usize {
         self.pos
     }
 
     /// Push a new atomic-load branch
-    pub(super) fn push_load(&mut self, seed: &[u8]) {
-        assert_path_len!(self.branches);
+    pub(super) fn push_load(&mut self, seed: &[u8], threads: &thread::Set) {
+        assert_path_len!(self.branches, threads);
+
+        let replay = self.replay(self.pos);
 
         let load_ref = self.branches.insert(Load {
             values: [0; MAX_ATOMIC_HISTORY],
@@ -200,6 +444,16 @@ impl Path {
             load.values[i] = store;
             load.len += 1;
         }
+
+        if let Some(random) = self.random.as_mut().filter(|_| self.exploring) {
+            load.pos = random.below(load.len as usize) as u8;
+        }
+
+        if let Choice::Load(pos) = replay {
+            if pos < load.len {
+                load.pos = pos;
+            }
+        }
     }
 
     /// Returns the atomic write to read
@@ -217,12 +471,18 @@ impl Path {
     }
 
     /// Branch on spurious notifications
-    pub(super) fn branch_spurious(&mut self) -> bool {
+    pub(super) fn branch_spurious(&mut self, threads: &thread::Set) -> bool {
         if self.is_traversed() {
-            assert_path_len!(self.branches);
+            assert_path_len!(self.branches, threads);
+
+            let spur = match (self.replay(self.pos), self.random.as_mut()) {
+                (Choice::Spurious(spur), _) => spur,
+                (_, Some(random)) if self.exploring => random.below(2) == 1,
+                _ => false,
+            };
 
             self.branches.insert(Spurious {
-                spur: false,
+                spur,
                 exploring: self.exploring,
             });
         }
@@ -241,14 +501,17 @@ impl Path {
     pub(super) fn branch_thread(
         &mut self,
         execution_id: execution::Id,
+        threads: &thread::Set,
         seed: impl ExactSizeIterator<Item = Thread>,
     ) -> Option<thread::Id> {
         if self.is_traversed() {
-            assert_path_len!(self.branches);
+            assert_path_len!(self.branches, threads);
 
             // Find the last thread scheduling branch in the path
             let prev = self.last_schedule();
 
+            let replay = self.replay(self.pos);
+
             // Entering a new exploration space.
             //
             // Initialize a  new branch. The initial field values don't matter
@@ -282,6 +545,31 @@ impl Path {
                 }
             }
 
+            // Set when every thread that may run is asleep.
+            let mut redundant = None;
+
+            if self.sleep_sets && self.exploring && self.sleep_next != 0 {
+                for (i, th) in schedule.threads.iter_mut().enumerate() {
+                    if self.sleep_next & (1 << i) != 0
+                        && matches!(th, Thread::Active | Thread::Skip)
+                    {
+                        *th = Thread::Sleep;
+                    }
+                }
+
+                if let Some(i) = active.filter(|&i| schedule.threads[i as usize] == Thread::Sleep) {
+                    active = None;
+
+                    match schedule.threads.iter().position(|th| *th == Thread::Skip) {
+                        Some(j) => {
+                            schedule.threads[j] = Thread::Active;
+                            active = Some(j as u8);
+                        }
+                        None => redundant = Some(i),
+                    }
+                }
+            }
+
             // Ensure at least one thread is active, otherwise toggle a yielded
             // thread.
             if active.is_none() {
@@ -294,6 +582,16 @@ impl Path {
                 }
             }
 
+            if let Some(i) = redundant.filter(|_| active.is_none()) {
+                // Everything below was covered by an equivalent order. Finish
+                // the execution without exploring anything new.
+                schedule.threads[i as usize] = Thread::Active;
+                schedule.exploring = false;
+                active = Some(i);
+                self.exploring = false;
+                self.skipping = true;
+            }
+
             let mut initial_active = active;
 
             if let Some(prev) = prev {
@@ -316,6 +614,29 @@ impl Path {
             let schedule = schedule_ref.get_mut(&mut self.branches);
             schedule.initial_active = initial_active;
             schedule.preemptions = preemptions;
+
+            // Switching away from a thread that could keep running is a
+            // preemption, which the bound may not allow.
+            let bounded = initial_active.is_some()
+                && self
+                    .preemption_bound
+                    .is_some_and(|bound| preemptions >= bound);
+
+            if let Some(partition) = &mut self.partition {
+                if partition.root.is_none() && self.exploring && !bounded {
+                    partition.split(schedule_ref.index(), &mut schedule.threads);
+                }
+            }
+
+            if let Some(random) = self.random.as_mut().filter(|_| self.exploring) {
+                if !bounded {
+                    random.pick_thread(&mut schedule.threads);
+                }
+            }
+
+            if let Choice::Thread(i) = replay {
+                schedule.replay(i as usize);
+            }
         }
 
         let schedule = object::Ref::from_usize(self.pos)
@@ -325,12 +646,15 @@ impl Path {
 
         self.pos += 1;
 
-        schedule
-            .threads
-            .iter()
-            .enumerate()
-            .find(|&(_, th)| th.is_active())
-            .map(|(i, _)| thread::Id::new(execution_id, i))
+        let active = schedule.active_thread_index();
+
+        self.sleep_next = if self.sleep_sets && schedule.exploring {
+            schedule.sleep_next(active, threads)
+        } else {
+            0
+        };
+
+        active.map(|i| thread::Id::new(execution_id, i as usize))
     }
 
     pub(super) fn backtrack(&mut self, mut point: usize, thread_id: thread::Id) {
@@ -375,7 +699,7 @@ impl Path {
 
                     curr = prev;
                 } else {
-                    if curr.get(&mut self.branches).exploring {
+                    if curr.get(&self.branches).exploring {
                         // This is the very first schedule
                         curr.get_mut(&mut self.branches)
                             .backtrack(thread_id, self.preemption_bound);
@@ -386,11 +710,9 @@ impl Path {
         }
     }
 
-    /// Reset the path to prepare for the next exploration of the model.
-    ///
-    /// This function will also trim the object store, dropping any objects that
-    /// are created in pruned sections of the path.
-    pub(super) fn step(&mut self) -> bool {
+    /// Rewind the path so the next execution takes the same branches as the
+    /// current one.
+    pub(crate) fn rewind(&mut self) {
         // Reset the position to zero, the path will start traversing from the
         // beginning
         self.pos = 0;
@@ -398,11 +720,30 @@ impl Path {
         // Reset exploring / critical / skip
         self.exploring = self.exploring_on_start;
         self.skipping = false;
+        self.critical_depth = 0;
+        self.sleep_next = 0;
+    }
+
+    /// Reset the path to prepare for the next exploration of the model.
+    ///
+    /// This function will also trim the object store, dropping any objects that
+    /// are created in pruned sections of the path.
+    pub(super) fn step(&mut self) -> bool {
+        self.rewind();
+
+        // Random executions start over from an empty path every time.
+        if let Some(random) = &mut self.random {
+            self.branches.clear();
+            random.remaining = random.remaining.saturating_sub(1);
+            return random.remaining > 0;
+        }
 
         // Set the final branch to try the next option. If all options have been
         // traversed, pop the final branch and try again w/ the one under it.
         //
-        // This is depth-first tree traversal.
+        // This is depth-first tree traversal. It is iterative, so stack usage
+        // does not grow with the number of branches, which is itself bounded
+        // by `max_branches`.
         //
         for last in (0..self.branches.len()).rev() {
             let last = object::Ref::from_usize(last);
@@ -410,6 +751,17 @@ impl Path {
             // Remove all objects that were created **after** this branch
             self.branches.truncate(last);
 
+            if let Some(partition) = &mut self.partition {
+                if partition.root.is_some_and(|root| root > last.index()) {
+                    // Backtracked above the split, the next one is fresh.
+                    partition.root = None;
+                    partition.idle = false;
+                } else if partition.idle {
+                    // Other workers explore everything below an empty share.
+                    continue;
+                }
+            }
+
             if let Some(schedule_ref) = last.downcast::<Schedule>(&self.branches) {
                 let schedule = schedule_ref.get_mut(&mut self.branches);
 
@@ -467,7 +819,109 @@ impl Path {
     }
 
     fn last_schedule(&self) -> Option<object::Ref<Schedule>> {
-        self.branches.iter_ref::<Schedule>().rev().next()
+        self.branches.iter_ref::<Schedule>().next_back()
+    }
+
+    /// Returns the thread picked at each scheduling branch taken so far by
+    /// the current execution.
+    pub(crate) fn schedule_trace(&self) -> Vec<usize> {
+        (0..self.pos.min(self.branches.len()))
+            .filter_map(|pos| object::Ref::from_usize(pos).downcast::<Schedule>(&self.branches))
+            .filter_map(|schedule| schedule.get(&self.branches).active_thread_index())
+            .map(usize::from)
+            .collect()
+    }
+}
+
+impl Partition {
+    /// Makes the branch at `pos` the split if several threads may run there,
+    /// keeping only this worker's share of them.
+    fn split(&mut self, pos: usize, threads: &mut [Thread; MAX_THREADS]) {
+        let candidates: Vec<_> = (0..MAX_THREADS)
+            .filter(|&i| matches!(threads[i], Thread::Active | Thread::Skip))
+            .collect();
+
+        if candidates.len() < 2 {
+            return;
+        }
+
+        self.root = Some(pos);
+
+        let mut share = candidates
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j % self.workers == self.worker)
+            .map(|(_, &i)| i);
+
+        let first = match share.next() {
+            Some(first) => first,
+            None => {
+                // Run the default choice without counting it.
+                self.idle = true;
+                return;
+            }
+        };
+
+        // Other workers' threads are never explored here, even when
+        // backtracking.
+        for &i in &candidates {
+            threads[i] = Thread::Disabled;
+        }
+
+        threads[first] = Thread::Active;
+
+        for i in share {
+            threads[i] = Thread::Pending;
+        }
+    }
+}
+
+impl Random {
+    fn new(iterations: usize, seed: u64) -> Random {
+        Random {
+            // xorshift gets stuck on zero.
+            state: seed | 1,
+            remaining: iterations,
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Returns a number in `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// Makes a random thread that may run active, keeping yielded threads
+    /// for when no other thread can run.
+    fn pick_thread(&mut self, threads: &mut [Thread; MAX_THREADS]) {
+        let candidates = threads
+            .iter()
+            .filter(|th| matches!(th, Thread::Active | Thread::Skip))
+            .count();
+
+        if candidates < 2 {
+            return;
+        }
+
+        let pick = self.below(candidates);
+
+        for (i, th) in threads
+            .iter_mut()
+            .filter(|th| matches!(th, Thread::Active | Thread::Skip))
+            .enumerate()
+        {
+            *th = if i == pick {
+                Thread::Active
+            } else {
+                Thread::Skip
+            };
+        }
     }
 }
 
@@ -481,6 +935,47 @@ impl Schedule {
             .map(|(index, _)| index as u8)
     }
 
+    /// Runs the thread at index `i` instead of the active one, if it may run.
+    fn replay(&mut self, i: usize) {
+        if !matches!(self.threads.get(i), Some(Thread::Skip | Thread::Yield)) {
+            return;
+        }
+
+        for th in &mut self.threads {
+            if th.is_active() {
+                *th = Thread::Skip;
+            }
+        }
+
+        self.threads[i] = Thread::Active;
+    }
+
+    /// Returns the threads to put to sleep after the active thread runs: the
+    /// threads asleep or already explored here, as long as their next
+    /// operation does not depend on the active thread's.
+    fn sleep_next(&self, active: Option<u8>, threads: &thread::Set) -> u8 {
+        let operation = |i: usize| threads.iter().nth(i).and_then(|(_, th)| th.operation);
+
+        let active = match active.and_then(|i| operation(i as usize)) {
+            Some(active) => active,
+            None => return 0,
+        };
+
+        let mut sleep = 0;
+
+        for (i, th) in self.threads.iter().enumerate() {
+            if !matches!(th, Thread::Sleep | Thread::Visited) {
+                continue;
+            }
+
+            if operation(i).is_some_and(|operation| !operation.is_dependent(&active)) {
+                sleep |= 1 << i;
+            }
+        }
+
+        sleep
+    }
+
     /// Compute the number of preemptions for the current state of the branch
     fn preemptions(&self) -> u8 {
         if self.initial_active.is_some() && self.initial_active != self.active_thread_index() {
@@ -523,6 +1018,9 @@ impl Schedule {
 }
 
 impl Thread {
+    /// Marks a skipped thread to be explored. A sleeping thread stays asleep
+    /// even when a race asks to backtrack to it, as the orders it leads to
+    /// are equivalent to ones explored through its earlier sibling.
     fn explore(&mut self) {
         if *self == Thread::Skip {
             *self = Thread::Pending;
@@ -22,6 +22,12 @@ pub(super) enum Action {
 
     /// Write lock
     Write,
+
+    /// Upgradable read lock
+    UpgradableRead,
+
+    /// Upgrade of an upgradable read lock to a write lock
+    Upgrade,
 }
 
 #[derive(Debug)]
@@ -30,6 +36,10 @@ pub(super) struct State {
     /// A set of `thread::Id` when Read locked.
     lock: Option<Locked>,
 
+    /// The reader holding the upgradable read lock, if any. It is also part
+    /// of the `Locked::Read` set.
+    upgradable: Option<thread::Id>,
+
     /// Tracks write access to the rwlock.
     last_access: Option<Access>,
 
@@ -43,6 +53,7 @@ impl RwLock {
         super::execution(|execution| {
             let state = execution.objects.insert(State {
                 lock: None,
+                upgradable: None,
                 last_access: None,
                 synchronize: Synchronize::new(),
             });
@@ -78,6 +89,65 @@ impl RwLock {
         );
     }
 
+    /// Acquire the upgradable read lock.
+    /// Fail to acquire it if write locked or if another thread holds the
+    /// upgradable read lock.
+    pub(crate) fn acquire_upgradable_read_lock(&self, location: Location) {
+        self.state.branch_disable(
+            Action::UpgradableRead,
+            self.is_write_locked() || self.is_upgradable_locked(),
+            location,
+        );
+
+        assert!(
+            self.post_acquire_read_lock(),
+            "expected to be able to acquire upgradable read lock"
+        );
+
+        super::execution(|execution| {
+            let thread_id = execution.threads.active_id();
+            self.state.get_mut(&mut execution.objects).upgradable = Some(thread_id);
+
+            // Block all other threads attempting to acquire the upgradable
+            // read lock
+            self.block_threads(execution, thread_id, |action| {
+                action == Action::UpgradableRead
+            });
+        });
+    }
+
+    /// Upgrade the upgradable read lock held by the current thread to a write
+    /// lock. Blocks until all other readers released the lock.
+    pub(crate) fn upgrade(&self, location: Location) {
+        self.state
+            .branch_disable(Action::Upgrade, self.has_other_readers(), location);
+
+        super::execution(|execution| {
+            let state = self.state.get_mut(&mut execution.objects);
+            let thread_id = execution.threads.active_id();
+
+            assert_eq!(
+                state.upgradable.take(),
+                Some(thread_id),
+                "upgrade without holding the upgradable read lock"
+            );
+            assert!(
+                !self.has_other_readers_in(state, thread_id),
+                "expected to be able to upgrade the read lock"
+            );
+
+            state.lock = Some(Locked::Write(thread_id));
+
+            state.synchronize.sync_load(&mut execution.threads, Acquire);
+
+            // Establish sequential consistency between locks
+            execution.threads.seq_cst();
+
+            // Block all other threads attempting to acquire rwlock
+            self.block_threads(execution, thread_id, |_| true);
+        });
+    }
+
     pub(crate) fn try_acquire_read_lock(&self, location: Location) -> bool {
         self.state.branch_action(Action::Read, location);
         self.post_acquire_read_lock()
@@ -88,6 +158,17 @@ impl RwLock {
         self.post_acquire_write_lock()
     }
 
+    /// Checks that every release of the lock happens-before the current
+    /// thread, as `get_mut` and `into_inner` require.
+    pub(crate) fn assert_exclusive(&self, location: Location) {
+        super::execution(|execution| {
+            self.state
+                .get(&execution.objects)
+                .synchronize
+                .assert_exclusive(&execution.threads, "RwLock", location);
+        })
+    }
+
     pub(crate) fn release_read_lock(&self) {
         super::execution(|execution| {
             let state = self.state.get_mut(&mut execution.objects);
@@ -107,10 +188,33 @@ impl RwLock {
 
             readers.remove(&thread_id);
 
+            if state.upgradable == Some(thread_id) {
+                state.upgradable = None;
+            }
+
             if readers.is_empty() {
                 state.lock = None;
 
                 self.unlock_threads(execution, thread_id);
+                return;
+            }
+
+            // Wake the threads that only waited on this reader.
+            let upgradable = state.upgradable;
+            let upgrader_alone = upgradable
+                .is_some_and(|upgrader| readers.len() == 1 && readers.contains(&upgrader));
+
+            for (id, th) in execution.threads.iter_mut() {
+                let op = match th.operation.as_ref() {
+                    Some(op) if id != thread_id && op.object() == self.state.erase() => op,
+                    _ => continue,
+                };
+
+                if (op.action() == Action::Upgrade && upgrader_alone)
+                    || (op.action() == Action::UpgradableRead && upgradable.is_none())
+                {
+                    th.set_runnable();
+                }
             }
         });
     }
@@ -153,6 +257,56 @@ impl RwLock {
         }
     }
 
+    /// Blocks the **other** threads about to perform an action on the RwLock
+    /// that `blocks` returns `true` for.
+    fn block_threads(
+        &self,
+        execution: &mut Execution,
+        thread_id: thread::Id,
+        blocks: impl Fn(Action) -> bool,
+    ) {
+        for (id, th) in execution.threads.iter_mut() {
+            if id == thread_id {
+                continue;
+            }
+
+            let op = match th.operation.as_ref() {
+                Some(op) if op.object() == self.state.erase() => op,
+                _ => continue,
+            };
+
+            let action = match op.action() {
+                object::Action::RwLock(action) => action,
+                _ => continue,
+            };
+
+            if blocks(action) {
+                let location = op.location();
+                th.set_blocked(location);
+            }
+        }
+    }
+
+    /// Returns `true` if the upgradable read lock is held.
+    fn is_upgradable_locked(&self) -> bool {
+        super::execution(|execution| self.state.get(&execution.objects).upgradable.is_some())
+    }
+
+    /// Returns `true` if readers other than the current thread hold the lock.
+    fn has_other_readers(&self) -> bool {
+        super::execution(|execution| {
+            let thread_id = execution.threads.active_id();
+            self.has_other_readers_in(self.state.get(&execution.objects), thread_id)
+        })
+    }
+
+    fn has_other_readers_in(&self, state: &State, thread_id: thread::Id) -> bool {
+        match &state.lock {
+            Some(Locked::Read(readers)) => readers.iter().any(|&id| id != thread_id),
+            _ => false,
+        }
+    }
+
     /// Returns `true` if RwLock is read locked
     fn is_read_locked(&self) -> bool {
         super::execution(|execution| {
@@ -201,7 +355,8 @@ impl RwLock {
 
             execution.threads.seq_cst();
 
-            // Block all writer threads from attempting to acquire the RwLock
+            // Block all writer threads from attempting to acquire the RwLock,
+            // and the upgrade of another reader's upgradable lock
             for (id, th) in execution.threads.iter_mut() {
                 if id == thread_id {
                     continue;
@@ -212,7 +367,7 @@ impl RwLock {
                     _ => continue,
                 };
 
-                if op.action() == Action::Write {
+                if op.action() == Action::Write || op.action() == Action::Upgrade {
                     let location = op.location();
                     th.set_blocked(location);
                 }
@@ -265,6 +420,18 @@ impl State {
         self.last_access.as_ref()
     }
 
+    /// The threads holding the lock, in id order.
+    pub(super) fn holders(&self) -> Vec<thread::Id> {
+        let mut holders: Vec<_> = match &self.lock {
+            Some(Locked::Read(readers)) => readers.iter().copied().collect(),
+            Some(Locked::Write(writer)) => vec![*writer],
+            None => vec![],
+        };
+
+        holders.sort_by_key(|id| id.as_usize());
+        holders
+    }
+
     pub(crate) fn set_last_access(&mut self, path_id: usize, version: &VersionVec) {
         Access::set_or_create(&mut self.last_access, path_id, version)
     }
@@ -4,7 +4,7 @@ use crate::rt::Execution;
 
 use generator::{self, Generator, Gn};
 use scoped_tls::scoped_thread_local;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::VecDeque;
 
 pub(crate) struct Scheduler {
@@ -17,6 +17,12 @@ scoped_thread_local! {
     static STATE: RefCell<State<'_>>
 }
 
+thread_local! {
+    /// Set when a panic unwinds out of the runtime, e.g. because it found a
+    /// bug in the model, as opposed to a panic in the model's own code.
+    static RUNTIME_PANICKED: Cell<bool> = const { Cell::new(false) };
+}
+
 struct QueuedSpawn {
     f: Box<dyn FnOnce()>,
     stack_size: Option<usize>,
@@ -40,9 +46,26 @@ impl Scheduler {
     where
         F: FnOnce(&mut Execution) -> R,
     {
+        struct Guard(bool);
+
+        impl Drop for Guard {
+            fn drop(&mut self) {
+                if std::thread::panicking() && !self.0 {
+                    RUNTIME_PANICKED.with(|panicked| panicked.set(true));
+                }
+            }
+        }
+
+        let _guard = Guard(std::thread::panicking());
         Self::with_state(|state| f(state.execution))
     }
 
+    /// Returns `true` if a panic unwound out of the runtime during the current
+    /// execution.
+    pub(crate) fn runtime_panicked() -> bool {
+        RUNTIME_PANICKED.with(Cell::get)
+    }
+
     /// Perform a context switch
     pub(crate) fn switch() {
         use std::future::Future;
@@ -78,6 +101,8 @@ impl Scheduler {
     where
         F: FnOnce() + Send + 'static,
     {
+        RUNTIME_PANICKED.with(|panicked| panicked.set(false));
+
         let mut threads = Vec::new();
         threads.push(spawn_thread(Box::new(f), None));
         threads[0].resume();
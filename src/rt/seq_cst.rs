@@ -0,0 +1,161 @@
+use crate::rt::thread;
+
+use std::fmt;
+
+/// The `SeqCst` loads and stores of a single execution, in the order they
+/// ran. Loom uses this order as the single total order of `SeqCst`
+/// operations, and every `SeqCst` load is checked against it.
+#[derive(Debug, Default)]
+pub(crate) struct SeqCstOrder {
+    ops: Vec<Op>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    /// `thread` made the `store`-th store to the atomic `object` with a
+    /// `SeqCst` store or read-modify-write.
+    Store {
+        thread: usize,
+        object: usize,
+        store: u16,
+    },
+
+    /// `thread` read the `store`-th store to the atomic `object` with a
+    /// `SeqCst` load or read-modify-write.
+    Load {
+        thread: usize,
+        object: usize,
+        store: u16,
+    },
+}
+
+impl SeqCstOrder {
+    /// Records a `SeqCst` store.
+    pub(super) fn store(&mut self, thread: thread::Id, object: usize, store: u16) {
+        self.ops.push(Op::Store {
+            thread: thread.as_usize(),
+            object,
+            store,
+        });
+    }
+
+    /// Records a `SeqCst` load and checks that it read the last `SeqCst`
+    /// store to `object` before it in the order, or a newer store.
+    ///
+    /// `ordered` is `false` when the store read is not `SeqCst` and does not
+    /// happen before any newer `SeqCst` store. The load may then read it even
+    /// though it is older.
+    ///
+    /// On failure, returns the store the load should have seen.
+    pub(super) fn load(
+        &mut self,
+        thread: thread::Id,
+        object: usize,
+        store: u16,
+        ordered: bool,
+    ) -> Result<(), u16> {
+        let last = self.ops.iter().rev().find_map(|op| match *op {
+            Op::Store {
+                object: o, store, ..
+            } if o == object => Some(store),
+            _ => None,
+        });
+
+        self.ops.push(Op::Load {
+            thread: thread.as_usize(),
+            object,
+            store,
+        });
+
+        match last {
+            Some(last) if ordered && store < last => Err(last),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for SeqCstOrder {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(fmt, "SeqCst order:")?;
+
+        for op in &self.ops {
+            match *op {
+                Op::Store {
+                    thread,
+                    object,
+                    store,
+                } => writeln!(
+                    fmt,
+                    "    thread #{}: store #{} to atomic #{}",
+                    thread, store, object
+                )?,
+                Op::Load {
+                    thread,
+                    object,
+                    store,
+                } => writeln!(
+                    fmt,
+                    "    thread #{}: load of store #{} from atomic #{}",
+                    thread, store, object
+                )?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SeqCstOrder;
+    use crate::rt::execution;
+    use crate::rt::thread::Id;
+
+    const X: usize = 0;
+    const Y: usize = 1;
+
+    /// The threads storing to `x` and `y`, and two readers.
+    fn threads() -> [Id; 4] {
+        let execution_id = execution::Id::new();
+        [1, 2, 3, 4].map(|id| Id::new(execution_id, id))
+    }
+
+    // Independent reads of independent writes: two threads see the stores of
+    // 1 to `x` and `y`, each initially 0, in opposite orders. Whichever order
+    // the operations ran in, one of the loads reads a stale store.
+    #[test]
+    fn iriw_is_violation() {
+        let [x, y, a, b] = threads();
+        let mut order = SeqCstOrder::default();
+
+        order.store(x, X, 1);
+        assert_eq!(Ok(()), order.load(a, X, 1, true));
+        assert_eq!(Ok(()), order.load(a, Y, 0, true));
+        order.store(y, Y, 1);
+        assert_eq!(Ok(()), order.load(b, Y, 1, true));
+        assert_eq!(Err(1), order.load(b, X, 0, true));
+    }
+
+    #[test]
+    fn iriw_agreeing_readers_are_consistent() {
+        let [x, y, a, b] = threads();
+        let mut order = SeqCstOrder::default();
+
+        order.store(x, X, 1);
+        assert_eq!(Ok(()), order.load(a, X, 1, true));
+        assert_eq!(Ok(()), order.load(b, Y, 0, true));
+        order.store(y, Y, 1);
+        assert_eq!(Ok(()), order.load(a, Y, 1, true));
+        assert_eq!(Ok(()), order.load(b, X, 1, true));
+    }
+
+    #[test]
+    fn unordered_store_may_be_read() {
+        let [x, _, a, _] = threads();
+        let mut order = SeqCstOrder::default();
+
+        order.store(x, X, 2);
+        assert_eq!(Ok(()), order.load(a, X, 1, false));
+        assert_eq!(Err(2), order.load(a, X, 1, true));
+    }
+}
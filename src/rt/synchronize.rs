@@ -1,4 +1,4 @@
-use crate::rt::{thread, VersionVec};
+use crate::rt::{location, thread, Location, StoreId, VersionVec};
 
 use std::sync::atomic::Ordering::{self, *};
 
@@ -21,15 +21,25 @@ impl Synchronize {
     }
 
     pub fn sync_load(&mut self, threads: &mut thread::Set, order: Ordering) {
+        self.sync_load_store(threads, order, None);
+    }
+
+    /// Like `sync_load`, for a load that read the atomic store `store`.
+    pub fn sync_load_store(
+        &mut self,
+        threads: &mut thread::Set,
+        order: Ordering,
+        store: Option<StoreId>,
+    ) {
         match order {
             Relaxed | Release => {
                 // Nothing happens!
             }
             Acquire | AcqRel => {
-                self.sync_acq(threads);
+                self.sync_acq(threads, store);
             }
             SeqCst => {
-                self.sync_acq(threads);
+                self.sync_acq(threads, store);
                 threads.seq_cst();
             }
             order => unimplemented!("unimplemented ordering {:?}", order),
@@ -53,8 +63,28 @@ impl Synchronize {
         }
     }
 
-    fn sync_acq(&mut self, threads: &mut thread::Set) {
-        threads.active_mut().causality.join(&self.happens_before);
+    /// Returns `true` if everything released to this point already
+    /// happens-before the active thread, so acquiring it has no effect.
+    pub fn is_implied(&self, threads: &thread::Set) -> bool {
+        self.happens_before <= threads.active().causality
+    }
+
+    /// Panics unless everything released to this point happens-before the
+    /// active thread, i.e. the thread has exclusive access to the object.
+    pub fn assert_exclusive(&self, threads: &thread::Set, object: &str, location: Location) {
+        if let Some(thread) = threads.active().causality.ahead(&self.happens_before) {
+            location::panic(format!(
+                "Causality violation: Exclusive access to {} that does not happen-after \
+                 thread #{} released it.",
+                object, thread
+            ))
+            .thread("exclusive access", threads.active_id(), location)
+            .fire();
+        }
+    }
+
+    fn sync_acq(&mut self, threads: &mut thread::Set, store: Option<StoreId>) {
+        threads.acquire_store("acquire", store, &self.happens_before);
     }
 
     fn sync_rel(&mut self, threads: &thread::Set) {
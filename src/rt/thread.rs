@@ -1,10 +1,38 @@
 use crate::rt::execution;
+use crate::rt::happens_before::{HappensBeforeGraph, StoreId};
 use crate::rt::object::Operation;
 use crate::rt::vv::VersionVec;
 
-use std::{any::Any, collections::HashMap, fmt, ops};
+use std::{any::Any, collections::BTreeMap, fmt, ops};
 
 use super::Location;
+
+use tracing::trace;
+
+/// Digest of an empty sequence of observed values (the FNV-1a offset basis).
+const OBSERVED_INIT: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// A spurious event loom explores. Only one event of each kind is explored per
+/// thread and execution, which keeps retry loops finite while still letting
+/// them combine, e.g. a weak compare-exchange failing before a wait times out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Spur {
+    /// `thread::park` returned spuriously or timed out.
+    Park,
+
+    /// A weak compare-exchange failed spuriously.
+    CompareExchange,
+
+    /// A condvar wait returned spuriously or timed out.
+    CondvarWait,
+}
+
+impl Spur {
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
 pub(crate) struct Thread {
     pub id: Id,
 
@@ -32,6 +60,42 @@ pub(crate) struct Thread {
     /// Number of times the thread yielded
     pub yield_count: usize,
 
+    /// Consecutive `spin_loop` calls made without progress
+    pub spins: usize,
+
+    /// Digest of the atomic values read since the last `spin_loop`
+    observed: u64,
+
+    /// Digest of the atomic values read between the two most recent
+    /// `spin_loop` calls
+    last_observed: Option<u64>,
+
+    /// True if the thread operated on a synchronization object other than an
+    /// atomic since the last `spin_loop`
+    synced: bool,
+
+    /// Number of atomic stores and operations on other synchronization
+    /// objects the thread performed, i.e. operations that may let a spinning
+    /// thread make progress
+    sync_count: usize,
+
+    /// Sum of the other threads' `sync_count` at the previous `spin_loop`
+    last_others: Option<usize>,
+
+    /// Number of loom operations the thread performed in this execution
+    pub op_count: usize,
+
+    /// Priority set through `thread::Builder`. Used to pick the waiter woken
+    /// by a priority-inheritance mutex.
+    pub priority: u32,
+
+    /// Name set through `thread::Builder`. Only used in diagnostics.
+    pub name: Option<String>,
+
+    /// Kinds of spurious events the thread already went through, one bit
+    /// per `Spur`.
+    spurred: u8,
+
     locals: LocalMap,
 
     /// `tracing` span used to associate diagnostics with the current thread.
@@ -55,6 +119,15 @@ pub(crate) struct Set {
     /// synchronize with this causality.
     pub seq_cst_causality: VersionVec,
 
+    /// The total order of sequentially consistent operations in the current
+    /// execution. Each entry is the thread performing the operation and the
+    /// thread's own version at that point.
+    seq_cst_order: Vec<(Id, u16)>,
+
+    /// Synchronization edges of the current execution, when they are
+    /// recorded.
+    pub(crate) happens_before: Option<HappensBeforeGraph>,
+
     /// `tracing` span used as the parent for new thread spans.
     iteration_span: tracing::Span,
 }
@@ -76,14 +149,16 @@ impl Id {
 #[derive(Debug, Clone, Copy)]
 pub(crate) enum State {
     Runnable { unparked: bool },
-    Blocked(#[allow(dead_code)] Location),
+    Blocked(Location),
     Yield,
     Terminated,
 }
 
-type LocalMap = HashMap<LocalKeyId, LocalValue>;
+// Ordered so that thread locals are dropped in the same order in every
+// execution.
+type LocalMap = BTreeMap<LocalKeyId, LocalValue>;
 
-#[derive(Eq, PartialEq, Hash, Copy, Clone)]
+#[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone)]
 struct LocalKeyId(usize);
 
 struct LocalValue(Option<Box<dyn Any>>);
@@ -101,10 +176,30 @@ impl Thread {
             dpor_vv: VersionVec::new(),
             last_yield: None,
             yield_count: 0,
-            locals: HashMap::new(),
+            spins: 0,
+            observed: OBSERVED_INIT,
+            last_observed: None,
+            synced: false,
+            sync_count: 0,
+            last_others: None,
+            op_count: 0,
+            priority: 0,
+            name: None,
+            spurred: 0,
+            locals: BTreeMap::new(),
         }
     }
 
+    /// Returns `true` if the thread already went through a spurious event of
+    /// this kind in the current execution.
+    pub(crate) fn did_spur(&self, spur: Spur) -> bool {
+        self.spurred & spur.bit() != 0
+    }
+
+    pub(crate) fn set_spurred(&mut self, spur: Spur) {
+        self.spurred |= spur.bit();
+    }
+
     pub(crate) fn is_runnable(&self) -> bool {
         matches!(self.state, State::Runnable { .. })
     }
@@ -121,6 +216,14 @@ impl Thread {
         matches!(self.state, State::Blocked(..))
     }
 
+    /// Location of the operation the thread is blocked on, if any.
+    pub(crate) fn blocked_location(&self) -> Option<Location> {
+        match self.state {
+            State::Blocked(location) => Some(location),
+            _ => None,
+        }
+    }
+
     pub(crate) fn is_yield(&self) -> bool {
         matches!(self.state, State::Yield)
     }
@@ -131,6 +234,56 @@ impl Thread {
         self.yield_count += 1;
     }
 
+    /// Records that the thread read `value` from an atomic.
+    pub(crate) fn observe(&mut self, value: u64) {
+        // FNV-1a, so that reading the same values in a different order
+        // counts as progress.
+        self.observed = (self.observed ^ value).wrapping_mul(0x0100_0000_01b3);
+    }
+
+    /// Records that the thread operated on a mutex, channel, condvar or other
+    /// non-atomic synchronization object.
+    pub(crate) fn observe_sync(&mut self) {
+        self.synced = true;
+        self.sync_count += 1;
+    }
+
+    /// Records that the thread stored to an atomic.
+    pub(crate) fn observe_store(&mut self) {
+        self.sync_count += 1;
+    }
+
+    /// Number of atomic stores and operations on other synchronization
+    /// objects the thread performed.
+    pub(crate) fn sync_count(&self) -> usize {
+        self.sync_count
+    }
+
+    /// Records a `spin_loop` call, returning the number of consecutive calls
+    /// made without progress.
+    ///
+    /// `others` is the number of synchronizing operations the other threads
+    /// performed so far, see [`sync_count`](Thread::sync_count). A call makes
+    /// progress if, since the previous one, another thread ran such an
+    /// operation, or the thread read new atomic values or operated on another
+    /// synchronization object. When `stuck` is set no other thread can run,
+    /// so only the spinning thread could change what it waits for and no
+    /// call counts as progress.
+    pub(crate) fn observe_spin(&mut self, stuck: bool, others: usize) -> usize {
+        let observed = std::mem::replace(&mut self.observed, OBSERVED_INIT);
+        let synced = std::mem::replace(&mut self.synced, false);
+        let others = self.last_others.replace(others) != Some(others);
+
+        if stuck || (!synced && !others && self.last_observed == Some(observed)) {
+            self.spins += 1;
+        } else {
+            self.spins = 0;
+        }
+
+        self.last_observed = Some(observed);
+        self.spins
+    }
+
     pub(crate) fn is_terminated(&self) -> bool {
         matches!(self.state, State::Terminated)
     }
@@ -150,6 +303,13 @@ impl Thread {
         Box::new(locals)
     }
 
+    /// Location of the operation the thread is about to take, if captured.
+    fn location(&self) -> Location {
+        self.operation
+            .map(|operation| operation.location())
+            .unwrap_or_default()
+    }
+
     pub(crate) fn unpark(&mut self, unparker: &Thread) {
         self.causality.join(&unparker.causality);
         self.set_unparked();
@@ -172,6 +332,7 @@ impl fmt::Debug for Thread {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Thread")
             .field("id", &self.id)
+            .field("name", &self.name)
             .field("state", &self.state)
             .field("critical", &self.critical)
             .field("operation", &self.operation)
@@ -180,6 +341,7 @@ impl fmt::Debug for Thread {
             .field("dpor_vv", &self.dpor_vv)
             .field("last_yield", &self.last_yield)
             .field("yield_count", &self.yield_count)
+            .field("spins", &self.spins)
             .field("locals", &format_args!("[..locals..]"))
             .finish()
     }
@@ -202,6 +364,8 @@ impl Set {
             threads,
             active: Some(0),
             seq_cst_causality: VersionVec::new(),
+            seq_cst_order: Vec::new(),
+            happens_before: None,
             iteration_span,
         }
     }
@@ -303,6 +467,8 @@ impl Set {
     }
 
     pub(crate) fn unpark(&mut self, id: Id) {
+        trace!(thread = ?self.active_id(), unparked = ?id, "unpark");
+
         if id == self.active_id() {
             // The thread is unparking itself. We don't have to join its
             // causality with the unparker's causality in this case, since the
@@ -312,10 +478,50 @@ impl Set {
         }
 
         // Synchronize memory
+        let unparker = self.active().causality;
+        self.record_happens_before("unpark", None, &unparker, id);
+
         let (active, th) = self.active2_mut(id);
         th.unpark(active);
     }
 
+    /// Joins `happens_before` into the active thread's causality.
+    pub(crate) fn acquire(&mut self, kind: &'static str, happens_before: &VersionVec) {
+        self.acquire_store(kind, None, happens_before);
+    }
+
+    /// Like `acquire`, for an acquire that read `store`.
+    pub(crate) fn acquire_store(
+        &mut self,
+        kind: &'static str,
+        store: Option<StoreId>,
+        happens_before: &VersionVec,
+    ) {
+        self.record_happens_before(kind, store, happens_before, self.active_id());
+        self.active_mut().causality.join(happens_before);
+    }
+
+    /// Records the edges thread `to` is about to gain by joining
+    /// `happens_before`, if the happens-before graph is recorded.
+    pub(crate) fn record_happens_before(
+        &mut self,
+        kind: &'static str,
+        store: Option<StoreId>,
+        happens_before: &VersionVec,
+        to: Id,
+    ) {
+        if let Some(graph) = &mut self.happens_before {
+            graph.record(
+                kind,
+                store,
+                self.threads[self.active.unwrap()].location(),
+                happens_before,
+                to,
+                &self.threads[to.id].causality,
+            );
+        }
+    }
+
     /// Insert a point of sequential consistency
     /// TODO
     /// - Deprecate SeqCst accesses and allow SeqCst fences only. The semantics of SeqCst accesses
@@ -338,14 +544,39 @@ impl Set {
         // for `fence(SeqCst)`-only scenario; use `seq_cst_fence` for `fence(SeqCst)`).
         // As a quick fix, just disable it. This may fail to model correct code,
         // but will not silently allow bugs.
+        self.track_seq_cst();
     }
 
     pub(crate) fn seq_cst_fence(&mut self) {
-        self.threads[self.active.unwrap()]
-            .causality
-            .join(&self.seq_cst_causality);
+        let seq_cst_causality = self.seq_cst_causality;
+        self.acquire("seq_cst fence", &seq_cst_causality);
         self.seq_cst_causality
             .join(&self.threads[self.active.unwrap()].causality);
+        self.track_seq_cst();
+    }
+
+    /// Append the active thread's current point to the SeqCst total order.
+    fn track_seq_cst(&mut self) {
+        let point = (self.active_id(), self.active_atomic_version());
+
+        // A single operation may establish sequential consistency more than
+        // once (e.g. both halves of a SeqCst rmw).
+        if self.seq_cst_order.last() == Some(&point) {
+            return;
+        }
+
+        // The total order must agree with each thread's program order.
+        debug_assert!(
+            self.seq_cst_order
+                .iter()
+                .rev()
+                .find(|(thread, _)| *thread == point.0)
+                .map_or(true, |&(_, version)| version <= point.1),
+            "SeqCst order violates program order; order = {:?}",
+            self.seq_cst_order
+        );
+
+        self.seq_cst_order.push(point);
     }
 
     pub(crate) fn clear(&mut self, execution_id: execution::Id) {
@@ -357,6 +588,7 @@ impl Set {
         self.execution_id = execution_id;
         self.active = Some(0);
         self.seq_cst_causality = VersionVec::new();
+        self.seq_cst_order.clear();
     }
 
     pub(crate) fn iter(&self) -> impl ExactSizeIterator<Item = (Id, &Thread)> + '_ {
@@ -432,6 +664,10 @@ impl Id {
     pub(crate) fn as_usize(self) -> usize {
         self.id
     }
+
+    pub(crate) fn execution_id(self) -> execution::Id {
+        self.execution_id
+    }
 }
 
 impl From<Id> for usize {
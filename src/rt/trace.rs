@@ -0,0 +1,98 @@
+use crate::rt::object::Operation;
+use crate::rt::{thread, Location};
+
+use std::fmt;
+
+/// Decisions taken by a single execution, in order.
+#[derive(Debug, Default)]
+pub(crate) struct Trace {
+    events: Vec<Event>,
+}
+
+#[derive(Debug)]
+enum Event {
+    /// The thread was picked to run its next operation.
+    Schedule {
+        thread: usize,
+        operation: String,
+        location: Location,
+    },
+
+    /// An atomic load or read-modify-write read the given value, written by
+    /// `writer` at the given operation of its own.
+    Load {
+        thread: usize,
+        value: u64,
+        writer: Option<(usize, u16)>,
+    },
+}
+
+impl Trace {
+    /// Records that `thread` was picked to run `operation`.
+    pub(super) fn schedule(&mut self, thread: thread::Id, operation: Option<Operation>) {
+        let (operation, location) = match operation {
+            Some(operation) => (format!("{:?}", operation.action()), operation.location()),
+            None => ("start".to_string(), Location::disabled()),
+        };
+
+        self.events.push(Event::Schedule {
+            thread: thread.as_usize(),
+            operation,
+            location,
+        });
+    }
+
+    /// Records that `thread` read `value` from an atomic, as written by
+    /// `writer` at the given operation.
+    pub(super) fn load(
+        &mut self,
+        thread: thread::Id,
+        value: u64,
+        writer: Option<(thread::Id, u16)>,
+    ) {
+        self.events.push(Event::Load {
+            thread: thread.as_usize(),
+            value,
+            writer: writer.map(|(writer, op)| (writer.as_usize(), op)),
+        });
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(fmt, "schedule trace:")?;
+
+        for event in &self.events {
+            match event {
+                Event::Schedule {
+                    thread,
+                    operation,
+                    location,
+                } => {
+                    write!(fmt, "    thread #{}: {}", thread, operation)?;
+
+                    if location.is_captured() {
+                        write!(fmt, " @ {}", location)?;
+                    }
+
+                    writeln!(fmt)?;
+                }
+                Event::Load {
+                    thread,
+                    value,
+                    writer,
+                } => {
+                    write!(fmt, "    thread #{}: read {}", thread, value)?;
+
+                    if let Some((writer, op)) = writer {
+                        write!(fmt, ", written by thread #{} at op {}", writer, op)?;
+                    }
+
+                    writeln!(fmt)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -28,14 +28,13 @@ impl<T> Arc<T> {
     /// Returns the inner value, if the `Arc` has exactly one strong reference.
     #[track_caller]
     pub fn try_unwrap(this: Arc<T>) -> Result<T, Arc<T>> {
-        if !this.obj.get_mut(location!()) {
+        if !this.obj.try_unwrap(location!()) {
             return Err(this);
         }
 
         assert_eq!(1, std::sync::Arc::strong_count(&this.value));
         // work around our inability to destruct the object normally,
         // because of the `Drop` presense.
-        this.obj.ref_dec(location!());
         this.unregister();
 
         // Use the same pattern of unwrapping as `std` does.
@@ -123,6 +122,23 @@ impl<T: ?Sized> Arc<T> {
         this.obj.strong_count()
     }
 
+    /// Gets the number of `Weak` pointers to this value.
+    #[track_caller]
+    pub fn weak_count(this: &Self) -> usize {
+        this.obj.weak_count()
+    }
+
+    /// Creates a new `Weak` pointer to this value.
+    #[track_caller]
+    pub fn downgrade(this: &Self) -> Weak<T> {
+        this.obj.weak_inc(location!());
+
+        Weak {
+            obj: std::sync::Arc::clone(&this.obj),
+            value: std::sync::Arc::downgrade(&this.value),
+        }
+    }
+
     /// Increments the strong reference count on the `Arc<T>` associated with the
     /// provided pointer by one.
     ///
@@ -155,7 +171,7 @@ impl<T: ?Sized> Arc<T> {
     }
 
     /// Returns a mutable reference to the inner value, if there are
-    /// no other `Arc` pointers to the same value.
+    /// no other `Arc` or `Weak` pointers to the same value.
     #[track_caller]
     pub fn get_mut(this: &mut Self) -> Option<&mut T> {
         if this.obj.get_mut(location!()) {
@@ -251,6 +267,9 @@ impl<T: ?Sized> Drop for Arc<T> {
                 "something odd is going on"
             );
             self.unregister();
+
+            // The inner value is dropped along with `self.value`, on this
+            // thread, after it acquired the other threads' decrements.
         }
     }
 }
@@ -280,3 +299,55 @@ impl<T: ?Sized> Borrow<T> for Arc<T> {
         self
     }
 }
+
+/// Mock implementation of `std::sync::Weak`.
+#[derive(Debug)]
+pub struct Weak<T: ?Sized> {
+    obj: std::sync::Arc<rt::Arc>,
+    value: std::sync::Weak<T>,
+}
+
+impl<T: ?Sized> Weak<T> {
+    /// Attempts to upgrade the `Weak` pointer to an `Arc`, returning `None`
+    /// if the inner value has been dropped.
+    #[track_caller]
+    pub fn upgrade(&self) -> Option<Arc<T>> {
+        if !self.obj.upgrade(location!()) {
+            return None;
+        }
+
+        let value = self
+            .value
+            .upgrade()
+            .expect("Arc was released without loom noticing");
+
+        Some(Arc {
+            obj: std::sync::Arc::clone(&self.obj),
+            value,
+        })
+    }
+
+    /// Returns `true` if the two `Weak`s point to the same allocation.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.value.ptr_eq(&other.value)
+    }
+}
+
+impl<T: ?Sized> Clone for Weak<T> {
+    #[track_caller]
+    fn clone(&self) -> Weak<T> {
+        self.obj.weak_inc(location!());
+
+        Weak {
+            obj: std::sync::Arc::clone(&self.obj),
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<T: ?Sized> Drop for Weak<T> {
+    #[track_caller]
+    fn drop(&mut self) {
+        self.obj.weak_dec(location!());
+    }
+}
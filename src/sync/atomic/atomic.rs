@@ -28,6 +28,14 @@ where
         self.state.load(location!(), order)
     }
 
+    pub(crate) fn load_candidates(&self, order: Ordering) -> Vec<T> {
+        self.state.load_candidates(order)
+    }
+
+    pub(crate) fn coherence_order(&self) -> Vec<T> {
+        self.state.coherence_order()
+    }
+
     #[track_caller]
     pub(crate) fn store(&self, value: T, order: Ordering) {
         self.state.store(location!(), value, order)
@@ -86,13 +94,33 @@ where
         success: Ordering,
         failure: Ordering,
     ) -> Result<T, T> {
-        self.try_rmw(success, failure, |actual| {
-            if actual == current {
-                Ok(new)
-            } else {
-                Err(actual)
-            }
-        })
+        self.compare_exchange_impl(false, current, new, success, failure)
+    }
+
+    #[track_caller]
+    pub(crate) fn compare_exchange_weak(
+        &self,
+        current: T,
+        new: T,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<T, T> {
+        self.compare_exchange_impl(true, current, new, success, failure)
+    }
+
+    /// Shared by the strong and weak variants. A weak exchange may fail even
+    /// though the value matches `current`.
+    #[track_caller]
+    fn compare_exchange_impl(
+        &self,
+        weak: bool,
+        current: T,
+        new: T,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<T, T> {
+        self.state
+            .compare_exchange(location!(), weak, current, new, success, failure)
     }
 
     #[track_caller]
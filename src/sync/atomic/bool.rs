@@ -16,6 +16,12 @@ impl AtomicBool {
         AtomicBool(Atomic::new(v, location!()))
     }
 
+    /// Get access to a mutable reference to the inner value.
+    #[track_caller]
+    pub fn with_mut<R>(&mut self, f: impl FnOnce(&mut bool) -> R) -> R {
+        self.0.with_mut(f)
+    }
+
     /// Load the value without any synchronization.
     ///
     /// # Safety
@@ -43,6 +49,25 @@ impl AtomicBool {
         self.0.load(order)
     }
 
+    /// Returns every value a `load` with the given ordering could return at
+    /// this point, oldest first, without performing the load.
+    ///
+    /// This has no `std` equivalent. It is meant for asserting what the
+    /// memory model allows in tests.
+    pub fn load_candidates(&self, order: Ordering) -> Vec<bool> {
+        self.0.load_candidates(order)
+    }
+
+    /// Returns the values stored so far, in the modification order loom
+    /// has established for this execution. Only the most recent stores are
+    /// tracked.
+    ///
+    /// This has no `std` equivalent. It is meant for asserting coherence in
+    /// tests.
+    pub fn coherence_order(&self) -> Vec<bool> {
+        self.0.coherence_order()
+    }
+
     /// Stores a value into the atomic bool.
     #[track_caller]
     pub fn store(&self, val: bool, order: Ordering) {
@@ -82,7 +107,7 @@ impl AtomicBool {
         success: Ordering,
         failure: Ordering,
     ) -> Result<bool, bool> {
-        self.compare_exchange(current, new, success, failure)
+        self.0.compare_exchange_weak(current, new, success, failure)
     }
 
     /// Logical "and" with the current value.
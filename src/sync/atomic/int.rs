@@ -54,6 +54,25 @@ macro_rules! atomic_int {
                 self.0.load(order)
             }
 
+            /// Returns every value a `load` with the given ordering could
+            /// return at this point, oldest first, without performing the load.
+            ///
+            /// This has no `std` equivalent. It is meant for asserting what
+            /// the memory model allows in tests.
+            pub fn load_candidates(&self, order: Ordering) -> Vec<$int_type> {
+                self.0.load_candidates(order)
+            }
+
+            /// Returns the values stored so far, in the modification order
+            /// loom has established for this execution. Only the most recent
+            /// stores are tracked.
+            ///
+            /// This has no `std` equivalent. It is meant for asserting
+            /// coherence in tests.
+            pub fn coherence_order(&self) -> Vec<$int_type> {
+                self.0.coherence_order()
+            }
+
             /// Stores a value into the atomic integer.
             #[track_caller]
             pub fn store(&self, val: $int_type, order: Ordering) {
@@ -98,7 +117,7 @@ macro_rules! atomic_int {
                 success: Ordering,
                 failure: Ordering,
             ) -> Result<$int_type, $int_type> {
-                self.compare_exchange(current, new, success, failure)
+                self.0.compare_exchange_weak(current, new, success, failure)
             }
 
             /// Adds to the current value, returning the previous value.
@@ -113,6 +132,26 @@ macro_rules! atomic_int {
                 self.0.rmw(|v| v.wrapping_sub(val), order)
             }
 
+            /// Adds to the current value, saturating at the numeric bounds
+            /// instead of overflowing, and returns the previous value.
+            ///
+            /// This has no `std` equivalent. It is a single read-modify-write,
+            /// so concurrent calls never push the value past the bounds.
+            #[track_caller]
+            pub fn fetch_add_saturating(&self, val: $int_type, order: Ordering) -> $int_type {
+                self.0.rmw(|v| v.saturating_add(val), order)
+            }
+
+            /// Subtracts from the current value, saturating at the numeric
+            /// bounds instead of overflowing, and returns the previous value.
+            ///
+            /// This has no `std` equivalent. It is a single read-modify-write,
+            /// so concurrent calls never push the value past the bounds.
+            #[track_caller]
+            pub fn fetch_sub_saturating(&self, val: $int_type, order: Ordering) -> $int_type {
+                self.0.rmw(|v| v.saturating_sub(val), order)
+            }
+
             /// Bitwise "and" with the current value.
             #[track_caller]
             pub fn fetch_and(&self, val: $int_type, order: Ordering) -> $int_type {
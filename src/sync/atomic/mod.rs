@@ -22,17 +22,28 @@ pub use std::sync::atomic::Ordering;
 
 /// Signals the processor that it is entering a busy-wait spin-loop.
 ///
-/// For loom, this is an alias of [`yield_now`] but is provided as a reflection
-/// of the deprecated [`core::sync::atomic::spin_loop_hint`] function. See the
-/// [`yield_now`] documentation for more information on what effect using this
-/// has on loom.
+/// For loom, this is an alias of [`hint::spin_loop`] but is provided as a
+/// reflection of the deprecated [`core::sync::atomic::spin_loop_hint`]
+/// function. See the [`hint::spin_loop`] documentation for more information
+/// on what effect using this has on loom.
 ///
-/// [`yield_now`]: crate::thread::yield_now
+/// [`hint::spin_loop`]: crate::hint::spin_loop
+#[track_caller]
 pub fn spin_loop_hint() {
-    crate::thread::yield_now();
+    crate::hint::spin_loop();
 }
 
 /// An atomic fence.
 pub fn fence(order: Ordering) {
     crate::rt::fence(order);
 }
+
+/// A compiler memory fence.
+///
+/// Unlike [`fence`], this establishes no synchronization between threads:
+/// other threads may still observe the current thread's relaxed accesses
+/// out of order. Loom never reorders a thread's own operations, so this
+/// has no effect on the model.
+pub fn compiler_fence(order: Ordering) {
+    crate::rt::compiler_fence(order);
+}
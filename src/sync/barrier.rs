@@ -1,19 +1,70 @@
-//! A stub for `std::sync::Barrier`.
+use super::{Condvar, Mutex};
 
+/// Mock implementation of `std::sync::Barrier`.
 #[derive(Debug)]
-/// `std::sync::Barrier` is not supported yet in Loom. This stub is provided just
-/// to make the code to compile.
-pub struct Barrier {}
+pub struct Barrier {
+    lock: Mutex<BarrierState>,
+    cvar: Condvar,
+    num_threads: usize,
+}
+
+#[derive(Debug)]
+struct BarrierState {
+    /// Number of threads waiting in the current generation.
+    count: usize,
+
+    /// Incremented each time all threads arrive and the barrier is released.
+    generation_id: usize,
+}
+
+/// Mock implementation of `std::sync::BarrierWaitResult`.
+#[derive(Debug)]
+pub struct BarrierWaitResult(bool);
 
 impl Barrier {
-    /// `std::sync::Barrier` is not supported yet in Loom. This stub is provided just
-    /// to make the code to compile.
-    pub fn new(_n: usize) -> Self {
-        unimplemented!("std::sync::Barrier is not supported yet in Loom.")
+    /// Creates a new barrier that can block a given number of threads.
+    pub fn new(n: usize) -> Barrier {
+        Barrier {
+            lock: Mutex::new(BarrierState {
+                count: 0,
+                generation_id: 0,
+            }),
+            cvar: Condvar::new(),
+            num_threads: n,
+        }
     }
-    /// `std::sync::Barrier` is not supported yet in Loom. This stub is provided just
-    /// to make the code to compile.
-    pub fn wait(&self) -> std::sync::BarrierWaitResult {
-        unimplemented!("std::sync::Barrier is not supported yet in Loom.")
+
+    /// Blocks the current thread until all threads have rendezvoused here.
+    ///
+    /// A single (arbitrary) thread of each generation receives a
+    /// [`BarrierWaitResult`] that returns `true` from `is_leader`.
+    #[track_caller]
+    pub fn wait(&self) -> BarrierWaitResult {
+        let mut lock = self.lock.lock().unwrap();
+        let local_gen = lock.generation_id;
+
+        lock.count += 1;
+
+        if lock.count < self.num_threads {
+            while local_gen == lock.generation_id {
+                lock = self.cvar.wait(lock).unwrap();
+            }
+
+            BarrierWaitResult(false)
+        } else {
+            lock.count = 0;
+            lock.generation_id = lock.generation_id.wrapping_add(1);
+            self.cvar.notify_all();
+
+            BarrierWaitResult(true)
+        }
+    }
+}
+
+impl BarrierWaitResult {
+    /// Returns `true` if this thread is the "leader thread" for the call to
+    /// [`Barrier::wait`].
+    pub fn is_leader(&self) -> bool {
+        self.0
     }
 }
@@ -36,20 +36,49 @@ impl Condvar {
         // Borrow the mutex guarded data again
         guard.reborrow();
 
+        guard.into_result()
+    }
+
+    /// Blocks the current thread until `condition` returns `false`, re-checking
+    /// it whenever the thread is woken up.
+    #[track_caller]
+    pub fn wait_while<'a, T, F>(
+        &self,
+        mut guard: MutexGuard<'a, T>,
+        mut condition: F,
+    ) -> LockResult<MutexGuard<'a, T>>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        while condition(&mut *guard) {
+            guard = self.wait(guard)?;
+        }
+
         Ok(guard)
     }
 
     /// Waits on this condition variable for a notification, timing out after a
     /// specified duration.
+    ///
+    /// The duration is ignored. Loom explores both the wait being notified and
+    /// it timing out, at most once per thread and execution.
+    #[track_caller]
     pub fn wait_timeout<'a, T>(
         &self,
-        guard: MutexGuard<'a, T>,
+        mut guard: MutexGuard<'a, T>,
         _dur: Duration,
     ) -> LockResult<(MutexGuard<'a, T>, WaitTimeoutResult)> {
-        // TODO: implement timing out
-        self.wait(guard)
-            .map(|guard| (guard, WaitTimeoutResult(false)))
-            .map_err(|err| PoisonError::new((err.into_inner(), WaitTimeoutResult(false))))
+        guard.unborrow();
+
+        let timed_out = self.object.wait_timeout(guard.rt(), location!());
+
+        guard.reborrow();
+
+        let timed_out = WaitTimeoutResult(timed_out);
+        match guard.into_result() {
+            Ok(guard) => Ok((guard, timed_out)),
+            Err(err) => Err(PoisonError::new((err.into_inner(), timed_out))),
+        }
     }
 
     /// Wakes up one blocked thread on this condvar.
@@ -7,14 +7,20 @@ mod condvar;
 pub mod mpsc;
 mod mutex;
 mod notify;
+mod once;
+mod once_cell;
 mod rwlock;
+mod semaphore;
 
-pub use self::arc::Arc;
-pub use self::barrier::Barrier;
+pub use self::arc::{Arc, Weak};
+pub use self::barrier::{Barrier, BarrierWaitResult};
 pub use self::condvar::{Condvar, WaitTimeoutResult};
 pub use self::mutex::{Mutex, MutexGuard};
 pub use self::notify::Notify;
-pub use self::rwlock::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+pub use self::once::Once;
+pub use self::once_cell::OnceCell;
+pub use self::rwlock::{RwLock, RwLockReadGuard, RwLockUpgradableReadGuard, RwLockWriteGuard};
+pub use self::semaphore::Semaphore;
 
 #[doc(no_inline)]
 pub use std::sync::{LockResult, TryLockResult};
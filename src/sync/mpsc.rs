@@ -7,7 +7,7 @@ use crate::rt;
 pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
     let location = location!();
     let (sender_channel, receiver_channel) = std::sync::mpsc::channel();
-    let channel = std::sync::Arc::new(rt::Channel::new(location));
+    let channel = std::sync::Arc::new(rt::Channel::new(None, location));
     let sender = Sender {
         object: std::sync::Arc::clone(&channel),
         sender: sender_channel,
@@ -19,6 +19,33 @@ pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
     (sender, receiver)
 }
 
+/// Mock implementation of `std::sync::mpsc::sync_channel`.
+///
+/// Senders block while `bound` messages are buffered. A `bound` of zero
+/// (rendezvous channel) is not supported.
+#[track_caller]
+pub fn sync_channel<T>(bound: usize) -> (SyncSender<T>, Receiver<T>) {
+    assert!(
+        bound > 0,
+        "rendezvous channels are not supported yet in Loom"
+    );
+
+    let location = location!();
+    let (sender_channel, receiver_channel) = std::sync::mpsc::channel();
+    let channel = std::sync::Arc::new(rt::Channel::new(Some(bound), location));
+    let sender = SyncSender {
+        inner: Sender {
+            object: std::sync::Arc::clone(&channel),
+            sender: sender_channel,
+        },
+    };
+    let receiver = Receiver {
+        object: std::sync::Arc::clone(&channel),
+        receiver: receiver_channel,
+    };
+    (sender, receiver)
+}
+
 #[derive(Debug)]
 /// Mock implementation of `std::sync::mpsc::Sender`.
 pub struct Sender<T> {
@@ -31,13 +58,16 @@ impl<T> Sender<T> {
     /// not be sent.
     #[track_caller]
     pub fn send(&self, msg: T) -> Result<(), std::sync::mpsc::SendError<T>> {
-        self.object.send(location!());
+        if !self.object.send(location!()) {
+            return Err(std::sync::mpsc::SendError(msg));
+        }
         self.sender.send(msg)
     }
 }
 
 impl<T> Clone for Sender<T> {
     fn clone(&self) -> Sender<T> {
+        self.object.add_sender();
         Sender {
             object: std::sync::Arc::clone(&self.object),
             sender: self.sender.clone(),
@@ -45,6 +75,35 @@ impl<T> Clone for Sender<T> {
     }
 }
 
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.object.drop_sender(location!());
+    }
+}
+
+#[derive(Debug)]
+/// Mock implementation of `std::sync::mpsc::SyncSender`.
+pub struct SyncSender<T> {
+    inner: Sender<T>,
+}
+
+impl<T> SyncSender<T> {
+    /// Sends a value on this channel, blocking while the channel is full.
+    /// Returns the value back if the receiver has hung up.
+    #[track_caller]
+    pub fn send(&self, msg: T) -> Result<(), std::sync::mpsc::SendError<T>> {
+        self.inner.send(msg)
+    }
+}
+
+impl<T> Clone for SyncSender<T> {
+    fn clone(&self) -> SyncSender<T> {
+        SyncSender {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
 #[derive(Debug)]
 /// Mock implementation of `std::sync::mpsc::Receiver`.
 pub struct Receiver<T> {
@@ -57,7 +116,9 @@ impl<T> Receiver<T> {
     /// corresponding channel has hung up.
     #[track_caller]
     pub fn recv(&self) -> Result<T, std::sync::mpsc::RecvError> {
-        self.object.recv(location!());
+        if !self.object.recv(location!()) {
+            return Err(std::sync::mpsc::RecvError);
+        }
         self.receiver.recv()
     }
     /// Attempts to wait for a value on this receiver, returning an error if the
@@ -72,7 +133,11 @@ impl<T> Receiver<T> {
     /// Attempts to return a pending value on this receiver without blocking.
     pub fn try_recv(&self) -> Result<T, std::sync::mpsc::TryRecvError> {
         if self.object.is_empty() {
-            return Err(std::sync::mpsc::TryRecvError::Empty);
+            if self.object.is_disconnected() {
+                Err(std::sync::mpsc::TryRecvError::Disconnected)
+            } else {
+                Err(std::sync::mpsc::TryRecvError::Empty)
+            }
         } else {
             self.recv().map_err(|e| e.into())
         }
@@ -85,5 +150,6 @@ impl<T> Drop for Receiver<T> {
         while !self.object.is_empty() {
             self.recv().unwrap();
         }
+        self.object.drop_receiver(location!());
     }
 }
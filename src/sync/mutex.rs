@@ -1,7 +1,7 @@
 use crate::rt;
 
 use std::ops;
-use std::sync::{LockResult, TryLockError, TryLockResult};
+use std::sync::{LockResult, PoisonError, TryLockError, TryLockResult};
 
 /// Mock implementation of `std::sync::Mutex`.
 #[derive(Debug)]
@@ -26,9 +26,39 @@ impl<T> Mutex<T> {
         }
     }
 
+    /// Creates a new priority-inheritance mutex in an unlocked state ready
+    /// for use.
+    ///
+    /// When the mutex is released, only the waiters with the highest
+    /// [priority](crate::thread::Builder::priority) are woken. Loom explores
+    /// every ordering of runnable threads regardless of priority, so boosting
+    /// the holder's priority has no observable effect beyond the wake order.
+    pub fn with_priority_inheritance(data: T) -> Mutex<T> {
+        Mutex {
+            data: std::sync::Mutex::new(data),
+            object: rt::Mutex::new_priority_inheritance(true),
+        }
+    }
+
     /// Consumes this mutex, returning the underlying data.
+    ///
+    /// Every release of the lock must happen-before the call, or the model
+    /// fails with a causality violation.
+    #[track_caller]
     pub fn into_inner(self) -> LockResult<T> {
-        Ok(self.data.into_inner().unwrap())
+        self.object.assert_exclusive(location!());
+
+        let poisoned = self.object.is_poisoned();
+        let data = self
+            .data
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        if poisoned {
+            Err(PoisonError::new(data))
+        } else {
+            Ok(data)
+        }
     }
 }
 
@@ -38,10 +68,7 @@ impl<T: ?Sized> Mutex<T> {
     pub fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
         self.object.acquire_lock(location!());
 
-        Ok(MutexGuard {
-            lock: self,
-            data: Some(self.data.lock().unwrap()),
-        })
+        MutexGuard::new(self).into_result()
     }
 
     /// Attempts to acquire this lock.
@@ -54,21 +81,46 @@ impl<T: ?Sized> Mutex<T> {
     #[track_caller]
     pub fn try_lock(&self) -> TryLockResult<MutexGuard<'_, T>> {
         if self.object.try_acquire_lock(location!()) {
-            Ok(MutexGuard {
-                lock: self,
-                data: Some(self.data.lock().unwrap()),
-            })
+            Ok(MutexGuard::new(self).into_result()?)
         } else {
             Err(TryLockError::WouldBlock)
         }
     }
 
+    /// Determines whether the mutex is poisoned.
+    ///
+    /// A mutex is poisoned when a thread panics while holding it. Loom fails
+    /// the model when a thread panics, so this can only be observed if the
+    /// panic is caught, e.g. with `std::panic::catch_unwind`.
+    pub fn is_poisoned(&self) -> bool {
+        self.object.is_poisoned()
+    }
+
+    /// Clears the poisoned state from a mutex.
+    pub fn clear_poison(&self) {
+        self.object.clear_poison();
+    }
+
     /// Returns a mutable reference to the underlying data.
+    ///
+    /// Every release of the lock must happen-before the call, or the model
+    /// fails with a causality violation.
+    #[track_caller]
     pub fn get_mut(&mut self) -> LockResult<&mut T> {
-        Ok(self.data.get_mut().unwrap())
+        self.object.assert_exclusive(location!());
+
+        let poisoned = self.object.is_poisoned();
+        let data = self.data.get_mut().unwrap_or_else(PoisonError::into_inner);
+
+        if poisoned {
+            Err(PoisonError::new(data))
+        } else {
+            Ok(data)
+        }
     }
 }
 
+#[allow(clippy::needless_maybe_sized)]
 impl<T: ?Sized + Default> Default for Mutex<T> {
     /// Creates a `Mutex<T>`, with the `Default` value for T.
     fn default() -> Self {
@@ -85,12 +137,34 @@ impl<T> From<T> for Mutex<T> {
 }
 
 impl<'a, T: ?Sized + 'a> MutexGuard<'a, T> {
+    /// Borrows the data of a mutex whose lock was just acquired.
+    fn new(lock: &'a Mutex<T>) -> MutexGuard<'a, T> {
+        let mut guard = MutexGuard { lock, data: None };
+        guard.reborrow();
+        guard
+    }
+
+    /// Wraps the guard in an error if the mutex is poisoned.
+    pub(super) fn into_result(self) -> LockResult<MutexGuard<'a, T>> {
+        if self.lock.object.is_poisoned() {
+            Err(PoisonError::new(self))
+        } else {
+            Ok(self)
+        }
+    }
+
     pub(super) fn unborrow(&mut self) {
         self.data = None;
     }
 
     pub(super) fn reborrow(&mut self) {
-        self.data = Some(self.lock.data.lock().unwrap());
+        // Poisoning is tracked by the modeled mutex, ignore `std`'s.
+        self.data = Some(
+            self.lock
+                .data
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
     }
 
     pub(super) fn rt(&self) -> &rt::Mutex {
@@ -114,6 +188,10 @@ impl<'a, T: ?Sized> ops::DerefMut for MutexGuard<'a, T> {
 
 impl<'a, T: ?Sized + 'a> Drop for MutexGuard<'a, T> {
     fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.lock.object.poison();
+        }
+
         self.data = None;
         self.lock.object.release_lock();
     }
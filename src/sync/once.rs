@@ -0,0 +1,45 @@
+use super::Mutex;
+
+/// Mock implementation of `std::sync::Once`.
+#[derive(Debug)]
+pub struct Once {
+    /// Held while the closure runs, so other callers block until it
+    /// completes. Unlocking it makes the initialization happen-before every
+    /// later caller.
+    done: Mutex<bool>,
+}
+
+impl Once {
+    /// Creates a new `Once` value.
+    // `std::sync::Once` does not implement `Default`, so this type does not
+    // either.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Once {
+        Once {
+            done: Mutex::new(false),
+        }
+    }
+
+    /// Performs an initialization routine once and only once.
+    ///
+    /// If another thread is running the routine, this blocks until it
+    /// completes. Everything the routine did happens-before this returns.
+    #[track_caller]
+    pub fn call_once<F>(&self, f: F)
+    where
+        F: FnOnce(),
+    {
+        let mut done = self.done.lock().unwrap();
+
+        if !*done {
+            f();
+            *done = true;
+        }
+    }
+
+    /// Returns `true` if some `call_once` call has completed successfully.
+    #[track_caller]
+    pub fn is_completed(&self) -> bool {
+        *self.done.lock().unwrap()
+    }
+}
@@ -0,0 +1,107 @@
+use super::atomic::AtomicBool;
+use super::Mutex;
+
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::sync::atomic::Ordering::{Acquire, Release};
+use std::sync::PoisonError;
+
+/// A thread-safe cell which can be written to only once, modeled after
+/// `std::sync::OnceLock`.
+pub struct OnceCell<T> {
+    /// Serializes initializers, so only one closure runs.
+    lock: Mutex<()>,
+
+    /// Stored with `Release` once `value` is written. Readers load it with
+    /// `Acquire` before touching `value`.
+    ready: AtomicBool,
+
+    value: UnsafeCell<Option<T>>,
+}
+
+// `value` is only written once, before `ready` is set, and only read after.
+unsafe impl<T: Send> Send for OnceCell<T> {}
+unsafe impl<T: Send + Sync> Sync for OnceCell<T> {}
+
+impl<T> OnceCell<T> {
+    /// Creates a new empty cell.
+    #[track_caller]
+    pub fn new() -> OnceCell<T> {
+        OnceCell {
+            lock: Mutex::new(()),
+            ready: AtomicBool::new(false),
+            value: UnsafeCell::new(None),
+        }
+    }
+
+    /// Gets the reference to the underlying value, or `None` if the cell is
+    /// empty or being initialized.
+    #[track_caller]
+    pub fn get(&self) -> Option<&T> {
+        if self.ready.load(Acquire) {
+            // SAFETY: `value` is never written again once `ready` is set.
+            unsafe { (*self.value.get()).as_ref() }
+        } else {
+            None
+        }
+    }
+
+    /// Sets the contents of the cell to `value`.
+    ///
+    /// Returns `Err(value)` if the cell already had a value.
+    #[track_caller]
+    pub fn set(&self, value: T) -> Result<(), T> {
+        let mut value = Some(value);
+        self.get_or_init(|| value.take().unwrap());
+
+        match value {
+            None => Ok(()),
+            Some(value) => Err(value),
+        }
+    }
+
+    /// Gets the contents of the cell, initializing it with `f` if the cell
+    /// was empty.
+    ///
+    /// If several threads race to initialize the cell, exactly one closure
+    /// runs and the others block until it completes.
+    #[track_caller]
+    pub fn get_or_init<F>(&self, f: F) -> &T
+    where
+        F: FnOnce() -> T,
+    {
+        if let Some(value) = self.get() {
+            return value;
+        }
+
+        // Like `std`, a panicking initializer leaves the cell empty for the
+        // next caller to retry.
+        let _guard = self.lock.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if !self.ready.load(Acquire) {
+            // SAFETY: initializers are serialized by `lock` and readers do
+            // not touch `value` before `ready` is set.
+            unsafe { *self.value.get() = Some(f()) };
+            self.ready.store(true, Release);
+        }
+
+        self.get().unwrap()
+    }
+
+    /// Consumes the cell, returning the wrapped value.
+    pub fn into_inner(self) -> Option<T> {
+        self.value.into_inner()
+    }
+}
+
+impl<T> Default for OnceCell<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for OnceCell<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("OnceCell").finish_non_exhaustive()
+    }
+}
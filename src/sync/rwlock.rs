@@ -26,6 +26,13 @@ pub struct RwLockWriteGuard<'a, T> {
     data: Option<std::sync::RwLockWriteGuard<'a, T>>,
 }
 
+/// RAII guard of an upgradable read lock, see [`RwLock::upgradable_read`].
+#[derive(Debug)]
+pub struct RwLockUpgradableReadGuard<'a, T> {
+    lock: &'a RwLock<T>,
+    data: Option<std::sync::RwLockReadGuard<'a, T>>,
+}
+
 impl<T> RwLock<T> {
     /// Creates a new rwlock in an unlocked state ready for use.
     pub fn new(data: T) -> RwLock<T> {
@@ -53,6 +60,23 @@ impl<T> RwLock<T> {
         })
     }
 
+    /// Locks this rwlock with upgradable read access, blocking the current
+    /// thread until it can be acquired.
+    ///
+    /// This has no `std` equivalent. Only one thread may hold upgradable read
+    /// access at a time. It excludes writers, but not plain readers. The guard
+    /// can later be upgraded to write access with
+    /// [`RwLockUpgradableReadGuard::upgrade`].
+    #[track_caller]
+    pub fn upgradable_read(&self) -> LockResult<RwLockUpgradableReadGuard<'_, T>> {
+        self.object.acquire_upgradable_read_lock(location!());
+
+        Ok(RwLockUpgradableReadGuard {
+            lock: self,
+            data: Some(self.data.try_read().expect("loom::RwLock state corrupt")),
+        })
+    }
+
     /// Attempts to acquire this rwlock with shared read access.
     ///
     /// If the access could not be granted at this time, then Err is returned.
@@ -107,12 +131,24 @@ impl<T> RwLock<T> {
     }
 
     /// Returns a mutable reference to the underlying data.
+    ///
+    /// Every release of the lock must happen-before the call, or the model
+    /// fails with a causality violation.
+    #[track_caller]
     pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        self.object.assert_exclusive(location!());
+
         Ok(self.data.get_mut().expect("loom::RwLock state corrupt"))
     }
 
     /// Consumes this `RwLock`, returning the underlying data.
+    ///
+    /// Every release of the lock must happen-before the call, or the model
+    /// fails with a causality violation.
+    #[track_caller]
     pub fn into_inner(self) -> LockResult<T> {
+        self.object.assert_exclusive(location!());
+
         Ok(self.data.into_inner().expect("loom::RwLock state corrupt"))
     }
 }
@@ -167,3 +203,38 @@ impl<'a, T: 'a> Drop for RwLockWriteGuard<'a, T> {
         self.lock.object.release_write_lock()
     }
 }
+
+impl<'a, T> RwLockUpgradableReadGuard<'a, T> {
+    /// Upgrades to exclusive write access, blocking the current thread until
+    /// all other readers released the lock.
+    #[track_caller]
+    pub fn upgrade(mut this: Self) -> RwLockWriteGuard<'a, T> {
+        let lock = this.lock;
+
+        // The lock is released by the write guard from now on.
+        this.data = None;
+        std::mem::forget(this);
+
+        lock.object.upgrade(location!());
+
+        RwLockWriteGuard {
+            lock,
+            data: Some(lock.data.try_write().expect("loom::RwLock state corrupt")),
+        }
+    }
+}
+
+impl<'a, T> ops::Deref for RwLockUpgradableReadGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.data.as_ref().unwrap().deref()
+    }
+}
+
+impl<'a, T: 'a> Drop for RwLockUpgradableReadGuard<'a, T> {
+    fn drop(&mut self) {
+        self.data = None;
+        self.lock.object.release_read_lock()
+    }
+}
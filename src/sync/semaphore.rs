@@ -0,0 +1,61 @@
+use super::{Condvar, Mutex};
+
+/// A counting semaphore.
+///
+/// This has no `std` equivalent. Waiting threads block on a modeled condition
+/// variable, so loom explores which waiter gets a released permit.
+#[derive(Debug)]
+pub struct Semaphore {
+    permits: Mutex<usize>,
+    cvar: Condvar,
+}
+
+impl Semaphore {
+    /// Creates a new semaphore with the given number of permits.
+    pub fn new(permits: usize) -> Semaphore {
+        Semaphore {
+            permits: Mutex::new(permits),
+            cvar: Condvar::new(),
+        }
+    }
+
+    /// Acquires `n` permits, blocking the current thread until enough are
+    /// available.
+    #[track_caller]
+    pub fn acquire(&self, n: usize) {
+        let mut permits = self.permits.lock().unwrap();
+
+        while *permits < n {
+            permits = self.cvar.wait(permits).unwrap();
+        }
+
+        *permits -= n;
+    }
+
+    /// Acquires `n` permits if they are available right away. Returns `true`
+    /// on success.
+    #[track_caller]
+    pub fn try_acquire(&self, n: usize) -> bool {
+        let mut permits = self.permits.lock().unwrap();
+
+        if *permits < n {
+            return false;
+        }
+
+        *permits -= n;
+        true
+    }
+
+    /// Releases `n` permits, waking the threads waiting for them.
+    #[track_caller]
+    pub fn release(&self, n: usize) {
+        *self.permits.lock().unwrap() += n;
+        self.cvar.notify_all();
+    }
+
+    /// Returns the number of permits currently available.
+    #[track_caller]
+    pub fn available_permits(&self) -> usize {
+        *self.permits.lock().unwrap()
+    }
+}
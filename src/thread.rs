@@ -8,12 +8,21 @@ use crate::rt::{self, Execution, Location};
 pub use std::thread::panicking;
 
 use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use std::{fmt, io};
 
 use tracing::trace;
 
 /// Mock implementation of `std::thread::JoinHandle`.
+///
+/// Dropping the handle detaches the thread. Loom still runs a detached thread
+/// to completion in every execution, and checks it for leaks, before the
+/// execution ends.
+///
+/// A panic in the thread is returned by [`join`](JoinHandle::join). If the
+/// thread is detached instead, the panic fails the model.
 pub struct JoinHandle<T> {
     result: Arc<Mutex<Option<std::thread::Result<T>>>>,
     notify: rt::Notify,
@@ -83,6 +92,8 @@ pub struct LocalKey<T> {
 pub struct Builder {
     name: Option<String>,
     stack_size: Option<usize>,
+    priority: u32,
+    inherit_causality: bool,
 }
 
 static CURRENT_THREAD_KEY: LocalKey<Thread> = LocalKey {
@@ -129,7 +140,7 @@ where
     F: 'static,
     T: 'static,
 {
-    spawn_internal(f, None, None, location!())
+    spawn_internal(f, None, None, 0, true, location!())
 }
 
 /// Mock implementation of `std::thread::park`.
@@ -140,13 +151,26 @@ where
 /// forever, and callers should be prepared for this possibility.
 #[track_caller]
 pub fn park() {
-    rt::park(location!());
+    rt::park_or_spur(location!());
+}
+
+/// Mock implementation of `std::thread::park_timeout`.
+///
+/// Like [`park`], but may also time out. The duration is ignored: loom explores
+/// both the thread being unparked and it timing out, at most once per thread
+/// and execution. An unpark racing with the timeout is saved for the next
+/// `park`, as in `std`.
+#[track_caller]
+pub fn park_timeout(_dur: Duration) {
+    rt::park_timeout(location!());
 }
 
 fn spawn_internal<F, T>(
     f: F,
     name: Option<String>,
     stack_size: Option<usize>,
+    priority: u32,
+    inherit_causality: bool,
     location: Location,
 ) -> JoinHandle<T>
 where
@@ -160,16 +184,35 @@ where
     let id = {
         let name = name.clone();
         let result = result.clone();
-        rt::spawn(stack_size, move || {
+        rt::spawn(stack_size, inherit_causality, move || {
             rt::execution(|execution| {
                 init_current(execution, name);
             });
 
-            *result.lock().unwrap() = Some(Ok(f()));
+            let ret = panic::catch_unwind(AssertUnwindSafe(f));
+
+            // Failures found by loom itself, and panics nobody can join, still
+            // fail the model.
+            if let Err(payload) = ret {
+                if rt::is_failing() || Arc::strong_count(&result) == 1 {
+                    panic::resume_unwind(payload);
+                }
+
+                *result.lock().unwrap() = Some(Err(payload));
+            } else {
+                *result.lock().unwrap() = Some(ret);
+            }
+
             notify.notify(location);
         })
     };
 
+    rt::execution(|execution| {
+        let thread = &mut execution.threads[id];
+        thread.priority = priority;
+        thread.name = name.clone();
+    });
+
     JoinHandle {
         result,
         notify,
@@ -190,6 +233,8 @@ impl Builder {
         Builder {
             name: None,
             stack_size: None,
+            priority: 0,
+            inherit_causality: true,
         }
     }
 
@@ -208,6 +253,33 @@ impl Builder {
         self
     }
 
+    /// Sets the priority of the new thread. Higher values mean higher
+    /// priority; the default is 0.
+    ///
+    /// Loom still explores every ordering of runnable threads, but a mutex
+    /// created with [`Mutex::with_priority_inheritance`] wakes its
+    /// highest-priority waiter first.
+    ///
+    /// [`Mutex::with_priority_inheritance`]: crate::sync::Mutex::with_priority_inheritance
+    pub fn priority(mut self, priority: u32) -> Builder {
+        self.priority = priority;
+
+        self
+    }
+
+    /// Sets whether the spawn synchronizes with the new thread. Defaults to
+    /// `true`, matching `std::thread::spawn`.
+    ///
+    /// When `false`, writes made by the current thread before the spawn do
+    /// not happen-before the new thread. This models spawn primitives that do
+    /// not synchronize, and checks that the code establishes its own
+    /// happens-before with the spawned work.
+    pub fn inherit_causality(mut self, inherit: bool) -> Builder {
+        self.inherit_causality = inherit;
+
+        self
+    }
+
     /// Spawns a new thread by taking ownership of the `Builder`, and returns an
     /// `io::Result` to its `JoinHandle`.
     #[track_caller]
@@ -217,7 +289,14 @@ impl Builder {
         F: Send + 'static,
         T: Send + 'static,
     {
-        Ok(spawn_internal(f, self.name, self.stack_size, location!()))
+        Ok(spawn_internal(
+            f,
+            self.name,
+            self.stack_size,
+            self.priority,
+            self.inherit_causality,
+            location!(),
+        ))
     }
 }
 
@@ -235,16 +314,192 @@ impl<T> JoinHandle<T> {
     }
 }
 
+impl<T> Drop for JoinHandle<T> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            return;
+        }
+
+        // A detached thread that already panicked fails the model.
+        let ret = self.result.lock().unwrap().take();
+
+        if let Some(Err(payload)) = ret {
+            panic::resume_unwind(payload);
+        }
+    }
+}
+
 impl<T: fmt::Debug> fmt::Debug for JoinHandle<T> {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt.debug_struct("JoinHandle").finish()
     }
 }
 
+/// Mock implementation of `std::thread::scope`.
+///
+/// Threads spawned in the scope may borrow non-`'static` data. Every one of
+/// them is joined before `scope` returns, so everything they did
+/// happens-before it returns. This also holds if `f` panics.
+///
+/// If a thread that was not joined manually panicked, `scope` resumes its
+/// panic once all threads are joined.
+#[track_caller]
+pub fn scope<'env, F, T>(f: F) -> T
+where
+    F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> T,
+{
+    let scope = Scope {
+        running: Mutex::new(Vec::new()),
+        location: location!(),
+        scope: PhantomData,
+        env: PhantomData,
+    };
+
+    // If loom itself failed, the execution is over and no other thread runs
+    // again. Otherwise the threads may still borrow from the caller, so they
+    // are joined even if `f` panicked.
+    let ret = match panic::catch_unwind(AssertUnwindSafe(|| f(&scope))) {
+        Err(payload) if rt::is_failing() => panic::resume_unwind(payload),
+        ret => ret,
+    };
+
+    match (ret, scope.join_all()) {
+        (Ok(ret), None) => ret,
+        (Err(payload), _) | (Ok(_), Some(payload)) => panic::resume_unwind(payload),
+    }
+}
+
+/// Mock implementation of `std::thread::Scope`.
+pub struct Scope<'scope, 'env: 'scope> {
+    /// Each scoped thread notifies its `Notify` once it is done, leaving its
+    /// panic, if any, in the slot. Both are checked when the scope ends.
+    running: Mutex<Vec<(rt::Notify, Panic)>>,
+    location: Location,
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+/// The panic of a scoped thread, until it is joined.
+type Panic = Arc<Mutex<Option<Box<dyn std::any::Any + Send>>>>;
+
+/// Mock implementation of `std::thread::ScopedJoinHandle`.
+pub struct ScopedJoinHandle<'scope, T> {
+    handle: JoinHandle<()>,
+    result: Arc<Mutex<Option<T>>>,
+    panic: Panic,
+    scope: PhantomData<&'scope ()>,
+}
+
+impl<'scope, 'env> Scope<'scope, 'env> {
+    /// Spawns a new thread within the scope, returning a
+    /// [`ScopedJoinHandle`] for it.
+    #[track_caller]
+    pub fn spawn<F, T>(&'scope self, f: F) -> ScopedJoinHandle<'scope, T>
+    where
+        F: FnOnce() -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        let location = location!();
+        let result = Arc::new(Mutex::new(None));
+        let panic: Panic = Arc::new(Mutex::new(None));
+        let done = rt::Notify::new(true, false);
+
+        let body: Box<dyn FnOnce() + 'scope> = {
+            let (result, panic) = (result.clone(), panic.clone());
+            Box::new(move || {
+                // The panic is left for `join` or the end of the scope, and
+                // the scope must be told the thread is done either way.
+                match panic::catch_unwind(AssertUnwindSafe(f)) {
+                    Ok(ret) => *result.lock().unwrap() = Some(ret),
+                    Err(payload) => {
+                        // Failures found by loom itself still fail the model.
+                        if rt::is_failing() {
+                            panic::resume_unwind(payload);
+                        }
+
+                        *panic.lock().unwrap() = Some(payload);
+                    }
+                }
+
+                done.notify(location);
+            })
+        };
+
+        // SAFETY: `scope` waits for `done` before returning or unwinding, so
+        // the thread finishes using everything it borrows within `'scope`.
+        let body: Box<dyn FnOnce() + 'static> = unsafe { std::mem::transmute(body) };
+
+        self.running.lock().unwrap().push((done, panic.clone()));
+
+        ScopedJoinHandle {
+            handle: spawn_internal(body, None, None, 0, true, location),
+            result,
+            panic,
+            scope: PhantomData,
+        }
+    }
+}
+
+impl<'scope, 'env> Scope<'scope, 'env> {
+    /// Waits for every thread in the scope, returning the first panic that
+    /// was not collected by `join`.
+    fn join_all(&self) -> Option<Box<dyn std::any::Any + Send>> {
+        let mut first = None;
+
+        // Threads may spawn more threads into the scope, so pop them one by one.
+        loop {
+            let running = self.running.lock().unwrap().pop();
+
+            match running {
+                Some((done, panic)) => {
+                    done.wait(self.location);
+
+                    if let Some(payload) = panic.lock().unwrap().take() {
+                        first = Some(payload);
+                    }
+                }
+                None => return first,
+            }
+        }
+    }
+}
+
+impl<'scope, 'env> fmt::Debug for Scope<'scope, 'env> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Scope").finish_non_exhaustive()
+    }
+}
+
+impl<'scope, T> ScopedJoinHandle<'scope, T> {
+    /// Waits for the associated thread to finish.
+    #[track_caller]
+    pub fn join(self) -> std::thread::Result<T> {
+        self.handle.join()?;
+
+        match self.panic.lock().unwrap().take() {
+            Some(payload) => Err(payload),
+            None => Ok(self.result.lock().unwrap().take().unwrap()),
+        }
+    }
+
+    /// Gets a handle to the underlying [`Thread`]
+    pub fn thread(&self) -> &Thread {
+        self.handle.thread()
+    }
+}
+
+impl<'scope, T> fmt::Debug for ScopedJoinHandle<'scope, T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("ScopedJoinHandle").finish()
+    }
+}
+
 fn _assert_traits() {
     fn assert<T: Send + Sync>() {}
 
     assert::<JoinHandle<()>>();
+    assert::<Scope<'_, '_>>();
+    assert::<ScopedJoinHandle<'_, ()>>();
 }
 
 impl<T: 'static> LocalKey<T> {
@@ -0,0 +1,100 @@
+#![deny(warnings, rust_2018_idioms)]
+
+use loom::sync::atomic::AtomicUsize;
+use loom::thread;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::Ordering::{AcqRel, Acquire, Release};
+use std::sync::{Arc, Mutex};
+
+/// Counts allocations made by the current OS thread. Loom runs every modeled
+/// thread on the thread calling `loom::model`.
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // The counter may already be destroyed while the thread exits.
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+// Version vectors are copied by every store and read-modify-write, e.g. into
+// the store's `Synchronize`. They are fixed-size arrays, so none of this
+// allocates.
+#[test]
+fn atomic_operations_do_not_allocate() {
+    loom::model(|| {
+        let num = Arc::new(AtomicUsize::new(0));
+
+        let th = {
+            let num = num.clone();
+            thread::spawn(move || num.store(1, Release))
+        };
+
+        th.join().unwrap();
+
+        let before = ALLOCATIONS.with(Cell::get);
+
+        for i in 0..50 {
+            num.store(i, Release);
+            num.fetch_add(1, AcqRel);
+        }
+
+        assert_eq!(0, ALLOCATIONS.with(Cell::get) - before);
+    });
+}
+
+// The path is reused across permutations, and its branches are fixed-size,
+// so how many allocations a permutation makes does not depend on the
+// branches it takes.
+#[test]
+fn permutations_allocate_the_same() {
+    let counts = Arc::new(Mutex::new(Vec::new()));
+    let counts_ = counts.clone();
+
+    loom::model(move || {
+        let before = ALLOCATIONS.with(Cell::get);
+        let num = Arc::new(AtomicUsize::new(0));
+
+        let th = {
+            let num = num.clone();
+            thread::spawn(move || {
+                for i in 0..3 {
+                    num.store(i, Release);
+                }
+            })
+        };
+
+        for _ in 0..3 {
+            num.load(Acquire);
+        }
+
+        th.join().unwrap();
+
+        let allocations = ALLOCATIONS.with(Cell::get) - before;
+        counts.lock().unwrap().push(allocations);
+    });
+
+    let counts = counts_.lock().unwrap();
+    assert!(counts.len() > 100);
+
+    // The first permutation also initializes lazily allocated state.
+    assert!(
+        counts[1..].iter().all(|&n| n == counts[1]),
+        "allocations per permutation: {:?}",
+        counts
+    );
+}
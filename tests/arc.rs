@@ -3,8 +3,8 @@
 use loom::cell::UnsafeCell;
 use loom::sync::atomic::AtomicBool;
 use loom::sync::atomic::Ordering::{Acquire, Release};
-use loom::sync::Arc;
 use loom::sync::Notify;
+use loom::sync::{Arc, Weak};
 use loom::thread;
 
 struct State {
@@ -128,3 +128,154 @@ fn try_unwrap_multithreaded() {
         let _ = Arc::try_unwrap(num).unwrap();
     });
 }
+
+#[test]
+fn drop_runs_on_last_decrementing_thread() {
+    use std::collections::HashSet;
+    use std::sync::Mutex;
+
+    struct Resource {
+        // The thread that ran `drop`, shared with the model for checking.
+        dropped_on: std::sync::Arc<Mutex<Vec<thread::ThreadId>>>,
+    }
+
+    impl Drop for Resource {
+        fn drop(&mut self) {
+            self.dropped_on.lock().unwrap().push(thread::current().id());
+        }
+    }
+
+    static LAST: Mutex<Option<HashSet<bool>>> = Mutex::new(None);
+
+    loom::model(|| {
+        let dropped_on = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let res = Arc::new(Resource {
+            dropped_on: dropped_on.clone(),
+        });
+
+        let th = {
+            let res = res.clone();
+            thread::spawn(move || {
+                drop(res);
+                thread::current().id()
+            })
+        };
+
+        drop(res);
+        let spawned = th.join().unwrap();
+
+        let dropped_on = dropped_on.lock().unwrap();
+        assert_eq!(dropped_on.len(), 1);
+
+        LAST.lock()
+            .unwrap()
+            .get_or_insert_with(HashSet::new)
+            .insert(dropped_on[0] == spawned);
+    });
+
+    // Either thread may perform the final decrement.
+    assert_eq!(LAST.lock().unwrap().take().unwrap().len(), 2);
+}
+
+#[test]
+fn destructor_sees_writes_before_every_drop() {
+    use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Pair([UnsafeCell<usize>; 2]);
+
+    // Each cell is only written by one thread, before it drops its clone.
+    unsafe impl Sync for Pair {}
+
+    impl Drop for Pair {
+        fn drop(&mut self) {
+            for cell in &self.0 {
+                cell.with(|ptr| assert_eq!(1, unsafe { *ptr }));
+            }
+            DROPS.fetch_add(1, SeqCst);
+        }
+    }
+
+    loom::model(|| {
+        DROPS.store(0, SeqCst);
+
+        let pair = Arc::new(Pair([UnsafeCell::new(0), UnsafeCell::new(0)]));
+
+        let ths: Vec<_> = (0..2)
+            .map(|i| {
+                let pair = pair.clone();
+                thread::spawn(move || {
+                    pair.0[i].with_mut(|ptr| unsafe { *ptr = 1 });
+                })
+            })
+            .collect();
+
+        drop(pair);
+
+        for th in ths {
+            th.join().unwrap();
+        }
+
+        assert_eq!(1, DROPS.load(SeqCst));
+    });
+}
+
+#[test]
+fn weak_upgrade_races_with_drop() {
+    loom::model(|| {
+        let num = Arc::new(State {
+            data: UnsafeCell::new(1),
+            guard: AtomicBool::new(false),
+        });
+        let weak = Arc::downgrade(&num);
+        assert_eq!(1, Arc::weak_count(&num));
+
+        let th = thread::spawn(move || {
+            // Either the value is still alive or it was fully dropped.
+            if let Some(num) = weak.upgrade() {
+                num.data.with(|ptr| assert_eq!(1, unsafe { *ptr }));
+            }
+        });
+
+        drop(num);
+        th.join().unwrap();
+    });
+}
+
+#[test]
+fn weak_upgrade_fails_after_last_drop() {
+    loom::model(|| {
+        let num = Arc::new(1);
+        let weak: Weak<i32> = Arc::downgrade(&num);
+
+        assert_eq!(Some(1), weak.upgrade().as_deref().copied());
+        drop(num);
+        assert!(weak.upgrade().is_none());
+    });
+}
+
+#[test]
+fn get_mut_fails_with_weak() {
+    loom::model(|| {
+        let mut num = Arc::new(1);
+        let weak = Arc::downgrade(&num);
+        assert!(Arc::get_mut(&mut num).is_none());
+
+        drop(weak);
+        assert_eq!(0, Arc::weak_count(&num));
+        *Arc::get_mut(&mut num).unwrap() += 1;
+        assert_eq!(2, *num);
+    });
+}
+
+#[test]
+fn try_unwrap_with_weak() {
+    loom::model(|| {
+        let num = Arc::new(1);
+        let weak = Arc::downgrade(&num);
+
+        assert_eq!(1, Arc::try_unwrap(num).ok().unwrap());
+        assert!(weak.upgrade().is_none());
+    });
+}
@@ -1,5 +1,8 @@
 #![deny(warnings, rust_2018_idioms)]
 
+#[macro_use]
+mod support;
+
 use loom::sync::atomic::AtomicUsize;
 use loom::thread;
 
@@ -10,8 +13,17 @@ loom::lazy_static! {
     static ref A: AtomicUsize = AtomicUsize::new(0);
     static ref NO_LEAK: loom::sync::Arc<usize> = Default::default();
     static ref ARC_WITH_SLOW_CONSTRUCTOR: loom::sync::Arc<usize> = { thread::yield_now(); Default::default() };
+    static ref COUNTER_WITH_SLOW_CONSTRUCTOR: AtomicUsize = {
+        INITS.fetch_add(1, Relaxed);
+        thread::yield_now();
+        AtomicUsize::new(1)
+    };
 }
 
+/// Number of times `COUNTER_WITH_SLOW_CONSTRUCTOR` was initialized in the
+/// current execution.
+static INITS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
 loom::thread_local! {
     static B: usize = A.load(Relaxed);
 }
@@ -41,6 +53,23 @@ fn lazy_static_arc_race() {
     });
 }
 
+#[test]
+fn lazy_static_initialized_once() {
+    loom::model(|| {
+        INITS.store(0, Relaxed);
+
+        let jh = thread::spawn(|| COUNTER_WITH_SLOW_CONSTRUCTOR.fetch_add(1, Relaxed));
+        let n = COUNTER_WITH_SLOW_CONSTRUCTOR.fetch_add(1, Relaxed);
+
+        // Both threads see the initial value, whichever of them initialized it.
+        let m = jh.join().unwrap();
+        assert_eq!(3, n + m);
+
+        assert_eq!(1, INITS.load(Relaxed));
+        assert_eq!(3, COUNTER_WITH_SLOW_CONSTRUCTOR.load(Relaxed));
+    });
+}
+
 #[test]
 fn lazy_static_arc_doesnt_leak() {
     loom::model(|| {
@@ -121,5 +150,435 @@ fn fetch_add_atomic() {
         let v2 = th.join().unwrap();
 
         assert_ne!(v1, v2);
+
+        // Neither increment is lost, even with `Relaxed` ordering.
+        assert_eq!(2, a1.load(Relaxed));
+    });
+}
+
+#[test]
+fn fetch_or_disjoint_masks() {
+    loom::model(|| {
+        let bits = Arc::new(AtomicUsize::new(0));
+
+        let ths: Vec<_> = [0b001, 0b010]
+            .into_iter()
+            .map(|mask| {
+                let bits = bits.clone();
+                thread::spawn(move || bits.fetch_or(mask, Relaxed))
+            })
+            .collect();
+
+        let prev = bits.fetch_or(0b100, Relaxed);
+        assert_eq!(0, prev & 0b100);
+
+        for th in ths {
+            th.join().unwrap();
+        }
+
+        assert_eq!(0b111, bits.load(Relaxed));
+    });
+}
+
+#[test]
+fn fetch_nand_is_not_and() {
+    loom::model(|| {
+        let num = AtomicUsize::new(0b1100);
+
+        assert_eq!(0b1100, num.fetch_nand(0b1010, Relaxed));
+        assert_eq!(!0b1000, num.load(Relaxed));
+    });
+}
+
+#[test]
+fn load_candidates_after_release_store() {
+    let seen: &'static _ = Box::leak(Box::new(std::sync::Mutex::new(Vec::new())));
+
+    loom::model(move || {
+        let num = Arc::new(AtomicUsize::new(0));
+
+        let writer = {
+            let num = num.clone();
+            thread::spawn(move || num.store(1, Release))
+        };
+
+        let reader = {
+            let num = num.clone();
+            thread::spawn(move || num.load_candidates(Relaxed))
+        };
+
+        seen.lock().unwrap().push(reader.join().unwrap());
+
+        writer.join().unwrap();
+
+        // Joining the writer makes its store visible.
+        assert_eq!(num.load_candidates(Relaxed), [1]);
+    });
+
+    let seen = seen.lock().unwrap();
+
+    // Without an acquire, the reader may observe the value from before the
+    // store even once the store happened.
+    assert!(seen.iter().all(|c| *c == [0] || *c == [0, 1]));
+    assert!(seen.contains(&vec![0, 1]));
+}
+
+#[test]
+fn readers_agree_with_coherence_order() {
+    let mut builder = loom::model::Builder::new();
+    builder.preemption_bound = Some(2);
+
+    builder.check(|| {
+        let num = Arc::new(AtomicUsize::new(0));
+
+        let writer = {
+            let num = num.clone();
+            thread::spawn(move || num.store(1, Relaxed))
+        };
+
+        let readers: Vec<_> = (0..2)
+            .map(|_| {
+                let num = num.clone();
+                thread::spawn(move || (num.load(Relaxed), num.load(Relaxed)))
+            })
+            .collect();
+
+        num.store(2, Relaxed);
+        writer.join().unwrap();
+
+        let reads: Vec<_> = readers.into_iter().map(|r| r.join().unwrap()).collect();
+
+        let order = num.coherence_order();
+        let pos = |v| order.iter().position(|&o| o == v).unwrap();
+
+        assert_eq!(order.len(), 3);
+
+        for (a, b) in reads {
+            // A thread never observes two stores in an order that contradicts
+            // the modification order.
+            assert!(
+                pos(a) <= pos(b),
+                "read {} then {}; order = {:?}",
+                a,
+                b,
+                order
+            );
+        }
+    });
+}
+
+/// `with_mut` on an atomic whose last store is not known to happen-before it.
+/// `std::sync::Arc` is not modeled, so observing the strong count drop does
+/// not synchronize with the other thread.
+fn unsynchronized_with_mut() {
+    let mut num = Arc::new(AtomicUsize::new(0));
+
+    {
+        let num = num.clone();
+        thread::spawn(move || num.store(1, Relaxed));
+    }
+
+    while Arc::strong_count(&num) > 1 {
+        thread::yield_now();
+    }
+
+    Arc::get_mut(&mut num).unwrap().with_mut(|v| *v += 1);
+}
+
+#[test]
+#[should_panic(expected = "Concurrent atomic store and unsync mut accesses")]
+fn with_mut_requires_happens_before() {
+    loom::model(unsynchronized_with_mut);
+}
+
+#[test]
+fn lenient_with_mut_continues() {
+    let mut builder = loom::model::Builder::new();
+    builder.lenient_with_mut = true;
+    builder.check(unsynchronized_with_mut);
+}
+
+/// Returns the outcomes of a compare-exchange that should succeed, across
+/// all executions.
+fn matching_compare_exchange_outcomes(weak: bool) -> Vec<Result<usize, usize>> {
+    use std::sync::Mutex;
+
+    let outcomes = Arc::new(Mutex::new(Vec::new()));
+    let outcomes2 = outcomes.clone();
+
+    loom::model(move || {
+        let num = AtomicUsize::new(1);
+
+        let res = if weak {
+            num.compare_exchange_weak(1, 2, AcqRel, Acquire)
+        } else {
+            num.compare_exchange(1, 2, AcqRel, Acquire)
+        };
+
+        // A failed exchange leaves the value untouched.
+        assert_eq!(num.load(Relaxed), if res.is_ok() { 2 } else { 1 });
+
+        // A mismatching exchange fails, and never branches.
+        assert!(num.compare_exchange_weak(0, 3, AcqRel, Acquire).is_err());
+
+        outcomes2.lock().unwrap().push(res);
+    });
+
+    let outcomes = outcomes.lock().unwrap().clone();
+    outcomes
+}
+
+#[test]
+fn strong_compare_exchange_never_fails_spuriously() {
+    assert_eq!(matching_compare_exchange_outcomes(false), [Ok(1)]);
+}
+
+#[test]
+fn weak_compare_exchange_may_fail_spuriously() {
+    let mut outcomes = matching_compare_exchange_outcomes(true);
+    outcomes.sort();
+
+    assert_eq!(outcomes, [Ok(1), Err(1)]);
+}
+
+#[test]
+fn racing_saturating_increments_stay_at_cap() {
+    loom::model(|| {
+        let num = Arc::new(AtomicUsize::new(usize::MAX - 1));
+
+        let ths: Vec<_> = (0..2)
+            .map(|_| {
+                let num = num.clone();
+                thread::spawn(move || num.fetch_add_saturating(1, AcqRel))
+            })
+            .collect();
+
+        let prevs: Vec<_> = ths.into_iter().map(|th| th.join().unwrap()).collect();
+
+        // Whichever increment ran second found the counter at the cap.
+        assert!(prevs.contains(&(usize::MAX - 1)));
+        assert!(prevs.contains(&usize::MAX));
+        assert_eq!(num.load(Relaxed), usize::MAX);
+    });
+}
+
+#[test]
+fn compare_exchange_loop_increments() {
+    loom::model(|| {
+        let num = Arc::new(AtomicUsize::new(0));
+
+        let increment = |num: &AtomicUsize| {
+            let mut curr = num.load(Relaxed);
+
+            loop {
+                match num.compare_exchange(curr, curr + 1, AcqRel, Relaxed) {
+                    Ok(_) => break,
+                    Err(actual) => curr = actual,
+                }
+            }
+        };
+
+        let th = {
+            let num = num.clone();
+            thread::spawn(move || increment(&num))
+        };
+
+        increment(&num);
+        th.join().unwrap();
+
+        assert_eq!(2, num.load(Relaxed));
+    });
+}
+
+/// Three threads incrementing a counter with a compare-exchange loop.
+fn compare_exchange_loops() {
+    let num = Arc::new(AtomicUsize::new(0));
+
+    let increment = |num: &AtomicUsize| {
+        let mut curr = num.load(Relaxed);
+
+        while let Err(actual) = num.compare_exchange(curr, curr + 1, AcqRel, Acquire) {
+            curr = actual;
+        }
+    };
+
+    let ths: Vec<_> = (0..2)
+        .map(|_| {
+            let num = num.clone();
+            thread::spawn(move || increment(&num))
+        })
+        .collect();
+
+    increment(&num);
+
+    for th in ths {
+        th.join().unwrap();
+    }
+
+    assert_eq!(3, num.load(Relaxed));
+}
+
+#[test]
+fn failed_compare_exchange_does_not_race_with_loads() {
+    let stats = loom::model::Builder::new().check_counting(compare_exchange_loops);
+
+    // Treating every exchange as a read-modify-write explores 1957.
+    assert_eq!(920, stats.iteration);
+}
+
+#[test]
+fn compare_exchange_finds_aba() {
+    let res = loom::model::Builder::new().try_check(|| {
+        let state = Arc::new(AtomicUsize::new(1));
+        let data = Arc::new(AtomicUsize::new(0));
+
+        let th = {
+            let (state, data) = (state.clone(), data.clone());
+            thread::spawn(move || {
+                // A -> B -> A
+                state.store(2, Release);
+                data.store(1, Release);
+                state.store(1, Release);
+            })
+        };
+
+        let seen = state.load(Acquire);
+        let before = data.load(Acquire);
+
+        // The exchange succeeds, so the state looks unchanged.
+        if state.compare_exchange(seen, seen, AcqRel, Acquire).is_ok() {
+            assert_eq!(before, data.load(Acquire), "ABA");
+        }
+
+        th.join().unwrap();
+    });
+
+    let err = expect_err!(res, InvariantViolated);
+    assert!(err.failure().message().contains("ABA"));
+}
+
+#[test]
+fn compare_exchange_weak_spin_loop_terminates() {
+    loom::model(|| {
+        let lock = Arc::new(AtomicUsize::new(0));
+
+        let acquire = |lock: &AtomicUsize| {
+            while lock.compare_exchange_weak(0, 1, Acquire, Relaxed).is_err() {
+                thread::yield_now();
+            }
+
+            lock.store(0, Release);
+        };
+
+        let th = {
+            let lock = lock.clone();
+            thread::spawn(move || acquire(&lock))
+        };
+
+        acquire(&lock);
+        th.join().unwrap();
+    });
+}
+
+#[test]
+fn fetch_update_two_threads() {
+    loom::model(|| {
+        let num = Arc::new(AtomicUsize::new(0));
+
+        // Increments, unless the other thread already moved past 1.
+        let th = {
+            let num = num.clone();
+            thread::spawn(move || {
+                num.fetch_update(AcqRel, Acquire, |v| if v < 2 { Some(v + 1) } else { None })
+            })
+        };
+
+        let mine = num.fetch_update(AcqRel, Acquire, |v| Some(v + 2));
+        let theirs = th.join().unwrap();
+
+        match theirs {
+            // Updated before the other thread.
+            Ok(prev) => assert_eq!(0, prev),
+            // Aborted after observing the other update; nothing was stored.
+            Err(prev) => assert_eq!(2, prev),
+        }
+
+        let expected = if theirs.is_ok() { 3 } else { 2 };
+        assert!(mine.is_ok());
+        assert_eq!(expected, num.load(Relaxed));
     });
 }
+
+#[test]
+fn racing_fetch_max_keeps_larger_input() {
+    loom::model(|| {
+        let num = Arc::new(AtomicUsize::new(0));
+
+        let th = {
+            let num = num.clone();
+            thread::spawn(move || num.fetch_max(7, Relaxed))
+        };
+
+        let prev = num.fetch_max(3, Relaxed);
+        th.join().unwrap();
+
+        assert!(prev == 0 || prev == 7, "{}", prev);
+        assert_eq!(7, num.load(Relaxed));
+        assert_eq!(7, num.swap(1, Relaxed));
+        assert_eq!(1, num.fetch_min(5, Relaxed));
+        assert_eq!(1, num.load(Relaxed));
+    });
+}
+
+/// Publishes a freshly initialized node through an `AtomicPtr` with the given
+/// store ordering.
+fn publish_node(order: std::sync::atomic::Ordering) {
+    use loom::cell::UnsafeCell;
+    use loom::sync::atomic::AtomicPtr;
+
+    struct Node {
+        a: UnsafeCell<usize>,
+        b: UnsafeCell<usize>,
+    }
+
+    loom::model(move || {
+        let head = Arc::new(AtomicPtr::new(std::ptr::null_mut::<Node>()));
+
+        let th = {
+            let head = head.clone();
+            thread::spawn(move || {
+                let node = Box::new(Node {
+                    a: UnsafeCell::new(0),
+                    b: UnsafeCell::new(0),
+                });
+                node.a.with_mut(|a| unsafe { *a = 1 });
+                node.b.with_mut(|b| unsafe { *b = 2 });
+
+                head.store(Box::into_raw(node), order);
+            })
+        };
+
+        let node = head.load(Acquire);
+
+        if !node.is_null() {
+            let node = unsafe { &*node };
+            assert_eq!(1, node.a.with(|a| unsafe { *a }));
+            assert_eq!(2, node.b.with(|b| unsafe { *b }));
+        }
+
+        th.join().unwrap();
+
+        drop(unsafe { Box::from_raw(head.load(Relaxed)) });
+    });
+}
+
+#[test]
+fn atomic_ptr_publishes_initialized_node() {
+    publish_node(Release);
+}
+
+#[test]
+#[should_panic(expected = "Causality violation")]
+fn atomic_ptr_relaxed_publish_races() {
+    publish_node(Relaxed);
+}
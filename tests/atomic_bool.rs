@@ -0,0 +1,86 @@
+#![deny(warnings, rust_2018_idioms)]
+
+use loom::sync::atomic::AtomicBool;
+use loom::thread;
+
+use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed};
+use std::sync::Arc;
+
+#[test]
+fn two_threads_toggle() {
+    loom::model(|| {
+        let flag = Arc::new(AtomicBool::new(false));
+
+        let th = {
+            let flag = flag.clone();
+            thread::spawn(move || flag.fetch_xor(true, AcqRel))
+        };
+
+        let mine = flag.fetch_xor(true, AcqRel);
+        let theirs = th.join().unwrap();
+
+        // Exactly one of the toggles observed the initial value.
+        assert_ne!(mine, theirs);
+        assert!(!flag.load(Acquire));
+    });
+}
+
+#[test]
+fn only_one_thread_wins_compare_exchange() {
+    loom::model(|| {
+        let flag = Arc::new(AtomicBool::new(false));
+
+        let th = {
+            let flag = flag.clone();
+            thread::spawn(move || flag.compare_exchange(false, true, AcqRel, Acquire).is_ok())
+        };
+
+        let mine = flag.compare_exchange(false, true, AcqRel, Acquire).is_ok();
+        let theirs = th.join().unwrap();
+
+        assert_ne!(mine, theirs);
+    });
+}
+
+#[test]
+fn with_mut_after_join() {
+    loom::model(|| {
+        let mut flag = Arc::new(AtomicBool::new(false));
+
+        let th = {
+            let flag = flag.clone();
+            thread::spawn(move || flag.swap(true, Relaxed))
+        };
+
+        assert!(!th.join().unwrap());
+
+        Arc::get_mut(&mut flag).unwrap().with_mut(|flag| {
+            assert!(*flag);
+            *flag = false;
+        });
+
+        assert!(!flag.load(Relaxed));
+    });
+}
+
+#[test]
+#[should_panic(expected = "Concurrent atomic store and unsync mut accesses")]
+fn with_mut_without_happens_before() {
+    loom::model(|| {
+        let mut flag = Arc::new(AtomicBool::new(false));
+
+        {
+            let flag = flag.clone();
+            thread::spawn(move || flag.store(true, Relaxed));
+        }
+
+        // `std::sync::Arc` is not modeled, so this does not synchronize.
+        while Arc::strong_count(&flag) > 1 {
+            thread::yield_now();
+        }
+
+        Arc::get_mut(&mut flag)
+            .unwrap()
+            .with_mut(|flag| *flag = false);
+    });
+}
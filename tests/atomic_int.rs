@@ -80,6 +80,19 @@ macro_rules! test_int {
                 });
             }
 
+            #[test]
+            fn saturating() {
+                loom::model(|| {
+                    let atomic = <$atomic>::new(<$int>::MAX - 1);
+                    assert_eq!(<$int>::MAX - 1, atomic.fetch_add_saturating(5, SeqCst));
+                    assert_eq!(<$int>::MAX, atomic.load(SeqCst));
+
+                    let atomic = <$atomic>::new(<$int>::MIN + 1);
+                    assert_eq!(<$int>::MIN + 1, atomic.fetch_sub_saturating(5, SeqCst));
+                    assert_eq!(<$int>::MIN, atomic.load(SeqCst));
+                });
+            }
+
             #[test]
             fn fetch_update() {
                 loom::model(|| {
@@ -106,8 +119,37 @@ test_int!(atomic_i16, i16, AtomicI16);
 test_int!(atomic_i32, i32, AtomicI32);
 test_int!(atomic_isize, isize, AtomicIsize);
 
-#[cfg(target_pointer_width = "64")]
+#[cfg(target_has_atomic = "64")]
 test_int!(atomic_u64, u64, AtomicU64);
 
-#[cfg(target_pointer_width = "64")]
+#[cfg(target_has_atomic = "64")]
 test_int!(atomic_i64, i64, AtomicI64);
+
+#[test]
+fn u8_fetch_add_wraps() {
+    use loom::sync::atomic::AtomicU8;
+    use std::sync::atomic::Ordering::SeqCst;
+
+    loom::model(|| {
+        let atomic = AtomicU8::new(255);
+        assert_eq!(255, atomic.fetch_add(1, SeqCst));
+        assert_eq!(0, atomic.load(SeqCst));
+    });
+}
+
+#[test]
+fn negative_values_round_trip() {
+    use loom::sync::atomic::{AtomicI16, AtomicI8};
+    use std::sync::atomic::Ordering::SeqCst;
+
+    loom::model(|| {
+        let atomic = AtomicI8::new(i8::MIN);
+        assert_eq!(i8::MIN, atomic.fetch_sub(1, SeqCst));
+        assert_eq!(i8::MAX, atomic.load(SeqCst));
+
+        let atomic = AtomicI16::new(0);
+        atomic.store(-1, SeqCst);
+        assert_eq!(-1, atomic.fetch_max(-2, SeqCst));
+        assert_eq!(-1, atomic.load(SeqCst));
+    });
+}
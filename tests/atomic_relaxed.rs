@@ -1,8 +1,12 @@
 #![deny(warnings, rust_2018_idioms)]
+#![allow(clippy::arc_with_non_send_sync)]
 
-use loom::sync::atomic::AtomicUsize;
+use loom::cell::UnsafeCell;
+use loom::model::Builder;
+use loom::sync::atomic::{fence, AtomicUsize};
 use loom::thread;
 
+use std::collections::HashSet;
 use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
 use std::sync::Arc;
 
@@ -91,3 +95,202 @@ fn check_ordering_invalid_2() {
         }
     });
 }
+
+#[test]
+fn private_loads_do_not_branch() {
+    let mut builder = loom::model::Builder::new();
+    // Every atomic access is a scheduling branch. Loads of an atomic only
+    // accessed by the current thread must not add a second branch to pick
+    // the store they read from.
+    builder.max_branches = 70;
+
+    builder.check(|| {
+        let num = Arc::new(AtomicUsize::new(0));
+
+        for i in 0..30 {
+            assert_eq!(i, num.load(Relaxed));
+            num.store(i + 1, Relaxed);
+        }
+    });
+}
+
+#[test]
+fn published_loads_branch() {
+    let values = Arc::new(std::sync::Mutex::new(HashSet::new()));
+    let values_ = values.clone();
+
+    loom::model(move || {
+        let num = Arc::new(AtomicUsize::new(0));
+
+        // Private until the thread is spawned.
+        assert_eq!(0, num.load(Relaxed));
+        num.store(1, Relaxed);
+
+        let th = {
+            let num = num.clone();
+            thread::spawn(move || num.store(2, Relaxed))
+        };
+
+        values.lock().unwrap().insert(num.load(Relaxed));
+        th.join().unwrap();
+    });
+
+    let values = values_.lock().unwrap();
+    assert!(values.contains(&1));
+    assert!(values.contains(&2));
+}
+
+#[test]
+fn read_read_coherence() {
+    let values = Arc::new(std::sync::Mutex::new(HashSet::new()));
+    let values_ = values.clone();
+
+    loom::model(move || {
+        let num = Arc::new(AtomicUsize::new(0));
+
+        let th = {
+            let num = num.clone();
+            thread::spawn(move || {
+                num.store(1, Relaxed);
+                num.store(2, Relaxed);
+            })
+        };
+
+        let first = num.load(Relaxed);
+        let second = num.load(Relaxed);
+        values.lock().unwrap().insert((first, second));
+
+        th.join().unwrap();
+    });
+
+    let values = values_.lock().unwrap();
+
+    // Once a store has been read, later reads never go back to an older one.
+    for &(first, second) in values.iter() {
+        assert!(first <= second, "read {} after {}", second, first);
+    }
+
+    // The first read may be stale even though the second one is not.
+    assert!(values.contains(&(0, 2)));
+    assert!(values.contains(&(1, 2)));
+}
+
+#[test]
+fn relaxed_stale_reads_explored() {
+    let values = Arc::new(std::sync::Mutex::new(HashSet::new()));
+    let values_ = values.clone();
+
+    loom::model(move || {
+        let num = Arc::new(AtomicUsize::new(0));
+
+        let th = {
+            let num = num.clone();
+            thread::spawn(move || {
+                num.store(1, Relaxed);
+                num.store(2, Relaxed);
+            })
+        };
+
+        th.join().unwrap();
+
+        // Joining orders the stores before the load, so only the last one
+        // may be read.
+        values.lock().unwrap().insert(num.load(Relaxed));
+    });
+
+    assert_eq!(*values_.lock().unwrap(), HashSet::from([2]));
+
+    let values = Arc::new(std::sync::Mutex::new(HashSet::new()));
+    let values_ = values.clone();
+
+    loom::model(move || {
+        let num = Arc::new(AtomicUsize::new(0));
+        let flag = Arc::new(AtomicUsize::new(0));
+
+        let th = {
+            let (num, flag) = (num.clone(), flag.clone());
+            thread::spawn(move || {
+                num.store(1, Relaxed);
+                num.store(2, Relaxed);
+                flag.store(1, Relaxed);
+            })
+        };
+
+        // Seeing the flag does not order the stores before the load.
+        if flag.load(Relaxed) == 1 {
+            values.lock().unwrap().insert(num.load(Relaxed));
+        }
+
+        th.join().unwrap();
+    });
+
+    assert_eq!(*values_.lock().unwrap(), HashSet::from([0, 1, 2]));
+}
+
+// An atomic only accessed with `Relaxed` so far must still synchronize once a
+// stronger ordering, or a fence, is used.
+#[test]
+fn relaxed_atomic_later_synchronizes() {
+    loom::model(|| {
+        let data = Arc::new(UnsafeCell::new(0));
+        let flag = Arc::new(AtomicUsize::new(0));
+
+        // Relaxed traffic before the flag is used to publish the data.
+        flag.store(1, Relaxed);
+        assert_eq!(1, flag.load(Relaxed));
+
+        let th = {
+            let (data, flag) = (data.clone(), flag.clone());
+            thread::spawn(move || {
+                data.with_mut(|v| unsafe { *v = 1 });
+                fence(Release);
+                flag.store(2, Relaxed);
+                flag.fetch_add(1, Release);
+            })
+        };
+
+        if flag.load(Relaxed) == 2 {
+            fence(Acquire);
+            assert_eq!(1, data.with(|v| unsafe { *v }));
+        }
+
+        if flag.load(Acquire) == 3 {
+            assert_eq!(1, data.with(|v| unsafe { *v }));
+        }
+
+        th.join().unwrap();
+    });
+}
+
+// Many relaxed counters and fences, as in a statistics-heavy model. Pins the
+// number of permutations so skipping synchronization for relaxed-only atomics
+// does not change what is explored.
+#[test]
+fn relaxed_heavy_model() {
+    let stats = Builder::new().check_counting(|| {
+        let counters: Arc<Vec<_>> = Arc::new((0..4).map(|_| AtomicUsize::new(0)).collect());
+
+        let th = {
+            let counters = counters.clone();
+            thread::spawn(move || {
+                for counter in counters.iter() {
+                    counter.fetch_add(1, Relaxed);
+                    fence(Acquire);
+                }
+            })
+        };
+
+        for counter in counters.iter().rev() {
+            counter.load(Relaxed);
+            fence(Acquire);
+        }
+
+        th.join().unwrap();
+
+        for counter in counters.iter() {
+            assert_eq!(1, counter.load(Relaxed));
+        }
+    });
+
+    assert_eq!(201, stats.iteration);
+}
@@ -0,0 +1,72 @@
+#![deny(warnings, rust_2018_idioms)]
+
+use loom::model::Builder;
+use loom::sync::{Arc, Barrier};
+use loom::thread;
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::SeqCst;
+
+#[derive(Default)]
+struct Generation {
+    arrived: AtomicUsize,
+    leaders: AtomicUsize,
+}
+
+#[test]
+fn one_leader_per_generation() {
+    let mut builder = Builder::new();
+    builder.preemption_bound = Some(1);
+
+    check_leaders(builder, 4, 3);
+}
+
+#[test]
+fn three_threads_two_rounds() {
+    let mut builder = Builder::new();
+    builder.preemption_bound = Some(2);
+
+    check_leaders(builder, 3, 2);
+}
+
+/// Runs `threads` threads through `generations` rounds of one barrier and
+/// checks each round has exactly one leader.
+fn check_leaders(builder: Builder, threads: usize, generations: usize) {
+    builder.check(move || {
+        let barrier = Arc::new(Barrier::new(threads));
+        // Bookkeeping only, not part of the model.
+        let generations: Arc<Vec<Generation>> =
+            Arc::new((0..generations).map(|_| Generation::default()).collect());
+
+        let run = {
+            let barrier = barrier.clone();
+            let generations = generations.clone();
+            move || {
+                for generation in generations.iter() {
+                    generation.arrived.fetch_add(1, SeqCst);
+
+                    let res = barrier.wait();
+
+                    // No thread leaves a generation before all of it arrived.
+                    assert_eq!(generation.arrived.load(SeqCst), threads);
+
+                    if res.is_leader() {
+                        generation.leaders.fetch_add(1, SeqCst);
+                    }
+                }
+            }
+        };
+
+        let ths: Vec<_> = (1..threads).map(|_| thread::spawn(run.clone())).collect();
+
+        run();
+
+        for th in ths {
+            th.join().unwrap();
+        }
+
+        for generation in generations.iter() {
+            assert_eq!(generation.leaders.load(SeqCst), 1);
+        }
+    });
+}
@@ -0,0 +1,102 @@
+#![deny(warnings, rust_2018_idioms)]
+
+use loom::model::Builder;
+use loom::sync::atomic::{fence, AtomicUsize};
+use loom::thread;
+
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+use std::sync::Arc;
+use std::time::Instant;
+
+// Four threads racing on many atomics, which stresses `Execution::schedule`
+// and DPOR backtracking. Run with
+// `cargo test --release --test bench -- --ignored --nocapture`.
+#[test]
+#[ignore]
+fn many_atomics_four_threads() {
+    let mut builder = Builder::new();
+    builder.preemption_bound = Some(2);
+    builder.max_permutations = Some(20_000);
+
+    let start = Instant::now();
+
+    let stats = builder.check_counting(|| {
+        let atomics: Arc<Vec<_>> = Arc::new((0..16).map(|_| AtomicUsize::new(0)).collect());
+
+        let ths: Vec<_> = (0..3)
+            .map(|i| {
+                let atomics = atomics.clone();
+                thread::spawn(move || {
+                    for (j, atomic) in atomics.iter().enumerate() {
+                        if j % 3 == i {
+                            atomic.store(1, Release);
+                        } else {
+                            atomic.load(Acquire);
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for atomic in atomics.iter().step_by(4) {
+            atomic.load(Acquire);
+        }
+
+        for th in ths {
+            th.join().unwrap();
+        }
+    });
+
+    println!(
+        "{} permutations, {} branches in {:?}",
+        stats.iteration,
+        stats.branches,
+        start.elapsed()
+    );
+}
+
+// Relaxed counters read behind acquire fences, as in a statistics-heavy
+// model. Every fence scans the atomics, which is cheap for counters only
+// ever accessed with `Relaxed`: skipping their synchronization takes a
+// release run from about 2.2s to 1.1s. Run like `many_atomics_four_threads`.
+#[test]
+#[ignore]
+fn relaxed_counters_with_fences() {
+    let mut builder = Builder::new();
+    builder.preemption_bound = Some(2);
+    builder.max_permutations = Some(20_000);
+
+    let start = Instant::now();
+
+    let stats = builder.check_counting(|| {
+        let counters: Arc<Vec<_>> = Arc::new((0..64).map(|_| AtomicUsize::new(0)).collect());
+
+        let ths: Vec<_> = (0..2)
+            .map(|i| {
+                let counters = counters.clone();
+                thread::spawn(move || {
+                    for counter in counters.iter().skip(i).step_by(2) {
+                        counter.fetch_add(1, Relaxed);
+                        fence(Acquire);
+                    }
+                })
+            })
+            .collect();
+
+        for counter in counters.iter().rev().step_by(4) {
+            counter.load(Relaxed);
+            fence(Acquire);
+        }
+
+        for th in ths {
+            th.join().unwrap();
+        }
+    });
+
+    println!(
+        "{} permutations, {} branches in {:?}",
+        stats.iteration,
+        stats.branches,
+        start.elapsed()
+    );
+}
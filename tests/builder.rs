@@ -0,0 +1,500 @@
+#![deny(warnings, rust_2018_idioms)]
+
+#[macro_use]
+mod support;
+
+use loom::model::Builder;
+use loom::sync::atomic::{AtomicBool, AtomicUsize};
+use loom::sync::Arc;
+use loom::thread;
+
+use std::sync::atomic::Ordering::{Relaxed, SeqCst};
+
+/// Fails only if the reader observes the writer's first two stores in turn,
+/// which takes three preemptions.
+fn three_preemptions() {
+    let num = Arc::new(AtomicUsize::new(0));
+
+    let writer = {
+        let num = num.clone();
+        thread::spawn(move || {
+            num.store(1, SeqCst);
+            num.store(2, SeqCst);
+            num.store(3, SeqCst);
+        })
+    };
+
+    let reader = thread::spawn(move || {
+        let first = num.load(SeqCst);
+        let second = num.load(SeqCst);
+        assert!(first != 1 || second != 2, "saw both stores");
+    });
+
+    writer.join().unwrap();
+    reader.join().unwrap();
+}
+
+#[test]
+fn default_finds_three_preemption_bug() {
+    let mut builder = Builder::new();
+    builder.preemption_bound = None;
+
+    let res = builder.try_check(three_preemptions);
+
+    expect_err!(res, InvariantViolated);
+}
+
+#[test]
+fn max_preemptions_bounds_exploration() {
+    let res = Builder::new()
+        .max_preemptions(2)
+        .try_check(three_preemptions);
+
+    assert_eq!(res, Ok(()));
+}
+
+/// Reads stale values unless loads only see the most recent store.
+fn stale_load() {
+    let num = Arc::new(AtomicUsize::new(0));
+    let done = Arc::new(AtomicBool::new(false));
+
+    let th = {
+        let (num, done) = (num.clone(), done.clone());
+        thread::spawn(move || {
+            num.store(1, Relaxed);
+            done.store(true, Relaxed);
+        })
+    };
+
+    if done.load(Relaxed) {
+        assert_eq!(1, num.load(Relaxed), "stale load");
+    }
+
+    th.join().unwrap();
+}
+
+#[test]
+fn default_history_finds_stale_load() {
+    let res = Builder::new().try_check(stale_load);
+
+    expect_err!(res, InvariantViolated);
+}
+
+#[test]
+fn max_history_limits_stale_loads() {
+    let res = Builder::new().max_history(1).try_check(stale_load);
+
+    assert_eq!(res, Ok(()));
+}
+
+#[test]
+#[should_panic(expected = "max_history must be between 1 and 7")]
+fn max_history_out_of_range() {
+    Builder::new().max_history(8);
+}
+
+#[test]
+#[should_panic(expected = "max_history must be between 1 and 7")]
+fn max_history_field_out_of_range() {
+    let mut builder = Builder::new();
+    builder.max_history = 0;
+    builder.check(|| {});
+}
+
+#[test]
+fn max_history_env_out_of_range() {
+    // The variable is read by `Builder::new`, so the check runs in a child
+    // process.
+    if std::env::var("LOOM_MAX_HISTORY").is_ok() {
+        Builder::new();
+        return;
+    }
+
+    let output = std::process::Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "max_history_env_out_of_range"])
+        .args(["--nocapture", "--test-threads=1"])
+        .env("LOOM_MAX_HISTORY", "0")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("max_history must be between 1 and 7"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn on_iteration_called_per_permutation() {
+    use std::sync::Mutex;
+
+    static RUNS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+    static ITERATIONS: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+    Builder::new()
+        .on_iteration(|stats| {
+            assert!(stats.branches >= stats.iteration);
+            ITERATIONS.lock().unwrap().push(stats.iteration);
+        })
+        .check(|| {
+            RUNS.fetch_add(1, SeqCst);
+
+            let num = Arc::new(AtomicUsize::new(0));
+
+            let th = {
+                let num = num.clone();
+                thread::spawn(move || num.store(1, SeqCst))
+            };
+
+            num.store(2, SeqCst);
+            th.join().unwrap();
+        });
+
+    // Loom explores four permutations of this model.
+    let iterations = ITERATIONS.lock().unwrap().clone();
+    assert_eq!(4, RUNS.load(SeqCst));
+    assert_eq!((1..=4).collect::<Vec<_>>(), iterations);
+}
+
+#[test]
+fn check_counting_reports_permutations() {
+    let stats = Builder::new().check_counting(|| {
+        let num = Arc::new(AtomicUsize::new(0));
+
+        let th = {
+            let num = num.clone();
+            thread::spawn(move || num.store(1, SeqCst))
+        };
+
+        num.store(2, SeqCst);
+        th.join().unwrap();
+    });
+
+    // The same model as `on_iteration_called_per_permutation`.
+    assert_eq!(4, stats.iteration);
+    assert!(stats.max_depth > 0);
+    assert!(stats.branches > stats.max_depth);
+    assert!(stats.branches <= stats.max_depth * stats.iteration);
+}
+
+#[test]
+fn check_counting_reports_branch_kinds() {
+    let stats = Builder::new().check_counting(|| {
+        let num = Arc::new(AtomicUsize::new(0));
+
+        // The value was stored by another thread, so each load branches on
+        // the store it reads.
+        let th = {
+            let num = num.clone();
+            thread::spawn(move || {
+                for _ in 0..3 {
+                    num.load(SeqCst);
+                }
+            })
+        };
+
+        th.join().unwrap();
+    });
+
+    assert_eq!(3 * stats.iteration, stats.load_branches);
+    assert!(stats.schedule_branches > 0);
+    assert_eq!(
+        stats.branches,
+        stats.schedule_branches + stats.load_branches
+    );
+}
+
+/// Two threads incrementing a counter with a separate load and store, so an
+/// increment may be lost.
+fn racy_increments() {
+    let num = Arc::new(AtomicUsize::new(0));
+
+    let ths: Vec<_> = (0..2)
+        .map(|_| {
+            let num = num.clone();
+            thread::spawn(move || {
+                let n = num.load(SeqCst);
+                num.store(n + 1, SeqCst);
+            })
+        })
+        .collect();
+
+    let n = num.load(SeqCst);
+    num.store(n + 1, SeqCst);
+
+    for th in ths {
+        th.join().unwrap();
+    }
+
+    assert_eq!(3, num.load(SeqCst), "lost an increment");
+}
+
+#[test]
+fn parallel_run_finds_same_bug() {
+    let serial = Builder::new().try_check(racy_increments);
+    let parallel = Builder::new().threads(2).try_check(racy_increments);
+
+    for err in [
+        expect_err!(serial, InvariantViolated),
+        expect_err!(parallel, InvariantViolated),
+    ] {
+        let message = err.failure().message();
+        assert!(message.contains("lost an increment"), "{}", message);
+    }
+}
+
+// The split is right after the spawn, where the two threads race, so a
+// serial run explores both choices there too.
+#[test]
+fn parallel_run_explores_same_permutations() {
+    let model = || {
+        let num = Arc::new(AtomicUsize::new(0));
+
+        let th = {
+            let num = num.clone();
+            thread::spawn(move || {
+                num.store(1, SeqCst);
+                num.store(2, SeqCst);
+            })
+        };
+
+        num.store(3, SeqCst);
+        num.store(4, SeqCst);
+        th.join().unwrap();
+    };
+
+    let serial = Builder::new().check_counting(model);
+
+    // With more workers than threads to split between, some stay idle.
+    for threads in [2, 3] {
+        let parallel = Builder::new().threads(threads).check_counting(model);
+
+        assert_eq!(
+            serial.iteration, parallel.iteration,
+            "threads = {}",
+            threads
+        );
+        assert_eq!(serial.branches, parallel.branches, "threads = {}", threads);
+        assert_eq!(
+            serial.max_depth, parallel.max_depth,
+            "threads = {}",
+            threads
+        );
+    }
+}
+
+#[test]
+fn parallel_run_reaches_same_outcomes_after_idle_split() {
+    use loom::sync::Mutex;
+    use std::collections::BTreeSet;
+
+    // The spawned threads block on the lock held by the main thread, so the
+    // first split is where it is released. A spurious failure branches
+    // before that: without it, the split is between two threads, fewer than
+    // the workers, and with it between three.
+    let model = |outcomes: &'static std::sync::Mutex<BTreeSet<Vec<usize>>>| {
+        move || {
+            let flag = AtomicUsize::new(0);
+            let spurious = flag.compare_exchange_weak(0, 1, SeqCst, SeqCst).is_err();
+
+            let next = Arc::new(Mutex::new(0));
+            let take = |next: &Mutex<usize>| {
+                let mut next = next.lock().unwrap();
+                *next += 1;
+                *next - 1
+            };
+
+            let guard = next.lock().unwrap();
+
+            let ths: Vec<_> = (0..1 + spurious as usize)
+                .map(|_| {
+                    let next = next.clone();
+                    let th = thread::spawn(move || take(&next));
+                    thread::yield_now();
+                    th
+                })
+                .collect();
+
+            drop(guard);
+
+            let mut outcome = vec![take(&next)];
+            outcome.extend(ths.into_iter().map(|th| th.join().unwrap()));
+            outcomes.lock().unwrap().insert(outcome);
+        }
+    };
+
+    static SERIAL: std::sync::Mutex<BTreeSet<Vec<usize>>> = std::sync::Mutex::new(BTreeSet::new());
+    static PARALLEL: std::sync::Mutex<BTreeSet<Vec<usize>>> =
+        std::sync::Mutex::new(BTreeSet::new());
+
+    Builder::new().check(model(&SERIAL));
+    Builder::new().threads(3).check(model(&PARALLEL));
+
+    let serial = SERIAL.lock().unwrap();
+    assert!(serial.contains(&vec![1, 2, 0]), "{:?}", serial);
+    assert_eq!(*serial, *PARALLEL.lock().unwrap());
+}
+
+#[test]
+fn sleep_sets_find_same_bug() {
+    let mut builder = Builder::new();
+    builder.sleep_sets = true;
+
+    let err = expect_err!(builder.try_check(racy_increments), InvariantViolated);
+    let message = err.failure().message();
+    assert!(message.contains("lost an increment"), "{}", message);
+}
+
+#[test]
+fn sleep_sets_reach_same_outcomes_in_fewer_permutations() {
+    use loom::sync::Mutex;
+    use std::collections::BTreeSet;
+
+    // Two symmetric spawned threads each bump their own counter and then
+    // append their id to a shared log, racing with the main thread.
+    let model = |outcomes: &'static std::sync::Mutex<BTreeSet<Vec<usize>>>| {
+        move || {
+            let counters = Arc::new([AtomicUsize::new(0), AtomicUsize::new(0)]);
+            let log = Arc::new(Mutex::new(vec![]));
+
+            let ths: Vec<_> = (0..2)
+                .map(|i| {
+                    let (counters, log) = (counters.clone(), log.clone());
+                    thread::spawn(move || {
+                        counters[i].fetch_add(1, Relaxed);
+                        log.lock().unwrap().push(i);
+                    })
+                })
+                .collect();
+
+            log.lock().unwrap().push(2);
+
+            for th in ths {
+                th.join().unwrap();
+            }
+
+            outcomes.lock().unwrap().insert(log.lock().unwrap().clone());
+        }
+    };
+
+    static EXHAUSTIVE: std::sync::Mutex<BTreeSet<Vec<usize>>> =
+        std::sync::Mutex::new(BTreeSet::new());
+    static SLEEP_SETS: std::sync::Mutex<BTreeSet<Vec<usize>>> =
+        std::sync::Mutex::new(BTreeSet::new());
+
+    let exhaustive = Builder::new().check_counting(model(&EXHAUSTIVE));
+
+    let mut builder = Builder::new();
+    builder.sleep_sets = true;
+    let sleep_sets = builder.check_counting(model(&SLEEP_SETS));
+
+    assert!(
+        sleep_sets.iteration < exhaustive.iteration,
+        "{} >= {}",
+        sleep_sets.iteration,
+        exhaustive.iteration
+    );
+
+    // Every order of the three appends.
+    assert_eq!(6, EXHAUSTIVE.lock().unwrap().len());
+    assert_eq!(*EXHAUSTIVE.lock().unwrap(), *SLEEP_SETS.lock().unwrap());
+}
+
+/// Two producers sending over a channel to one consumer.
+fn producer_consumer() {
+    let (tx, rx) = loom::sync::mpsc::channel();
+
+    let ths: Vec<_> = (0..2)
+        .map(|i| {
+            let tx = tx.clone();
+            thread::spawn(move || tx.send(i).unwrap())
+        })
+        .collect();
+
+    let mut received = [rx.recv().unwrap(), rx.recv().unwrap()];
+    received.sort();
+    assert_eq!([0, 1], received);
+
+    for th in ths {
+        th.join().unwrap();
+    }
+}
+
+#[test]
+fn sleep_sets_prune_producer_consumer() {
+    let exhaustive = Builder::new().check_counting(producer_consumer);
+
+    let mut builder = Builder::new();
+    builder.sleep_sets = true;
+    let sleep_sets = builder.check_counting(producer_consumer);
+
+    assert_eq!(47, exhaustive.iteration);
+    assert_eq!(28, sleep_sets.iteration);
+}
+
+#[test]
+fn sleep_sets_find_relaxed_publish_bug() {
+    // The producer publishes each slot with a `Relaxed` store, so the
+    // consumer may see a slot as ready before its value.
+    let model = || {
+        let slots = Arc::new([AtomicUsize::new(0), AtomicUsize::new(0)]);
+        let ready = Arc::new([AtomicBool::new(false), AtomicBool::new(false)]);
+
+        let producer = {
+            let (slots, ready) = (slots.clone(), ready.clone());
+            thread::spawn(move || {
+                for i in 0..2 {
+                    slots[i].store(i + 1, Relaxed);
+                    ready[i].store(true, Relaxed);
+                }
+            })
+        };
+
+        for i in 0..2 {
+            if ready[i].load(Relaxed) {
+                assert_eq!(i + 1, slots[i].load(Relaxed), "read an unpublished slot");
+            }
+        }
+
+        producer.join().unwrap();
+    };
+
+    let mut builder = Builder::new();
+    builder.sleep_sets = true;
+
+    let err = expect_err!(builder.try_check(model), InvariantViolated);
+    let message = err.failure().message();
+    assert!(message.contains("read an unpublished slot"), "{}", message);
+}
+
+#[test]
+fn check_counting_reports_thread_operations() {
+    let model = || {
+        let num = std::sync::Arc::new(AtomicUsize::new(0));
+
+        let th = {
+            let num = num.clone();
+            thread::spawn(move || {
+                for i in 0..5 {
+                    num.store(i, SeqCst);
+                }
+            })
+        };
+
+        num.load(SeqCst);
+        th.join().unwrap();
+    };
+
+    // The main thread loads and joins. The spawned thread stores five times,
+    // then notifies the join.
+    let stats = Builder::new().check_counting(model);
+    assert_eq!(vec![2, 6], stats.max_thread_operations);
+
+    let mut builder = Builder::new();
+    builder.worker_threads = 2;
+    let stats = builder.check_counting(model);
+    assert_eq!(vec![2, 6], stats.max_thread_operations);
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "checkpoint")]
+#![deny(warnings, rust_2018_idioms)]
+
+use loom::model::{Builder, LoomError};
+use loom::sync::atomic::AtomicUsize;
+use loom::thread;
+
+use std::sync::atomic::Ordering::SeqCst;
+use std::sync::Arc;
+
+/// Fails only if the spawned thread runs between the two loads.
+fn torn_read() {
+    let num = Arc::new(AtomicUsize::new(0));
+
+    let th = {
+        let num = num.clone();
+        thread::spawn(move || num.store(1, SeqCst))
+    };
+
+    let first = num.load(SeqCst);
+    let second = num.load(SeqCst);
+    assert_eq!(first, second, "torn read");
+
+    th.join().unwrap();
+}
+
+/// Runs the check, returning the failure and the number of permutations that
+/// completed before it.
+fn run(file: &std::path::Path) -> (LoomError, usize) {
+    let passed = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+    let err = {
+        let passed = passed.clone();
+        let mut builder = Builder::new();
+        builder.checkpoint_file = Some(file.to_path_buf());
+        builder
+            .on_iteration(move |_| {
+                passed.fetch_add(1, SeqCst);
+            })
+            .try_check(torn_read)
+            .unwrap_err()
+    };
+
+    (err, passed.load(SeqCst))
+}
+
+#[test]
+fn failing_schedule_is_replayed_first() {
+    let file = std::env::temp_dir().join(format!(
+        "loom-checkpoint-replay-{}.json",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&file);
+
+    let (first, passed) = run(&file);
+    assert!(passed > 0, "the first permutation should pass");
+    assert!(file.exists());
+
+    for _ in 0..2 {
+        let (replayed, passed) = run(&file);
+        assert_eq!(0, passed);
+        assert_eq!(first, replayed);
+    }
+
+    std::fs::remove_file(&file).unwrap();
+}
@@ -1,11 +1,16 @@
 #![deny(warnings, rust_2018_idioms)]
 
+#[macro_use]
+mod support;
+
 use loom::sync::atomic::AtomicUsize;
 use loom::sync::{Condvar, Mutex};
 use loom::thread;
 
+use std::collections::HashSet;
 use std::sync::atomic::Ordering::SeqCst;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[test]
 fn notify_one() {
@@ -40,6 +45,156 @@ fn notify_all() {
     });
 }
 
+#[test]
+#[should_panic(expected = "condvar used with two different mutexes")]
+fn wait_with_two_mutexes() {
+    loom::model(wait_on_either_mutex);
+}
+
+#[test]
+fn wait_with_two_mutexes_is_typed_error() {
+    let res = loom::model::Builder::new().try_check(wait_on_either_mutex);
+    let err = expect_err!(res, CondvarMutexMismatch);
+    assert!(err.failure().message().contains("two different mutexes"));
+}
+
+/// Two threads wait on the same condvar, each with its own mutex.
+fn wait_on_either_mutex() {
+    let state = Arc::new((
+        Condvar::new(),
+        [Mutex::new(()), Mutex::new(())],
+        AtomicUsize::new(0),
+    ));
+
+    let waiters: Vec<_> = (0..2)
+        .map(|i| {
+            let state = state.clone();
+            thread::spawn(move || {
+                let (condvar, mutexes, ready) = &*state;
+                let mut guard = mutexes[i].lock().unwrap();
+
+                while ready.load(SeqCst) == 0 {
+                    guard = condvar.wait(guard).unwrap();
+                }
+            })
+        })
+        .collect();
+
+    state.2.store(1, SeqCst);
+    state.0.notify_all();
+
+    for th in waiters {
+        th.join().unwrap();
+    }
+}
+
+#[test]
+fn wait_timeout_loop_terminates() {
+    let outcomes: &'static _ = Box::leak(Box::new(std::sync::Mutex::new(HashSet::new())));
+
+    loom::model(move || {
+        let state = Arc::new((Mutex::new(false), Condvar::new()));
+
+        let th = {
+            let state = state.clone();
+            thread::spawn(move || {
+                *state.0.lock().unwrap() = true;
+                state.1.notify_one();
+            })
+        };
+
+        let (lock, condvar) = &*state;
+        let mut ready = lock.lock().unwrap();
+        let mut timed_out = false;
+
+        while !*ready {
+            let (guard, res) = condvar.wait_timeout(ready, Duration::from_secs(1)).unwrap();
+            ready = guard;
+            timed_out |= res.timed_out();
+        }
+
+        drop(ready);
+        th.join().unwrap();
+        outcomes.lock().unwrap().insert(timed_out);
+    });
+
+    // Both a notified wait and one that timed out were explored.
+    assert_eq!(*outcomes.lock().unwrap(), HashSet::from([false, true]));
+}
+
+// A spurious weak compare-exchange failure does not use up the wait's timeout.
+#[test]
+fn wait_timeout_after_spurious_compare_exchange() {
+    let outcomes: &'static _ = Box::leak(Box::new(std::sync::Mutex::new(HashSet::new())));
+
+    loom::model(move || {
+        let state = Arc::new((Mutex::new(false), Condvar::new()));
+
+        let th = {
+            let state = state.clone();
+            thread::spawn(move || {
+                *state.0.lock().unwrap() = true;
+                state.1.notify_one();
+            })
+        };
+
+        let num = AtomicUsize::new(0);
+        let spurious = num.compare_exchange_weak(0, 1, SeqCst, SeqCst).is_err();
+
+        let (lock, condvar) = &*state;
+        let mut ready = lock.lock().unwrap();
+        let mut timed_out = false;
+
+        while !*ready {
+            let (guard, res) = condvar.wait_timeout(ready, Duration::from_secs(1)).unwrap();
+            ready = guard;
+            timed_out |= res.timed_out();
+        }
+
+        drop(ready);
+        th.join().unwrap();
+        outcomes.lock().unwrap().insert((spurious, timed_out));
+    });
+
+    assert!(outcomes.lock().unwrap().contains(&(true, true)));
+}
+
+/// A consumer waits with `wait_while` until the producer clears `empty`.
+fn wait_while_flag(builder: loom::model::Builder) {
+    builder.check(|| {
+        let state = Arc::new((Mutex::new(true), Condvar::new()));
+
+        let th = {
+            let state = state.clone();
+            thread::spawn(move || {
+                *state.0.lock().unwrap() = false;
+                state.1.notify_one();
+            })
+        };
+
+        let (lock, condvar) = &*state;
+        let empty = condvar
+            .wait_while(lock.lock().unwrap(), |empty| *empty)
+            .unwrap();
+        assert!(!*empty);
+
+        drop(empty);
+        th.join().unwrap();
+    });
+}
+
+#[test]
+fn wait_while() {
+    wait_while_flag(loom::model::Builder::new());
+}
+
+#[test]
+fn wait_while_spurious_wakeup() {
+    let mut builder = loom::model::Builder::new();
+    builder.spurious_park = true;
+    wait_while_flag(builder);
+}
+
 struct Inc {
     num: AtomicUsize,
     mutex: Mutex<()>,
@@ -80,3 +235,40 @@ impl Inc {
         self.condvar.notify_all();
     }
 }
+
+/// Waits for the flag with `if` instead of a predicate loop.
+fn wait_without_loop() {
+    let pair = Arc::new((Mutex::new(false), Condvar::new()));
+
+    let th = {
+        let pair = pair.clone();
+        thread::spawn(move || {
+            *pair.0.lock().unwrap() = true;
+            pair.1.notify_one();
+        })
+    };
+
+    let mut ready = pair.0.lock().unwrap();
+    if !*ready {
+        ready = pair.1.wait(ready).unwrap();
+    }
+    assert!(*ready, "woke up before the flag was set");
+    drop(ready);
+
+    th.join().unwrap();
+}
+
+#[test]
+fn missing_predicate_loop_passes_without_spurious_wakeups() {
+    loom::model::Builder::new().check(wait_without_loop);
+}
+
+#[test]
+fn missing_predicate_loop_caught_by_spurious_wakeups() {
+    let res = loom::model::Builder::new()
+        .spurious_wakeups(true)
+        .try_check(wait_without_loop);
+
+    let err = expect_err!(res, InvariantViolated);
+    assert_eq!("woke up before the flag was set", err.failure().message());
+}
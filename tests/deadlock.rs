@@ -33,3 +33,92 @@ fn two_mutexes_deadlock() {
         th2.join().unwrap();
     });
 }
+
+#[test]
+fn blocked_and_yielding_threads_are_not_deadlocked() {
+    use loom::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering::{Acquire, Release};
+
+    let mut builder = loom::model::Builder::new();
+    builder.preemption_bound = Some(2);
+
+    let res = builder.try_check(|| {
+        let lock = Rc::new(Mutex::new(()));
+        let flag = Rc::new(AtomicBool::new(false));
+
+        let th1 = {
+            let (lock, flag) = (lock.clone(), flag.clone());
+            thread::spawn(move || {
+                let _guard = lock.lock().unwrap();
+                flag.store(true, Release);
+            })
+        };
+
+        // Spins with `yield_now` while the other threads are blocked, so the
+        // scheduler must pick a yielded thread rather than report a deadlock.
+        let th2 = {
+            let flag = flag.clone();
+            thread::spawn(move || {
+                while !flag.load(Acquire) {
+                    thread::yield_now();
+                }
+            })
+        };
+
+        drop(lock.lock().unwrap());
+
+        th1.join().unwrap();
+        th2.join().unwrap();
+    });
+
+    assert!(res.is_ok(), "{:?}", res);
+}
+
+#[test]
+#[should_panic(expected = "Some(\"waiter\")")]
+fn deadlock_message_names_threads() {
+    loom::model(|| {
+        let lock = std::sync::Arc::new(Mutex::new(()));
+        let guard = lock.lock().unwrap();
+
+        let th = {
+            let lock = lock.clone();
+            thread::Builder::new()
+                .name("waiter".to_string())
+                .spawn(move || drop(lock.lock().unwrap()))
+                .unwrap()
+        };
+
+        th.join().unwrap();
+        drop(guard);
+    });
+}
+
+#[test]
+fn deadlock_message_has_blocked_locations() {
+    let mut builder = loom::model::Builder::new();
+    builder.location = true;
+
+    let res = builder.try_check(|| {
+        let lock = std::sync::Arc::new(Mutex::new(()));
+        let guard = lock.lock().unwrap();
+
+        let th = {
+            let lock = lock.clone();
+            thread::spawn(move || drop(lock.lock().unwrap()))
+        };
+
+        th.join().unwrap();
+        drop(guard);
+    });
+
+    let message = res.unwrap_err().failure().message().to_string();
+
+    // Line numbers of the `lock` and `join` calls above.
+    let line = line!();
+    let lock = format!("thread #1 @ {}:{}:", file!(), line - 10);
+    let join = format!("thread #0 @ {}:{}:", file!(), line - 7);
+
+    assert!(message.contains(&lock), "{}", message);
+    assert!(message.contains(&join), "{}", message);
+}
@@ -0,0 +1,93 @@
+#![deny(warnings, rust_2018_idioms)]
+
+use loom::sync::atomic::AtomicUsize;
+use loom::sync::Arc;
+use loom::thread;
+
+use std::sync::atomic::Ordering::SeqCst;
+
+#[test]
+fn nested_stop_exploring() {
+    loom::model(|| {
+        let num = Arc::new(AtomicUsize::new(0));
+        let num2 = num.clone();
+
+        let th = thread::spawn(move || num2.store(1, SeqCst));
+
+        loom::stop_exploring();
+        loom::stop_exploring();
+        let a = num.load(SeqCst);
+        // Still inside the outer critical section.
+        loom::explore();
+        let b = num.load(SeqCst);
+        loom::explore();
+
+        // Without preemption between the loads, both see the same value.
+        assert_eq!(a, b);
+
+        th.join().unwrap();
+    });
+}
+
+#[test]
+#[should_panic(expected = "not in critical state")]
+fn unbalanced_explore() {
+    loom::model(|| {
+        loom::stop_exploring();
+        loom::explore();
+        loom::explore();
+    });
+}
+
+#[test]
+fn deeply_nested_stop_exploring() {
+    const DEPTH: usize = 100_000;
+
+    loom::model(|| {
+        let num = Arc::new(AtomicUsize::new(0));
+        let num2 = num.clone();
+
+        let th = thread::spawn(move || num2.store(1, SeqCst));
+
+        for _ in 0..DEPTH {
+            loom::stop_exploring();
+        }
+
+        num.load(SeqCst);
+
+        for _ in 0..DEPTH {
+            loom::explore();
+        }
+
+        th.join().unwrap();
+    });
+}
+
+#[test]
+fn stats_report_deepest_critical_section() {
+    let nested = |depth: usize| {
+        move || {
+            let num = Arc::new(AtomicUsize::new(0));
+            let num2 = num.clone();
+
+            let th = thread::spawn(move || num2.store(1, SeqCst));
+
+            for _ in 0..depth {
+                loom::stop_exploring();
+            }
+
+            num.load(SeqCst);
+
+            for _ in 0..depth {
+                loom::explore();
+            }
+
+            th.join().unwrap();
+        }
+    };
+
+    let builder = loom::model::Builder::new();
+
+    assert_eq!(0, builder.check_counting(nested(0)).max_critical_depth);
+    assert_eq!(3, builder.check_counting(nested(3)).max_critical_depth);
+}
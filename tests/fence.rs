@@ -1,10 +1,12 @@
 #![deny(warnings, rust_2018_idioms)]
+#![allow(clippy::arc_with_non_send_sync)]
 
 use loom::cell::UnsafeCell;
-use loom::sync::atomic::{fence, AtomicBool};
+use loom::sync::atomic::{compiler_fence, fence, AtomicBool, AtomicUsize};
 use loom::thread;
 
-use std::sync::atomic::Ordering::{Acquire, Relaxed, Release, SeqCst};
+use std::collections::HashSet;
+use std::sync::atomic::Ordering::{self, AcqRel, Acquire, Relaxed, Release, SeqCst};
 use std::sync::Arc;
 
 #[test]
@@ -74,6 +76,43 @@ fn fence_sw_collapsed_load() {
     });
 }
 
+#[test]
+fn fence_acqrel_relays_release() {
+    loom::model(|| {
+        let data = Arc::new(UnsafeCell::new(0));
+        let flag1 = Arc::new(AtomicBool::new(false));
+        let flag2 = Arc::new(AtomicBool::new(false));
+
+        let writer = {
+            let (data, flag1) = (data.clone(), flag1.clone());
+            thread::spawn(move || {
+                data.with_mut(|ptr| unsafe { *ptr = 42 });
+                fence(Release);
+                flag1.store(true, Relaxed);
+            })
+        };
+
+        let relay = {
+            let (flag1, flag2) = (flag1.clone(), flag2.clone());
+            thread::spawn(move || {
+                if flag1.load(Relaxed) {
+                    // Acquires from the writer and releases to the reader.
+                    fence(AcqRel);
+                    flag2.store(true, Relaxed);
+                }
+            })
+        };
+
+        if flag2.load(Relaxed) {
+            fence(Acquire);
+            assert_eq!(42, data.with(|ptr| unsafe { *ptr }));
+        }
+
+        writer.join().unwrap();
+        relay.join().unwrap();
+    });
+}
+
 // SB+fences from the Promising Semantics paper (https://sf.snu.ac.kr/promise-concurrency/)
 #[test]
 fn sb_fences() {
@@ -215,3 +254,101 @@ fn w_rwc() {
         }
     });
 }
+
+// Message passing through a relaxed flag, with the non-atomic payload published
+// by a release fence before the flag store and an acquire fence after the flag
+// load.
+#[test]
+fn fence_publication() {
+    loom::model(|| {
+        let data = Arc::new(UnsafeCell::new(0));
+        let flag = Arc::new(AtomicBool::new(false));
+
+        let th = {
+            let (data, flag) = (data.clone(), flag.clone());
+            thread::spawn(move || {
+                data.with_mut(|ptr| unsafe { *ptr = 42 });
+                fence(Release);
+                flag.store(true, Relaxed);
+            })
+        };
+
+        if flag.load(Relaxed) {
+            fence(Acquire);
+            assert_eq!(42, data.with(|ptr| unsafe { *ptr }));
+        }
+        th.join().unwrap();
+    });
+}
+
+#[test]
+#[should_panic(expected = "Causality violation")]
+fn fence_publication_missing_release() {
+    loom::model(|| {
+        let data = Arc::new(UnsafeCell::new(0));
+        let flag = Arc::new(AtomicBool::new(false));
+
+        let th = {
+            let (data, flag) = (data.clone(), flag.clone());
+            thread::spawn(move || {
+                data.with_mut(|ptr| unsafe { *ptr = 42 });
+                flag.store(true, Relaxed);
+            })
+        };
+
+        if flag.load(Relaxed) {
+            fence(Acquire);
+            data.with(|ptr| unsafe { *ptr });
+        }
+        th.join().unwrap();
+    });
+}
+
+#[test]
+#[should_panic(expected = "Causality violation")]
+fn fence_publication_missing_acquire() {
+    loom::model(|| {
+        let data = Arc::new(UnsafeCell::new(0));
+        let flag = Arc::new(AtomicBool::new(false));
+
+        let th = {
+            let (data, flag) = (data.clone(), flag.clone());
+            thread::spawn(move || {
+                data.with_mut(|ptr| unsafe { *ptr = 42 });
+                fence(Release);
+                flag.store(true, Relaxed);
+            })
+        };
+
+        if flag.load(Relaxed) {
+            data.with(|ptr| unsafe { *ptr });
+        }
+        th.join().unwrap();
+    });
+}
+
+fn store_buffering_outcomes(fence: fn(Ordering)) -> HashSet<(usize, usize)> {
+    let mut result = None;
+
+    loom::litmus::two_thread(
+        move |(x, y): &(AtomicUsize, AtomicUsize)| {
+            x.store(1, Relaxed);
+            fence(SeqCst);
+            y.load(Relaxed)
+        },
+        move |(x, y)| {
+            y.store(1, Relaxed);
+            fence(SeqCst);
+            x.load(Relaxed)
+        },
+        |outcomes| result = Some(outcomes.clone()),
+    );
+
+    result.unwrap()
+}
+
+#[test]
+fn compiler_fence_does_not_synchronize() {
+    assert!(!store_buffering_outcomes(fence).contains(&(0, 0)));
+    assert!(store_buffering_outcomes(compiler_fence).contains(&(0, 0)));
+}
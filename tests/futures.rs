@@ -90,3 +90,267 @@ fn spurious_poll() {
 
     assert!(actual.load(Acquire));
 }
+
+#[test]
+fn async_mutex_mutual_exclusion() {
+    use loom::cell::UnsafeCell;
+    use loom::future::Mutex;
+    use loom::sync::atomic::AtomicBool;
+
+    struct Shared {
+        lock: Mutex<()>,
+        busy: AtomicBool,
+        /// Only accessed with `lock` held, so any race is reported.
+        count: UnsafeCell<usize>,
+    }
+
+    unsafe impl Sync for Shared {}
+
+    async fn critical_section(shared: &Shared) {
+        let _guard = shared.lock.lock().await;
+
+        assert!(!shared.busy.swap(true, Relaxed));
+        // Gives the other task a chance to contend for the lock.
+        thread::yield_now();
+        shared.count.with_mut(|count| unsafe { *count += 1 });
+        shared.busy.store(false, Relaxed);
+    }
+
+    loom::model(|| {
+        let shared = Arc::new(Shared {
+            lock: Mutex::new(()),
+            busy: AtomicBool::new(false),
+            count: UnsafeCell::new(0),
+        });
+
+        let th = {
+            let shared = shared.clone();
+            thread::spawn(move || block_on(critical_section(&shared)))
+        };
+
+        block_on(critical_section(&shared));
+        th.join().unwrap();
+
+        assert_eq!(2, shared.count.with(|count| unsafe { *count }));
+    });
+}
+
+#[test]
+fn spawned_tasks_explore_wake_orders() {
+    use loom::sync::Mutex;
+    use std::collections::HashSet;
+    use std::task::Waker;
+
+    /// A single-value async channel.
+    #[derive(Default)]
+    struct Chan {
+        state: Mutex<(Option<usize>, Option<Waker>)>,
+    }
+
+    impl Chan {
+        fn send(&self, value: usize) {
+            let waker = {
+                let mut state = self.state.lock().unwrap();
+                state.0 = Some(value);
+                state.1.take()
+            };
+
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+
+        /// Returns the value and whether the receiver had to wait for it.
+        async fn recv(&self) -> (usize, bool) {
+            let mut waited = false;
+
+            poll_fn(|cx| {
+                let mut state = self.state.lock().unwrap();
+
+                match state.0.take() {
+                    Some(value) => Poll::Ready((value, waited)),
+                    None => {
+                        waited = true;
+                        state.1 = Some(cx.waker().clone());
+                        Poll::Pending
+                    }
+                }
+            })
+            .await
+        }
+    }
+
+    let waits = Arc::new(std::sync::Mutex::new(HashSet::new()));
+    let waits_ = waits.clone();
+
+    loom::model(move || {
+        let chan = Arc::new(Chan::default());
+
+        let receiver = {
+            let chan = chan.clone();
+            loom::future::spawn(async move { chan.recv().await })
+        };
+
+        let sender = loom::future::spawn(async move { chan.send(1) });
+
+        sender.join().unwrap();
+        let (value, waited) = receiver.join().unwrap();
+
+        assert_eq!(1, value);
+        waits.lock().unwrap().insert(waited);
+    });
+
+    // The receiver is polled both before and after the value is sent.
+    let waits = waits_.lock().unwrap();
+    assert!(waits.contains(&true));
+    assert!(waits.contains(&false));
+}
+
+/// A waker slot that is woken concurrently with registering.
+trait WakerSlot: Send + Sync + 'static {
+    fn new() -> Self;
+    fn register(&self, waker: std::task::Waker);
+    fn take_waker(&self) -> Option<std::task::Waker>;
+
+    fn wake(&self) {
+        if let Some(waker) = self.take_waker() {
+            waker.wake();
+        }
+    }
+}
+
+impl WakerSlot for AtomicWaker {
+    fn new() -> Self {
+        AtomicWaker::new()
+    }
+
+    fn register(&self, waker: std::task::Waker) {
+        AtomicWaker::register(self, waker)
+    }
+
+    fn take_waker(&self) -> Option<std::task::Waker> {
+        AtomicWaker::take_waker(self)
+    }
+}
+
+/// Registers a task concurrently with waking it. Either the task is woken, or
+/// the wake ran first and the task is still registered.
+fn register_wake_race<W: WakerSlot>() {
+    use std::task::Wake;
+
+    #[derive(Default)]
+    struct Woken(std::sync::atomic::AtomicBool);
+
+    impl Wake for Woken {
+        fn wake(self: Arc<Self>) {
+            self.0.store(true, Relaxed);
+        }
+    }
+
+    loom::model(|| {
+        let slot = Arc::new(W::new());
+        let woken = Arc::new(Woken::default());
+
+        let waker = {
+            let slot = slot.clone();
+            thread::spawn(move || slot.wake())
+        };
+
+        slot.register(woken.clone().into());
+        waker.join().unwrap();
+
+        let registered = slot.take_waker().is_some();
+        assert!(woken.0.load(Relaxed) || registered, "lost wake-up");
+    });
+}
+
+#[test]
+fn atomic_waker_register_wake_race() {
+    register_wake_race::<AtomicWaker>();
+}
+
+#[test]
+#[should_panic(expected = "Causality violation")]
+fn plain_waker_slot_register_wake_race() {
+    use loom::cell::UnsafeCell;
+
+    /// Stores the waker without any synchronization.
+    struct PlainWaker(UnsafeCell<Option<std::task::Waker>>);
+
+    unsafe impl Sync for PlainWaker {}
+
+    impl WakerSlot for PlainWaker {
+        fn new() -> Self {
+            PlainWaker(UnsafeCell::new(None))
+        }
+
+        fn register(&self, waker: std::task::Waker) {
+            self.0.with_mut(|slot| unsafe { *slot = Some(waker) });
+        }
+
+        fn take_waker(&self) -> Option<std::task::Waker> {
+            self.0.with_mut(|slot| unsafe { (*slot).take() })
+        }
+    }
+
+    register_wake_race::<PlainWaker>();
+}
+
+#[test]
+fn waker_will_wake() {
+    use std::task::Waker;
+
+    /// Returns the waker of the task polling it.
+    fn current_waker() -> impl std::future::Future<Output = Waker> {
+        poll_fn(|cx| Poll::Ready(cx.waker().clone()))
+    }
+
+    loom::model(|| {
+        let first = loom::future::spawn(current_waker()).join().unwrap();
+        let second = loom::future::spawn(current_waker()).join().unwrap();
+
+        // A waker cloned within the task that owns it.
+        block_on(async {
+            let waker = current_waker().await;
+            assert!(waker.will_wake(&waker.clone()));
+            assert!(!waker.will_wake(&first));
+        });
+
+        assert!(first.will_wake(&first.clone()));
+        assert!(!first.will_wake(&second));
+    });
+}
+
+#[test]
+fn tasks_alternate_with_yield_now() {
+    use loom::future::{yield_now, Mutex};
+
+    const TASKS: usize = 2;
+
+    loom::model(|| {
+        let counter = Arc::new(Mutex::new(0));
+
+        let tasks: Vec<_> = (0..TASKS)
+            .map(|_| {
+                let counter = counter.clone();
+
+                loom::future::spawn(async move {
+                    // Other tasks run while the lock is held.
+                    let mut count = counter.lock().await;
+                    let n = *count;
+                    yield_now().await;
+                    *count = n + 1;
+                    drop(count);
+
+                    yield_now().await;
+                })
+            })
+            .collect();
+
+        for task in tasks {
+            task.join().unwrap();
+        }
+
+        assert_eq!(TASKS, *counter.try_lock().unwrap());
+    });
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "checkpoint")]
+#![deny(warnings, rust_2018_idioms)]
+
+use loom::sync::atomic::AtomicUsize;
+use loom::thread;
+
+use serde_json::Value;
+use std::sync::atomic::Ordering::{Acquire, Release};
+use std::sync::Arc;
+
+#[test]
+fn export_message_passing_graph() {
+    let path = std::env::temp_dir().join(format!(
+        "loom-happens-before-graph-{}.json",
+        std::process::id()
+    ));
+
+    let mut builder = loom::model::Builder::new();
+    builder.happens_before_graph = Some(path.clone());
+
+    builder.check(|| {
+        let flag = Arc::new(AtomicUsize::new(0));
+        let flag2 = flag.clone();
+
+        // Gives the spawn something to order.
+        flag.store(0, Release);
+
+        let th = thread::spawn(move || flag2.store(1, Release));
+
+        // The last explored permutation reads the store.
+        while flag.load(Acquire) == 0 {
+            thread::yield_now();
+        }
+
+        th.join().unwrap();
+    });
+
+    let graph = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let graph: Value = serde_json::from_str(&graph).unwrap();
+    let edges = graph["edges"].as_array().unwrap();
+    let has_edge = |kind: &str, from: u64, from_version: u64, to: u64| {
+        edges.iter().any(|edge| {
+            edge["kind"] == kind
+                && edge["from"] == from
+                && edge["from_version"] == from_version
+                && edge["to"] == to
+        })
+    };
+
+    assert!(has_edge("spawn", 0, 1, 1), "{:#}", graph);
+
+    // The store is the spawned thread's first operation after it started.
+    assert!(has_edge("acquire", 1, 2, 0), "{:#}", graph);
+
+    // The acquire names the store it read: the flag is the first object, and
+    // its initial value and the first store come before the spawned thread's.
+    let acquire = edges.iter().find(|edge| edge["kind"] == "acquire").unwrap();
+    assert_eq!(acquire["store"]["object"], 0, "{:#}", graph);
+    assert_eq!(acquire["store"]["store_index"], 2, "{:#}", graph);
+
+    let spawn = edges.iter().find(|edge| edge["kind"] == "spawn").unwrap();
+    assert!(spawn["store"].is_null(), "{:#}", graph);
+}
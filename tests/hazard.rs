@@ -0,0 +1,98 @@
+#![deny(warnings, rust_2018_idioms)]
+
+#[macro_use]
+mod support;
+
+use loom::hazard::{self, Domain};
+use loom::model::{Builder, LoomError};
+use loom::sync::atomic::AtomicPtr;
+use loom::sync::Arc;
+use loom::thread;
+
+use std::sync::atomic::Ordering::{Relaxed, SeqCst};
+
+struct Node {
+    value: usize,
+}
+
+/// A reader protects the current node while the main thread replaces it and
+/// reclaims the old one with `reclaim`.
+fn protected_read_races_reclaim(reclaim: fn(&Domain<Node>)) -> Result<(), LoomError> {
+    Builder::new().try_check(move || {
+        let domain = Arc::new(Domain::new());
+        let src = Arc::new(AtomicPtr::new(hazard::alloc(Node { value: 1 })));
+
+        let reader = {
+            let (domain, src) = (domain.clone(), src.clone());
+            thread::spawn(move || {
+                let node = domain.protect(&src);
+                let value = unsafe { (*node).value };
+                domain.clear();
+                value
+            })
+        };
+
+        let old = src.swap(hazard::alloc(Node { value: 2 }), SeqCst);
+        unsafe { domain.retire(old) };
+        reclaim(&domain);
+
+        let value = reader.join().unwrap();
+        assert!(value == 1 || value == 2);
+
+        unsafe { hazard::free(src.load(Relaxed)) };
+    })
+}
+
+#[test]
+fn protected_node_is_not_freed() {
+    protected_read_races_reclaim(|domain| domain.reclaim()).unwrap();
+}
+
+#[test]
+fn incomplete_scan_is_caught() {
+    let res = protected_read_races_reclaim(|domain| {
+        // Stops at the first empty slot, missing hazards of later threads.
+        domain.reclaim_with(|hazards| {
+            hazards
+                .iter()
+                .map(|hazard| hazard.load(Relaxed))
+                .take_while(|ptr| !ptr.is_null())
+                .collect()
+        })
+    });
+
+    expect_err!(res, UseAfterFree);
+}
+
+#[test]
+fn freeing_protected_node_is_caught() {
+    let res = Builder::new().try_check(|| {
+        let domain = Domain::new();
+        let src = AtomicPtr::new(hazard::alloc(Node { value: 1 }));
+
+        let node = domain.protect(&src);
+        unsafe { domain.retire(node) };
+
+        // Ignores the hazard slots altogether.
+        domain.reclaim_with(|_| Vec::new());
+    });
+
+    let err = expect_err!(res, UseAfterFree);
+    assert!(err
+        .failure()
+        .message()
+        .contains("freed while protected by thread #0"));
+}
+
+#[test]
+fn retired_node_is_leak_checked() {
+    let res = Builder::new().try_check(|| {
+        let domain = Domain::new();
+        unsafe { domain.retire(hazard::alloc(Node { value: 1 })) };
+
+        // Never reclaims the retired node.
+        std::mem::forget(domain);
+    });
+
+    expect_err!(res, Leak);
+}
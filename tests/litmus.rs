@@ -1,10 +1,11 @@
 #![deny(warnings, rust_2018_idioms)]
 
-use loom::sync::atomic::AtomicUsize;
+use loom::model::Builder;
+use loom::sync::atomic::{fence, AtomicUsize};
 use loom::thread;
 
 use std::collections::HashSet;
-use std::sync::atomic::Ordering::Relaxed;
+use std::sync::atomic::Ordering::{self, Acquire, Relaxed, Release, SeqCst};
 use std::sync::{Arc, Mutex};
 
 // Loom currently does not support load buffering.
@@ -57,3 +58,373 @@ fn store_buffering() {
     });
     assert!(values_.lock().unwrap().contains(&(0, 0)));
 }
+
+/// Outcomes of the store buffering litmus with the given orderings.
+fn store_buffering_ordered(store: Ordering, load: Ordering) -> HashSet<(usize, usize)> {
+    let values = Arc::new(Mutex::new(HashSet::new()));
+    let values_ = values.clone();
+    loom::model(move || {
+        let x = Arc::new(AtomicUsize::new(0));
+        let y = Arc::new(AtomicUsize::new(0));
+
+        let a = {
+            let (x, y) = (x.clone(), y.clone());
+            thread::spawn(move || {
+                x.store(1, store);
+                y.load(load)
+            })
+        };
+
+        y.store(1, store);
+        let b = x.load(load);
+
+        let a = a.join().unwrap();
+        values.lock().unwrap().insert((a, b));
+    });
+    let values = values_.lock().unwrap().clone();
+    values
+}
+
+#[test]
+fn store_buffering_release_acquire() {
+    assert!(store_buffering_ordered(Release, Acquire).contains(&(0, 0)));
+}
+
+#[test]
+fn store_buffering_seq_cst() {
+    let values = store_buffering_ordered(SeqCst, SeqCst);
+    assert!(!values.contains(&(0, 0)));
+    assert!(values.contains(&(0, 1)));
+    assert!(values.contains(&(1, 0)));
+    assert!(values.contains(&(1, 1)));
+}
+
+/// Outcomes of the store buffering litmus with the given store buffer depth.
+fn store_buffering_tso(depth: usize) -> HashSet<(usize, usize)> {
+    let values = Arc::new(Mutex::new(HashSet::new()));
+    let values_ = values.clone();
+    let mut builder = Builder::new();
+    builder.store_buffer_depth = Some(depth);
+    builder.check(move || {
+        let x = Arc::new(AtomicUsize::new(0));
+        let y = Arc::new(AtomicUsize::new(0));
+
+        let a = {
+            let (x, y) = (x.clone(), y.clone());
+            thread::spawn(move || {
+                x.store(1, Relaxed);
+                y.load(Relaxed)
+            })
+        };
+
+        y.store(1, Relaxed);
+        let b = x.load(Relaxed);
+
+        let a = a.join().unwrap();
+        values.lock().unwrap().insert((a, b));
+    });
+    let values = values_.lock().unwrap().clone();
+    values
+}
+
+// Without store buffers, one of the loads must see the other thread's store.
+#[test]
+fn store_buffering_tso_depth_0() {
+    let values = store_buffering_tso(0);
+    assert!(!values.contains(&(0, 0)));
+    assert!(values.contains(&(0, 1)));
+    assert!(values.contains(&(1, 0)));
+}
+
+// x86-TSO allows both loads to miss the buffered stores.
+#[test]
+fn store_buffering_tso_depth_1() {
+    assert!(store_buffering_tso(1).contains(&(0, 0)));
+}
+
+// Two threads store to each atomic and then load the other one. Whichever
+// load runs last comes after both stores to its atomic, one buffered by each
+// writer, so missing them all needs per-thread store buffers.
+#[test]
+fn store_buffering_tso_buffers_are_per_thread() {
+    let values = Arc::new(Mutex::new(HashSet::new()));
+    let values_ = values.clone();
+    let mut builder = Builder::new();
+    builder.store_buffer_depth = Some(1);
+    // Running the threads one after another already gets there.
+    builder.preemption_bound = Some(1);
+    builder.check(move || {
+        let x = Arc::new(AtomicUsize::new(0));
+        let y = Arc::new(AtomicUsize::new(0));
+
+        let spawn = |store: &Arc<AtomicUsize>, load: &Arc<AtomicUsize>, value| {
+            let (store, load) = (store.clone(), load.clone());
+            thread::spawn(move || {
+                store.store(value, Relaxed);
+                load.load(Relaxed)
+            })
+        };
+
+        let ths = [spawn(&x, &y, 1), spawn(&x, &y, 2), spawn(&y, &x, 1)];
+
+        y.store(2, Relaxed);
+        let mut loads = vec![x.load(Relaxed)];
+
+        loads.extend(ths.into_iter().map(|th| th.join().unwrap()));
+        values.lock().unwrap().insert(loads);
+    });
+
+    assert!(values_.lock().unwrap().contains(&vec![0; 4]));
+}
+
+// TSO is multi-copy atomic: once a store leaves its writer's buffer, every
+// other thread sees it, so the readers of IRIW agree on the order of the
+// independent writes.
+#[test]
+fn independent_reads_of_independent_writes_tso() {
+    let values = Arc::new(Mutex::new(HashSet::new()));
+    let values_ = values.clone();
+    let mut builder = Builder::new();
+    builder.store_buffer_depth = Some(1);
+    builder.check(move || {
+        let x = Arc::new(AtomicUsize::new(0));
+        let y = Arc::new(AtomicUsize::new(0));
+
+        let writers =
+            [x.clone(), y.clone()].map(|atomic| thread::spawn(move || atomic.store(1, Relaxed)));
+
+        let reader = {
+            let (x, y) = (x.clone(), y.clone());
+            thread::spawn(move || (x.load(Relaxed), y.load(Relaxed)))
+        };
+
+        let second = (y.load(Relaxed), x.load(Relaxed));
+        let first = reader.join().unwrap();
+
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        values.lock().unwrap().insert((first, second));
+    });
+
+    let values = values_.lock().unwrap();
+    assert!(!values.contains(&((1, 0), (1, 0))), "{:?}", values);
+    assert!(values.contains(&((1, 1), (1, 1))));
+}
+
+// Message passing with relaxed operations is correct under TSO.
+#[test]
+fn message_passing_tso() {
+    let mut builder = Builder::new();
+    builder.store_buffer_depth = Some(1);
+    builder.check(|| {
+        let data = Arc::new(AtomicUsize::new(0));
+        let flag = Arc::new(AtomicUsize::new(0));
+
+        let th = {
+            let (data, flag) = (data.clone(), flag.clone());
+            thread::spawn(move || {
+                data.store(1, Relaxed);
+                flag.store(1, Relaxed);
+            })
+        };
+
+        if flag.load(Relaxed) == 1 {
+            assert_eq!(data.load(Relaxed), 1);
+        }
+
+        th.join().unwrap();
+    });
+}
+
+// SB with RMW writes: the SeqCst fences forbid both threads missing the other's
+// write.
+#[test]
+fn store_buffering_rmw_fences() {
+    let values = Arc::new(Mutex::new(HashSet::new()));
+    let values_ = values.clone();
+    loom::model(move || {
+        let x = Arc::new(AtomicUsize::new(0));
+        let y = Arc::new(AtomicUsize::new(0));
+
+        let a = {
+            let (x, y) = (x.clone(), y.clone());
+            thread::spawn(move || {
+                x.fetch_add(1, Relaxed);
+                fence(SeqCst);
+                y.load(Relaxed)
+            })
+        };
+
+        y.compare_exchange(0, 1, Relaxed, Relaxed).unwrap();
+        fence(SeqCst);
+        let b = x.load(Relaxed);
+
+        let a = a.join().unwrap();
+        values.lock().unwrap().insert((a, b));
+    });
+    let values = values_.lock().unwrap();
+    assert!(!values.contains(&(0, 0)));
+    assert!(values.contains(&(0, 1)));
+    assert!(values.contains(&(1, 0)));
+    assert!(values.contains(&(1, 1)));
+}
+
+/// Outcomes of independent reads of independent writes with the given
+/// orderings, checking SeqCst loads against a single total order.
+fn iriw_ordered(store: Ordering, load: Ordering) -> HashSet<((usize, usize), (usize, usize))> {
+    let values = Arc::new(Mutex::new(HashSet::new()));
+    let values_ = values.clone();
+    let mut builder = Builder::new();
+    builder.check_seq_cst = true;
+    builder.check(move || {
+        let x = Arc::new(AtomicUsize::new(0));
+        let y = Arc::new(AtomicUsize::new(0));
+
+        let writer = {
+            let y = y.clone();
+            thread::spawn(move || y.store(1, store))
+        };
+
+        let readers: Vec<_> = [(x.clone(), y.clone()), (y.clone(), x.clone())]
+            .into_iter()
+            .map(|(first, second)| thread::spawn(move || (first.load(load), second.load(load))))
+            .collect();
+
+        x.store(1, store);
+
+        writer.join().unwrap();
+        let outcome: Vec<_> = readers.into_iter().map(|th| th.join().unwrap()).collect();
+        values.lock().unwrap().insert((outcome[0], outcome[1]));
+    });
+    let values = values_.lock().unwrap().clone();
+    values
+}
+
+// The readers may disagree on the order of the writes.
+#[test]
+fn iriw_release_acquire() {
+    assert!(iriw_ordered(Release, Acquire).contains(&((1, 0), (1, 0))));
+}
+
+// Both readers observe the writes in the single SeqCst order.
+#[test]
+fn iriw_seq_cst() {
+    let values = iriw_ordered(SeqCst, SeqCst);
+    assert!(!values.contains(&((1, 0), (1, 0))));
+    assert!(values.iter().any(|&(a, _)| a == (1, 0)));
+    assert!(values.iter().any(|&(_, b)| b == (1, 0)));
+}
+
+// IRIW with RMW writes and SeqCst fences between the reads. Both readers must
+// agree on the order of the independent writes.
+#[test]
+fn iriw_rmw_fences() {
+    let values = Arc::new(Mutex::new(HashSet::new()));
+    let values_ = values.clone();
+    loom::model(move || {
+        let x = Arc::new(AtomicUsize::new(0));
+        let y = Arc::new(AtomicUsize::new(0));
+
+        let writer = {
+            let y = y.clone();
+            thread::spawn(move || {
+                y.fetch_add(1, Relaxed);
+            })
+        };
+
+        let readers: Vec<_> = [(x.clone(), y.clone()), (y.clone(), x.clone())]
+            .into_iter()
+            .map(|(first, second)| {
+                thread::spawn(move || {
+                    let a = first.load(Relaxed);
+                    fence(SeqCst);
+                    (a, second.load(Relaxed))
+                })
+            })
+            .collect();
+
+        x.fetch_add(1, Relaxed);
+
+        writer.join().unwrap();
+        let outcome: Vec<_> = readers.into_iter().map(|th| th.join().unwrap()).collect();
+        values.lock().unwrap().insert((outcome[0], outcome[1]));
+    });
+    let values = values_.lock().unwrap();
+    // The first reader saw x but not y while the second saw y but not x.
+    assert!(!values.contains(&((1, 0), (1, 0))));
+    assert!(values.iter().any(|&(a, _)| a == (1, 0)));
+    assert!(values.iter().any(|&(_, b)| b == (1, 0)));
+}
+
+// 2+2W with SeqCst fences between the writes, where the second write of each
+// thread is an RMW. The final values cannot both be the first write of each
+// thread.
+#[test]
+fn two_plus_two_writes_fences() {
+    let values = Arc::new(Mutex::new(HashSet::new()));
+    let values_ = values.clone();
+    loom::model(move || {
+        let x = Arc::new(AtomicUsize::new(0));
+        let y = Arc::new(AtomicUsize::new(0));
+
+        let th = {
+            let (x, y) = (x.clone(), y.clone());
+            thread::spawn(move || {
+                x.store(1, Relaxed);
+                fence(SeqCst);
+                y.swap(2, Relaxed);
+            })
+        };
+
+        y.store(1, Relaxed);
+        fence(SeqCst);
+        x.swap(2, Relaxed);
+
+        th.join().unwrap();
+        values
+            .lock()
+            .unwrap()
+            .insert((x.load(Relaxed), y.load(Relaxed)));
+    });
+    let values = values_.lock().unwrap();
+    assert!(!values.contains(&(1, 1)));
+    assert!(values.contains(&(2, 2)));
+}
+
+#[test]
+fn message_passing_two_thread() {
+    loom::litmus::two_thread(
+        |(data, flag): &(AtomicUsize, AtomicUsize)| {
+            data.store(1, Relaxed);
+            flag.store(1, Relaxed);
+        },
+        |(data, flag)| (flag.load(Relaxed), data.load(Relaxed)),
+        |outcomes| {
+            // Relaxed accesses do not order the data store before the flag.
+            assert!(outcomes.contains(&((), (1, 0))));
+            assert!(outcomes.contains(&((), (1, 1))));
+        },
+    );
+}
+
+#[test]
+fn store_buffering_two_thread_fences() {
+    loom::litmus::two_thread(
+        |(x, y): &(AtomicUsize, AtomicUsize)| {
+            x.store(1, Relaxed);
+            fence(SeqCst);
+            y.load(Relaxed)
+        },
+        |(x, y)| {
+            y.store(1, Relaxed);
+            fence(SeqCst);
+            x.load(Relaxed)
+        },
+        |outcomes| {
+            assert!(!outcomes.contains(&(0, 0)));
+            assert_eq!(outcomes.len(), 3);
+        },
+    );
+}
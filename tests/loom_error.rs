@@ -0,0 +1,273 @@
+#![deny(warnings, rust_2018_idioms)]
+
+#[macro_use]
+mod support;
+
+use loom::cell::UnsafeCell;
+use loom::model::Builder;
+use loom::sync::atomic::AtomicBool;
+use loom::sync::{Arc, Mutex};
+use loom::thread;
+
+use std::rc::Rc;
+use std::sync::atomic::Ordering::Relaxed;
+
+#[test]
+fn ok() {
+    let res = Builder::new().try_check(|| {
+        let lock = Arc::new(Mutex::new(0));
+        let lock2 = lock.clone();
+
+        let th = thread::spawn(move || *lock2.lock().unwrap() += 1);
+        *lock.lock().unwrap() += 1;
+        th.join().unwrap();
+    });
+
+    assert_eq!(res, Ok(()));
+}
+
+/// Locks two mutexes in opposite orders from two threads.
+fn lock_ab_ba() {
+    let a = Rc::new(Mutex::new(()));
+    let b = Rc::new(Mutex::new(()));
+
+    let th1 = {
+        let (a, b) = (a.clone(), b.clone());
+        thread::spawn(move || {
+            let _a = a.lock().unwrap();
+            let _b = b.lock().unwrap();
+        })
+    };
+    let th2 = thread::spawn(move || {
+        let _b = b.lock().unwrap();
+        let _a = a.lock().unwrap();
+    });
+
+    th1.join().unwrap();
+    th2.join().unwrap();
+}
+
+#[test]
+fn deadlock() {
+    let res = Builder::new().try_check(lock_ab_ba);
+
+    let err = expect_err!(res, Deadlock);
+    assert!(err.failure().message().starts_with("deadlock"));
+    assert!(!err.failure().schedule().is_empty());
+}
+
+#[test]
+fn deadlock_names_wait_for_cycle() {
+    let err = expect_err!(Builder::new().try_check(lock_ab_ba), Deadlock);
+    let failure = err.failure();
+
+    let message = failure.message();
+    let cycle = message
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("cycle: "))
+        .unwrap_or_else(|| panic!("no wait-for cycle in {}", message));
+
+    // Each thread waits on the mutex the other one holds.
+    for mutex in ["Mutex#0", "Mutex#1"] {
+        assert!(
+            cycle.contains(&format!("waiting on {} held by thread #", mutex)),
+            "{}",
+            cycle
+        );
+    }
+    assert!(
+        message.contains("thread #1 waiting on Mutex#1 held by thread #2"),
+        "{}",
+        message
+    );
+    assert!(
+        message.contains("thread #2 waiting on Mutex#0 held by thread #1"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn data_race() {
+    let res = Builder::new().try_check(|| {
+        let cell = Arc::new(UnsafeCell::new(0));
+        let cell2 = cell.clone();
+
+        let th = thread::spawn(move || cell2.with_mut(|ptr| unsafe { *ptr = 1 }));
+        cell.with_mut(|ptr| unsafe { *ptr = 2 });
+        th.join().unwrap();
+    });
+
+    let err = expect_err!(res, DataRace);
+    assert!(err.failure().message().starts_with("Causality violation"));
+}
+
+#[test]
+fn leak() {
+    let res = Builder::new().try_check(|| {
+        std::mem::forget(Arc::new(()));
+    });
+
+    let err = expect_err!(res, Leak);
+    assert!(err.failure().message().starts_with("Arc leaked"));
+    assert!(err.failure().object_index().is_some());
+}
+
+/// Two nodes pointing at each other through `Arc`s.
+#[derive(Default)]
+struct Node {
+    next: Mutex<Option<Arc<Node>>>,
+}
+
+#[test]
+fn arc_cycle_leak_reports_allocation_site() {
+    let mut builder = Builder::new();
+    builder.location = true;
+
+    // Where `a` is allocated.
+    let site = format!("{}:{}", file!(), line!() + 2);
+    let res = builder.try_check(|| {
+        let a = Arc::new(Node::default());
+        let b = Arc::new(Node::default());
+
+        *a.next.lock().unwrap() = Some(b.clone());
+        *b.next.lock().unwrap() = Some(a);
+    });
+
+    let err = expect_err!(res, Leak);
+    assert!(err.failure().message().starts_with("Arc leaked"));
+    assert!(err.failure().message().contains(&site));
+}
+
+#[test]
+fn arc_cycle_broken_does_not_leak() {
+    let res = Builder::new().try_check(|| {
+        let a = Arc::new(Node::default());
+        let b = Arc::new(Node::default());
+
+        *a.next.lock().unwrap() = Some(b.clone());
+        *b.next.lock().unwrap() = Some(a.clone());
+
+        thread::spawn(move || {
+            b.next.lock().unwrap().take();
+        })
+        .join()
+        .unwrap();
+    });
+
+    assert!(res.is_ok(), "{:?}", res);
+}
+
+#[test]
+fn track_leak() {
+    let res = Builder::new().try_check(|| {
+        std::mem::forget(loom::alloc::Track::new(0));
+    });
+
+    let err = expect_err!(res, Leak);
+    assert!(err.failure().message().starts_with("Allocation leaked"));
+}
+
+#[test]
+fn dealloc_twice() {
+    use loom::alloc::{alloc, dealloc, Layout};
+
+    let res = Builder::new().try_check(|| unsafe {
+        let layout = Layout::new::<usize>();
+        let ptr = alloc(layout);
+        dealloc(ptr, layout);
+        dealloc(ptr, layout);
+    });
+
+    // The first `dealloc` stops tracking the pointer.
+    expect_err!(res, UntrackedDealloc);
+}
+
+#[test]
+fn branch_limit_exceeded() {
+    let mut builder = Builder::new();
+    builder.max_branches = 20;
+
+    let res = builder.try_check(|| {
+        let flag = AtomicBool::new(false);
+
+        for _ in 0..30 {
+            assert!(!flag.load(Relaxed));
+        }
+    });
+
+    expect_err!(res, BranchLimitExceeded);
+}
+
+#[test]
+fn branch_limit_exceeded_names_thread() {
+    let mut builder = Builder::new();
+    builder.max_branches = 50;
+    builder.location = true;
+
+    // Where the spinning thread loads the flag.
+    let site = format!("{}:{}", file!(), line!() + 5);
+    let res = builder.try_check(|| {
+        let flag = std::sync::Arc::new(AtomicBool::new(false));
+        let flag2 = flag.clone();
+
+        let th = thread::spawn(move || while !flag2.load(Relaxed) {});
+
+        flag.store(true, Relaxed);
+        th.join().unwrap();
+    });
+
+    let err = expect_err!(res, BranchLimitExceeded);
+    let message = err.failure().message();
+
+    assert!(message.contains("maximum number of branches (50)"));
+    assert!(message.contains("Builder::max_branches"));
+    assert!(message.contains("Thread #1: Atomic(Load)"), "{}", message);
+    assert!(message.contains(&site), "{}", message);
+}
+
+#[test]
+fn invariant_violated() {
+    let res = Builder::new().try_check(|| {
+        let num = Arc::new(Mutex::new(0));
+        let num2 = num.clone();
+
+        let th = thread::spawn(move || *num2.lock().unwrap() = 1);
+        assert_eq!(0, *num.lock().unwrap(), "saw the spawned thread");
+        th.join().unwrap();
+    });
+
+    let err = expect_err!(res, InvariantViolated);
+    assert!(err.failure().message().contains("saw the spawned thread"));
+    // The spawned thread must run before the assertion.
+    assert!(err.failure().schedule().contains(&1));
+}
+
+#[test]
+#[should_panic(expected = "Arc leaked")]
+fn check_still_panics() {
+    Builder::new().check(|| {
+        std::mem::forget(Arc::new(()));
+    });
+}
+
+#[test]
+fn permutation_limit_exceeded() {
+    let mut builder = Builder::new();
+    builder.assert_max_permutations(1);
+
+    let res = builder.try_check(|| {
+        let num = Arc::new(AtomicBool::new(false));
+        let num2 = num.clone();
+
+        let th = thread::spawn(move || num2.store(true, Relaxed));
+        num.load(Relaxed);
+        th.join().unwrap();
+    });
+
+    let err = expect_err!(res, PermutationLimitExceeded);
+    assert!(err
+        .failure()
+        .message()
+        .starts_with("Model explored 2 permutations"));
+}
@@ -0,0 +1,55 @@
+#![deny(warnings, rust_2018_idioms)]
+
+#[macro_use]
+mod support;
+
+use loom::sync::atomic::AtomicUsize;
+
+use std::sync::atomic::Ordering::Relaxed;
+
+#[test]
+#[should_panic(expected = "Model created more than 10 objects")]
+fn unbounded_objects() {
+    let mut builder = loom::model::Builder::new();
+    builder.max_objects = Some(10);
+
+    builder.check(|| {
+        let mut n = 0;
+
+        while n < 100 {
+            let num = AtomicUsize::new(n);
+            n = num.load(Relaxed) + 1;
+        }
+    });
+}
+
+#[test]
+fn unbounded_objects_is_typed_error() {
+    let mut builder = loom::model::Builder::new();
+    builder.max_objects = Some(10);
+
+    let res = builder.try_check(|| {
+        let mut n = 0;
+
+        while n < 100 {
+            let num = AtomicUsize::new(n);
+            n = num.load(Relaxed) + 1;
+        }
+    });
+
+    let err = expect_err!(res, ObjectLimitExceeded);
+    assert!(err.failure().message().contains("more than 10 objects"));
+}
+
+#[test]
+fn objects_within_bound() {
+    let mut builder = loom::model::Builder::new();
+    builder.max_objects = Some(10);
+
+    builder.check(|| {
+        for n in 0..10 {
+            let num = AtomicUsize::new(n);
+            assert_eq!(n, num.load(Relaxed));
+        }
+    });
+}
@@ -0,0 +1,52 @@
+#![deny(warnings, rust_2018_idioms)]
+
+#[macro_use]
+mod support;
+
+use loom::sync::atomic::AtomicUsize;
+
+use std::sync::atomic::Ordering::Relaxed;
+
+#[test]
+#[should_panic(expected = "single permutation exceeded 100 operations; possible infinite loop")]
+fn infinite_loop() {
+    let mut builder = loom::model::Builder::new();
+    builder.max_operations = Some(100);
+    builder.max_branches = 10_000;
+
+    builder.check(|| {
+        let num = AtomicUsize::new(0);
+
+        while num.load(Relaxed) == 0 {}
+    });
+}
+
+#[test]
+fn infinite_loop_is_typed_error() {
+    let mut builder = loom::model::Builder::new();
+    builder.max_operations = Some(100);
+    builder.max_branches = 10_000;
+
+    let res = builder.try_check(|| {
+        let num = AtomicUsize::new(0);
+
+        while num.load(Relaxed) == 0 {}
+    });
+
+    let err = expect_err!(res, OperationLimitExceeded);
+    assert!(err.failure().message().contains("exceeded 100 operations"));
+}
+
+#[test]
+fn operations_within_bound() {
+    let mut builder = loom::model::Builder::new();
+    builder.max_operations = Some(100);
+
+    builder.check(|| {
+        let num = AtomicUsize::new(0);
+
+        for n in 0..50 {
+            num.store(n, Relaxed);
+        }
+    });
+}
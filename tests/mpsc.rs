@@ -1,4 +1,4 @@
-use loom::sync::mpsc::channel;
+use loom::sync::mpsc::{channel, sync_channel};
 use loom::thread;
 
 #[test]
@@ -87,3 +87,63 @@ fn drop_receiver() {
         assert_eq!(r.recv().unwrap(), 1);
     });
 }
+
+#[test]
+fn bounded_channel_two_senders() {
+    loom::model(|| {
+        let (s, r) = sync_channel(1);
+        let s2 = s.clone();
+
+        let th1 = thread::spawn(move || {
+            s.send(1).unwrap();
+            s.send(2).unwrap();
+        });
+        let th2 = thread::spawn(move || {
+            s2.send(3).unwrap();
+        });
+
+        let mut vals: Vec<i32> = (0..3).map(|_| r.recv().unwrap()).collect();
+        vals.sort_unstable();
+        assert_eq!(vals, [1, 2, 3]);
+
+        th1.join().unwrap();
+        th2.join().unwrap();
+    });
+}
+
+#[test]
+fn recv_fails_once_senders_dropped() {
+    loom::model(|| {
+        let (s, r) = sync_channel(1);
+        let s2 = s.clone();
+
+        thread::spawn(move || {
+            s.send(1).unwrap();
+        });
+        thread::spawn(move || {
+            drop(s2);
+        });
+
+        assert_eq!(r.recv(), Ok(1));
+        assert!(r.recv().is_err());
+    });
+}
+
+#[test]
+fn send_fails_once_receiver_dropped() {
+    loom::model(|| {
+        let (s, r) = sync_channel(1);
+
+        let th = thread::spawn(move || {
+            // The second send blocks on a full channel until the receiver is
+            // dropped, unless the receiver drained the first message.
+            let first = s.send(1);
+            let second = s.send(2);
+            assert!(first.is_ok() || second.is_err());
+            second
+        });
+
+        drop(r);
+        let _ = th.join().unwrap();
+    });
+}
@@ -2,9 +2,10 @@
 
 use loom::cell::UnsafeCell;
 use loom::sync::atomic::AtomicUsize;
-use loom::sync::Mutex;
+use loom::sync::{Arc, Mutex};
 use loom::thread;
 
+use std::collections::HashSet;
 use std::rc::Rc;
 use std::sync::atomic::Ordering::SeqCst;
 
@@ -91,3 +92,255 @@ fn mutex_into_inner() {
         assert_eq!(lock, 2);
     })
 }
+
+/// Returns the priorities in the order the waiters acquired the lock, for
+/// every explored execution where both were waiting when it was released.
+fn wake_orders(new: fn(Vec<u32>) -> Mutex<Vec<u32>>) -> HashSet<Vec<u32>> {
+    let orders: &'static _ = Box::leak(Box::new(std::sync::Mutex::new(HashSet::new())));
+
+    let mut builder = loom::model::Builder::new();
+    // Atomics are not preemption points, so each waiter announces itself and
+    // blocks on the mutex without being interleaved.
+    builder.sync_points_only = true;
+
+    builder.check(move || {
+        let lock = Arc::new(new(vec![]));
+        let waiting = Arc::new(AtomicUsize::new(0));
+
+        let guard = lock.lock().unwrap();
+
+        let ths: Vec<_> = [1, 2]
+            .into_iter()
+            .map(|priority| {
+                let (lock, waiting) = (lock.clone(), waiting.clone());
+                thread::Builder::new()
+                    .priority(priority)
+                    .spawn(move || {
+                        waiting.fetch_add(1, SeqCst);
+                        lock.lock().unwrap().push(priority);
+                    })
+                    .unwrap()
+            })
+            .collect();
+
+        while waiting.load(SeqCst) < 2 {
+            thread::yield_now();
+        }
+
+        drop(guard);
+
+        for th in ths {
+            th.join().unwrap();
+        }
+
+        let order = lock.lock().unwrap().clone();
+        orders.lock().unwrap().insert(order);
+    });
+
+    let orders = orders.lock().unwrap().clone();
+    orders
+}
+
+#[test]
+fn mutex_wakes_any_waiter() {
+    let orders = wake_orders(Mutex::new);
+
+    assert!(orders.contains(&vec![1, 2]));
+    assert!(orders.contains(&vec![2, 1]));
+}
+
+#[test]
+fn priority_inheritance_mutex_wakes_highest_priority_waiter() {
+    let orders = wake_orders(Mutex::with_priority_inheritance);
+
+    assert_eq!(orders.into_iter().collect::<Vec<_>>(), [vec![2, 1]]);
+}
+
+#[test]
+fn waiter_can_be_starved() {
+    const ROUNDS: usize = 2;
+
+    let orders: &'static _ = Box::leak(Box::new(std::sync::Mutex::new(HashSet::new())));
+
+    let mut builder = loom::model::Builder::new();
+    builder.sync_points_only = true;
+    // Starving the waiter takes no preemptions: the other threads simply keep
+    // running after each release.
+    builder.preemption_bound = Some(2);
+
+    builder.check(move || {
+        let lock = Arc::new(Mutex::new(vec![]));
+        let waiting = Arc::new(AtomicUsize::new(0));
+
+        let guard = lock.lock().unwrap();
+
+        // Thread 0 is the first to wait for the lock.
+        let waiter = {
+            let (lock, waiting) = (lock.clone(), waiting.clone());
+            thread::spawn(move || {
+                waiting.fetch_add(1, SeqCst);
+                lock.lock().unwrap().push(0);
+            })
+        };
+
+        while waiting.load(SeqCst) < 1 {
+            thread::yield_now();
+        }
+
+        let ths: Vec<_> = [1, 2]
+            .into_iter()
+            .map(|id| {
+                let lock = lock.clone();
+                thread::spawn(move || {
+                    for _ in 0..ROUNDS {
+                        lock.lock().unwrap().push(id);
+                    }
+                })
+            })
+            .collect();
+
+        drop(guard);
+
+        waiter.join().unwrap();
+        for th in ths {
+            th.join().unwrap();
+        }
+
+        let order = lock.lock().unwrap().clone();
+        assert_eq!(1 + 2 * ROUNDS, order.len());
+        orders.lock().unwrap().insert(order);
+    });
+
+    let orders = orders.lock().unwrap();
+
+    // The waiter may win the lock as soon as it is released...
+    assert!(orders.iter().any(|order| order[0] == 0));
+
+    // ...or lose it to every other acquisition.
+    assert!(orders.iter().any(|order| order[2 * ROUNDS] == 0));
+}
+
+#[test]
+fn try_lock_observes_held_and_free_lock() {
+    let outcomes: &'static _ = Box::leak(Box::new(std::sync::Mutex::new(HashSet::new())));
+
+    loom::model(move || {
+        let lock = Arc::new(Mutex::new(0));
+
+        let th = {
+            let lock = lock.clone();
+            thread::spawn(move || {
+                let mut guard = lock.lock().unwrap();
+                // Releasing the lock is not a preemption point, so yield while
+                // holding it to let `try_lock` run in between.
+                thread::yield_now();
+                *guard += 1;
+            })
+        };
+
+        let acquired = match lock.try_lock() {
+            Ok(guard) => {
+                assert!(*guard == 0 || *guard == 1);
+                true
+            }
+            Err(_) => false,
+        };
+
+        th.join().unwrap();
+        outcomes.lock().unwrap().insert(acquired);
+    });
+
+    assert_eq!(*outcomes.lock().unwrap(), HashSet::from([true, false]));
+}
+
+#[test]
+fn panic_while_locked_poisons_mutex() {
+    let outcomes: &'static _ = Box::leak(Box::new(std::sync::Mutex::new(HashSet::new())));
+
+    loom::model(move || {
+        let lock = Arc::new(Mutex::new(0));
+
+        let th = {
+            let lock = lock.clone();
+            thread::spawn(move || {
+                // The model fails on uncaught panics, so poisoning is only
+                // observable if the panic is caught.
+                let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    let mut guard = lock.lock().unwrap();
+                    *guard += 1;
+                    panic!("poison");
+                }));
+                assert!(res.is_err());
+            })
+        };
+
+        let poisoned = match lock.lock() {
+            Ok(guard) => {
+                assert_eq!(*guard, 0);
+                false
+            }
+            Err(err) => {
+                assert_eq!(*err.into_inner(), 1);
+                true
+            }
+        };
+
+        th.join().unwrap();
+        assert!(lock.is_poisoned());
+
+        lock.clear_poison();
+        assert_eq!(*lock.lock().unwrap(), 1);
+
+        outcomes.lock().unwrap().insert(poisoned);
+    });
+
+    assert_eq!(*outcomes.lock().unwrap(), HashSet::from([false, true]));
+}
+
+/// Hands a mutex over to another thread through an `AtomicPtr` and takes
+/// exclusive access to it there.
+fn hand_over_mutex(store: std::sync::atomic::Ordering, load: std::sync::atomic::Ordering) {
+    use loom::sync::atomic::AtomicPtr;
+
+    loom::model(move || {
+        let slot = Arc::new(AtomicPtr::new(std::ptr::null_mut()));
+
+        let th = {
+            let slot = slot.clone();
+            thread::spawn(move || {
+                let lock = Box::new(Mutex::new(0));
+                *lock.lock().unwrap() += 1;
+                slot.store(Box::into_raw(lock), store);
+            })
+        };
+
+        let ptr = slot.load(load);
+
+        if !ptr.is_null() {
+            let mut lock = unsafe { Box::from_raw(ptr) };
+            assert_eq!(1, *lock.get_mut().unwrap());
+        }
+
+        th.join().unwrap();
+
+        if ptr.is_null() {
+            let lock = unsafe { Box::from_raw(slot.load(load)) };
+            assert_eq!(1, lock.into_inner().unwrap());
+        }
+    });
+}
+
+#[test]
+fn valid_get_mut() {
+    use std::sync::atomic::Ordering::{Acquire, Release};
+
+    hand_over_mutex(Release, Acquire);
+}
+
+#[test]
+#[should_panic(expected = "Causality violation")]
+fn invalid_get_mut() {
+    use std::sync::atomic::Ordering::Relaxed;
+
+    hand_over_mutex(Relaxed, Relaxed);
+}
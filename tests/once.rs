@@ -0,0 +1,138 @@
+#![deny(warnings, rust_2018_idioms)]
+
+use loom::cell::UnsafeCell;
+use loom::sync::atomic::AtomicUsize;
+use loom::sync::{Arc, Once, OnceCell};
+use loom::thread;
+
+use std::sync::atomic::Ordering::Relaxed;
+
+struct Lazy {
+    once: Once,
+    value: UnsafeCell<usize>,
+}
+
+// `value` is only written inside `call_once`.
+unsafe impl Send for Lazy {}
+unsafe impl Sync for Lazy {}
+
+impl Lazy {
+    fn get(&self) -> usize {
+        self.once
+            .call_once(|| self.value.with_mut(|v| unsafe { *v = 42 }));
+        self.value.with(|v| unsafe { *v })
+    }
+}
+
+#[test]
+fn racing_call_once_initializes_once() {
+    loom::model(|| {
+        let lazy = Arc::new(Lazy {
+            once: Once::new(),
+            value: UnsafeCell::new(0),
+        });
+
+        let th = {
+            let lazy = lazy.clone();
+            thread::spawn(move || lazy.get())
+        };
+
+        assert_eq!(42, lazy.get());
+        assert_eq!(42, th.join().unwrap());
+        assert!(lazy.once.is_completed());
+    });
+}
+
+#[test]
+fn call_once_runs_closure_once() {
+    loom::model(|| {
+        let once = Arc::new(Once::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let ths: Vec<_> = (0..2)
+            .map(|_| {
+                let (once, calls) = (once.clone(), calls.clone());
+                thread::spawn(move || {
+                    once.call_once(|| {
+                        calls.fetch_add(1, Relaxed);
+                    })
+                })
+            })
+            .collect();
+
+        for th in ths {
+            th.join().unwrap();
+        }
+
+        assert_eq!(1, calls.load(Relaxed));
+    });
+}
+
+#[test]
+fn racing_get_or_init_runs_one_closure() {
+    loom::model(|| {
+        let cell = Arc::new(OnceCell::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let init = |cell: &OnceCell<usize>, calls: &AtomicUsize, value| {
+            *cell.get_or_init(|| {
+                calls.fetch_add(1, Relaxed);
+                value
+            })
+        };
+
+        let th = {
+            let (cell, calls) = (cell.clone(), calls.clone());
+            thread::spawn(move || init(&cell, &calls, 1))
+        };
+
+        // Each thread offers a different value, so agreeing on one means
+        // both saw the same initialization.
+        let mine = init(&cell, &calls, 2);
+        let theirs = th.join().unwrap();
+
+        assert_eq!(mine, theirs);
+        assert_eq!(1, calls.load(Relaxed));
+    });
+}
+
+#[test]
+fn set_after_value_exists_fails() {
+    loom::model(|| {
+        let cell = Arc::new(OnceCell::new());
+
+        let th = {
+            let cell = cell.clone();
+            thread::spawn(move || cell.set(1))
+        };
+
+        let mine = cell.set(2);
+        let theirs = th.join().unwrap();
+
+        match (mine, theirs) {
+            (Ok(()), Err(1)) => assert_eq!(Some(&2), cell.get()),
+            (Err(2), Ok(())) => assert_eq!(Some(&1), cell.get()),
+            res => panic!("unexpected results {:?}", res),
+        }
+    });
+}
+
+#[test]
+fn get_before_set_is_empty() {
+    loom::model(|| {
+        let cell = Arc::new(OnceCell::new());
+
+        let th = {
+            let cell = cell.clone();
+            thread::spawn(move || cell.set(String::from("hello")).unwrap())
+        };
+
+        // Either empty or fully initialized, never torn.
+        if let Some(value) = cell.get() {
+            assert_eq!("hello", value);
+        }
+
+        th.join().unwrap();
+        assert_eq!("hello", cell.get().unwrap());
+    });
+}
@@ -0,0 +1,108 @@
+#![deny(warnings, rust_2018_idioms)]
+
+#[macro_use]
+mod support;
+
+use loom::model::Builder;
+use loom::sync::atomic::AtomicUsize;
+use loom::sync::{Arc, Mutex};
+use loom::thread;
+
+use std::sync::atomic::Ordering::SeqCst;
+
+/// Two threads increment a counter, releasing the lock between reading and
+/// writing it.
+fn lost_update() {
+    let num = Arc::new(Mutex::new(0));
+
+    let ths: Vec<_> = (0..2)
+        .map(|_| {
+            let num = num.clone();
+            thread::spawn(move || {
+                let curr = *num.lock().unwrap();
+                *num.lock().unwrap() = curr + 1;
+            })
+        })
+        .collect();
+
+    for th in ths {
+        th.join().unwrap();
+    }
+
+    assert_eq!(2, *num.lock().unwrap(), "lost update");
+}
+
+fn random(iterations: usize, seed: u64) -> Builder {
+    let mut builder = Builder::new();
+    builder.random_iterations(iterations).random_seed(seed);
+    builder
+}
+
+#[test]
+fn random_finds_lost_update() {
+    let res = random(50, 7).try_check(lost_update);
+
+    let err = expect_err!(res, InvariantViolated);
+    assert!(err.failure().message().contains("lost update"));
+}
+
+#[test]
+fn random_is_reproducible_from_seed() {
+    let first = random(50, 7).try_check(lost_update).unwrap_err();
+    let second = random(50, 7).try_check(lost_update).unwrap_err();
+
+    assert_eq!(first.failure().schedule(), second.failure().schedule());
+}
+
+#[test]
+fn random_failure_prints_seed() {
+    // The seed is printed to stderr, so the check runs in a child process.
+    if std::env::var("LOOM_RANDOM_SEED_CHILD").is_ok() {
+        random(50, 7).check(lost_update);
+        return;
+    }
+
+    let output = std::process::Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "random_failure_prints_seed"])
+        .args(["--nocapture", "--test-threads=1"])
+        .env("LOOM_RANDOM_SEED_CHILD", "1")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("failed; seed = 7"), "{}", stderr);
+}
+
+#[test]
+fn random_passes_correct_model() {
+    let res = random(50, 7).try_check(|| {
+        let num = Arc::new(AtomicUsize::new(0));
+
+        let th = {
+            let num = num.clone();
+            thread::spawn(move || num.fetch_add(1, SeqCst))
+        };
+
+        num.fetch_add(1, SeqCst);
+        th.join().unwrap();
+
+        assert_eq!(2, num.load(SeqCst));
+    });
+
+    assert_eq!(res, Ok(()));
+}
+
+#[test]
+fn random_runs_requested_iterations() {
+    use std::sync::atomic::AtomicUsize;
+
+    static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+    random(20, 1).check(|| {
+        RUNS.fetch_add(1, SeqCst);
+    });
+
+    assert_eq!(20, RUNS.load(SeqCst));
+}
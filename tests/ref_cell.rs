@@ -0,0 +1,90 @@
+#![deny(warnings, rust_2018_idioms)]
+
+use loom::cell::RefCell;
+use loom::thread;
+
+use std::sync::Arc;
+
+/// Shares a `RefCell` between threads, which the borrow tracking must flag.
+struct Shared(RefCell<usize>);
+
+unsafe impl Sync for Shared {}
+
+#[test]
+fn borrows() {
+    loom::model(|| {
+        let cell = RefCell::new(1);
+
+        {
+            let a = cell.borrow();
+            let b = cell.borrow();
+            assert_eq!(2, *a + *b);
+        }
+
+        *cell.borrow_mut() += 1;
+        assert_eq!(2, cell.replace(3));
+        assert_eq!(3, cell.into_inner());
+    });
+}
+
+#[test]
+#[should_panic(expected = "already borrowed")]
+fn double_mutable_borrow() {
+    loom::model(|| {
+        let cell = RefCell::new(1);
+
+        let _a = cell.borrow_mut();
+        let _b = cell.borrow_mut();
+    });
+}
+
+#[test]
+#[should_panic(expected = "already mutably borrowed")]
+fn borrow_while_mutably_borrowed() {
+    loom::model(|| {
+        let cell = RefCell::new(1);
+
+        let _a = cell.borrow_mut();
+        let _b = cell.borrow();
+    });
+}
+
+#[test]
+#[should_panic]
+fn cross_thread_conflict() {
+    loom::model(|| {
+        let shared = Arc::new(Shared(RefCell::new(0)));
+
+        let th = {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                let value = shared.0.borrow();
+                // Lets the other thread run while the borrow is live.
+                thread::yield_now();
+                assert!(*value <= 1);
+            })
+        };
+
+        *shared.0.borrow_mut() += 1;
+
+        th.join().unwrap();
+    });
+}
+
+#[test]
+#[should_panic(expected = "Causality violation")]
+fn cross_thread_race_without_overlap() {
+    loom::model(|| {
+        let shared = Arc::new(Shared(RefCell::new(0)));
+
+        let th = {
+            let shared = shared.clone();
+            thread::spawn(move || *shared.0.borrow_mut() += 1)
+        };
+
+        // The borrows never overlap, but nothing orders them either.
+        *shared.0.borrow_mut() += 1;
+
+        th.join().unwrap();
+    });
+}
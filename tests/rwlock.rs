@@ -1,7 +1,10 @@
-use loom::sync::{Arc, RwLock, TryLockResult};
+use loom::cell::UnsafeCell;
+use loom::sync::atomic::AtomicUsize;
+use loom::sync::{Arc, RwLock, RwLockUpgradableReadGuard, TryLockResult};
 use loom::thread;
 
 use std::rc::Rc;
+use std::sync::atomic::Ordering::SeqCst;
 use std::sync::TryLockError;
 
 #[test]
@@ -162,3 +165,106 @@ fn rwlock_into_inner() {
         assert_eq!(lock, 2);
     })
 }
+
+#[test]
+fn rwlock_readers_see_consistent_snapshot() {
+    loom::model(|| {
+        let lock = Arc::new(RwLock::new((0, 0)));
+
+        let readers: Vec<_> = (0..2)
+            .map(|_| {
+                let lock = lock.clone();
+
+                thread::spawn(move || {
+                    let pair = lock.read().unwrap();
+                    assert_eq!(pair.0, pair.1);
+                    pair.0
+                })
+            })
+            .collect();
+
+        {
+            let mut pair = lock.write().unwrap();
+            pair.0 = 1;
+            thread::yield_now();
+            pair.1 = 1;
+        }
+
+        for reader in readers {
+            let seen = reader.join().unwrap();
+            assert!(seen == 0 || seen == 1);
+        }
+    });
+}
+
+#[test]
+fn rwlock_upgrade_waits_for_readers() {
+    loom::model(|| {
+        // The cell is guarded by the lock, so loom reports any access that is
+        // not ordered by it.
+        let state = Arc::new((RwLock::new(()), UnsafeCell::new(0)));
+
+        let readers: Vec<_> = (0..2)
+            .map(|_| {
+                let state = state.clone();
+                thread::spawn(move || {
+                    let _guard = state.0.read().unwrap();
+                    // Releasing the lock is not a preemption point, so yield
+                    // while holding it to let the upgrade race with it.
+                    thread::yield_now();
+                    let val = state.1.with(|ptr| unsafe { *ptr });
+                    assert!(val == 0 || val == 1);
+                })
+            })
+            .collect();
+
+        let guard = state.0.upgradable_read().unwrap();
+        assert_eq!(0, state.1.with(|ptr| unsafe { *ptr }));
+
+        let _guard = RwLockUpgradableReadGuard::upgrade(guard);
+        state.1.with_mut(|ptr| unsafe { *ptr = 1 });
+        drop(_guard);
+
+        for th in readers {
+            th.join().unwrap();
+        }
+    });
+}
+
+#[test]
+fn rwlock_upgradable_reads_are_exclusive() {
+    loom::model(|| {
+        let state = Arc::new((RwLock::new(()), AtomicUsize::new(0)));
+
+        let ths: Vec<_> = (0..2)
+            .map(|_| {
+                let state = state.clone();
+                thread::spawn(move || {
+                    let _guard = state.0.upgradable_read().unwrap();
+                    assert_eq!(0, state.1.fetch_add(1, SeqCst));
+                    state.1.fetch_sub(1, SeqCst);
+                })
+            })
+            .collect();
+
+        for th in ths {
+            th.join().unwrap();
+        }
+    });
+}
+
+#[test]
+fn rwlock_upgradable_read_admits_readers() {
+    loom::model(|| {
+        let lock = Arc::new(RwLock::new(1));
+        let guard = lock.upgradable_read().unwrap();
+
+        let th = {
+            let lock = lock.clone();
+            thread::spawn(move || *lock.read().unwrap())
+        };
+
+        assert_eq!(1, th.join().unwrap());
+        assert_eq!(1, *guard);
+    });
+}
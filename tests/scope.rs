@@ -0,0 +1,99 @@
+#![deny(warnings, rust_2018_idioms)]
+
+use loom::sync::atomic::AtomicUsize;
+use loom::sync::Mutex;
+use loom::thread;
+
+use std::sync::atomic::Ordering::Relaxed;
+
+#[test]
+fn scoped_threads_mutate_borrowed_slice() {
+    loom::model(|| {
+        let mut data = [0, 0];
+
+        thread::scope(|s| {
+            let (left, right) = data.split_at_mut(1);
+            s.spawn(move || left[0] += 1);
+            s.spawn(move || right[0] += 2);
+        });
+
+        // Returning from the scope joins both threads.
+        assert_eq!(data, [1, 2]);
+    });
+}
+
+#[test]
+fn scoped_threads_share_borrowed_mutex() {
+    loom::model(|| {
+        let data = Mutex::new(vec![]);
+
+        thread::scope(|s| {
+            for i in 0..2 {
+                let data = &data;
+                s.spawn(move || data.lock().unwrap().push(i));
+            }
+        });
+
+        let mut data = data.into_inner().unwrap();
+        data.sort_unstable();
+        assert_eq!(data, [0, 1]);
+    });
+}
+
+#[test]
+fn scope_end_happens_after_relaxed_store() {
+    loom::model(|| {
+        let num = AtomicUsize::new(0);
+
+        let ret = thread::scope(|s| {
+            let th = s.spawn(|| num.load(Relaxed));
+            s.spawn(|| num.store(1, Relaxed));
+            th.join().unwrap()
+        });
+
+        assert!(ret == 0 || ret == 1);
+        // The store happens-before the end of the scope, so it is visible.
+        assert_eq!(1, num.load(Relaxed));
+    });
+}
+
+#[test]
+fn scope_waits_for_threads_when_closure_panics() {
+    loom::model(|| {
+        let num = AtomicUsize::new(0);
+
+        let ret = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            thread::scope(|s| {
+                s.spawn(|| num.store(1, Relaxed));
+                panic!("scope closure");
+            })
+        }));
+
+        assert!(ret.is_err());
+        // The thread was joined before the panic left the scope.
+        assert_eq!(1, num.load(Relaxed));
+    });
+}
+
+#[test]
+fn scope_resumes_unjoined_panic() {
+    loom::model(|| {
+        let ret = std::panic::catch_unwind(|| {
+            thread::scope(|s| {
+                s.spawn(|| panic!("scoped thread"));
+            })
+        });
+
+        let payload = ret.unwrap_err();
+        assert_eq!(Some(&"scoped thread"), payload.downcast_ref::<&str>());
+    });
+}
+
+#[test]
+fn scope_does_not_resume_joined_panic() {
+    loom::model(|| {
+        let joined = thread::scope(|s| s.spawn(|| panic!("scoped thread")).join());
+
+        assert!(joined.is_err());
+    });
+}
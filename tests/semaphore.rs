@@ -0,0 +1,56 @@
+#![deny(warnings, rust_2018_idioms)]
+
+use loom::model::Builder;
+use loom::sync::{Arc, Semaphore};
+use loom::thread;
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::SeqCst;
+
+#[test]
+fn at_most_two_holders() {
+    let mut builder = Builder::new();
+    builder.preemption_bound = Some(2);
+
+    builder.check(|| {
+        let semaphore = Arc::new(Semaphore::new(2));
+        // Bookkeeping only, not part of the model.
+        let holders = Arc::new(AtomicUsize::new(0));
+
+        let ths: Vec<_> = (0..3)
+            .map(|_| {
+                let (semaphore, holders) = (semaphore.clone(), holders.clone());
+
+                thread::spawn(move || {
+                    semaphore.acquire(1);
+
+                    let held = holders.fetch_add(1, SeqCst) + 1;
+                    assert!(held <= 2, "{} threads hold a permit", held);
+                    thread::yield_now();
+                    holders.fetch_sub(1, SeqCst);
+
+                    semaphore.release(1);
+                })
+            })
+            .collect();
+
+        for th in ths {
+            th.join().unwrap();
+        }
+
+        assert_eq!(2, semaphore.available_permits());
+    });
+}
+
+#[test]
+fn try_acquire_without_permits() {
+    loom::model(|| {
+        let semaphore = Semaphore::new(1);
+
+        assert!(semaphore.try_acquire(1));
+        assert!(!semaphore.try_acquire(1));
+
+        semaphore.release(1);
+        assert!(semaphore.try_acquire(1));
+    });
+}
@@ -0,0 +1,67 @@
+#![deny(warnings, rust_2018_idioms)]
+
+use loom::sync::atomic::{fence, AtomicUsize};
+use loom::thread;
+
+use std::collections::HashSet;
+use std::sync::atomic::Ordering::SeqCst;
+use std::sync::Arc;
+
+// A reader that sees the second of two SeqCst stores also sees the first,
+// whichever other SeqCst operations the threads interleave.
+#[test]
+fn seq_cst_respects_program_order() {
+    loom::model(|| {
+        let x = Arc::new(AtomicUsize::new(0));
+        let y = Arc::new(AtomicUsize::new(0));
+
+        let th = {
+            let (x, y) = (x.clone(), y.clone());
+            thread::spawn(move || {
+                x.store(1, SeqCst);
+                fence(SeqCst);
+                y.store(1, SeqCst);
+            })
+        };
+
+        if y.load(SeqCst) == 1 {
+            assert_eq!(1, x.load(SeqCst));
+        }
+
+        th.join().unwrap();
+    });
+}
+
+// Two readers never disagree on the order of independent SeqCst stores
+// (independent reads of independent writes).
+#[test]
+fn seq_cst_readers_agree_on_store_order() {
+    let values = Arc::new(std::sync::Mutex::new(HashSet::new()));
+    let values_ = values.clone();
+
+    loom::model(move || {
+        let x = Arc::new(AtomicUsize::new(0));
+        let y = Arc::new(AtomicUsize::new(0));
+
+        let writers =
+            [x.clone(), y.clone()].map(|atomic| thread::spawn(move || atomic.store(1, SeqCst)));
+
+        let reader = {
+            let (x, y) = (x.clone(), y.clone());
+            thread::spawn(move || (x.load(SeqCst), y.load(SeqCst)))
+        };
+
+        let second = (y.load(SeqCst), x.load(SeqCst));
+        let first = reader.join().unwrap();
+
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        values.lock().unwrap().insert((first, second));
+    });
+
+    let values = values_.lock().unwrap();
+    assert!(!values.contains(&((1, 0), (1, 0))), "{:?}", values);
+    assert!(values.contains(&((1, 1), (1, 1))));
+}
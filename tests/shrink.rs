@@ -0,0 +1,144 @@
+#![deny(warnings, rust_2018_idioms)]
+
+#[macro_use]
+mod support;
+
+use loom::model::Builder;
+use loom::sync::atomic::AtomicUsize;
+use loom::sync::{Arc, Mutex};
+use loom::thread;
+
+use std::rc::Rc;
+use std::sync::atomic::Ordering::SeqCst;
+
+/// Two threads each increment a counter three times with a separate load
+/// and store. Preempting a thread between the two loses an increment.
+fn lost_increment() {
+    let num = Arc::new(AtomicUsize::new(0));
+
+    let ths: Vec<_> = (0..2)
+        .map(|_| {
+            let num = num.clone();
+            thread::spawn(move || {
+                for _ in 0..3 {
+                    let n = num.load(SeqCst);
+                    num.store(n + 1, SeqCst);
+                }
+            })
+        })
+        .collect();
+
+    for th in ths {
+        th.join().unwrap();
+    }
+
+    assert_eq!(6, num.load(SeqCst), "lost an increment");
+}
+
+fn switches(schedule: &[usize]) -> usize {
+    schedule.windows(2).filter(|w| w[0] != w[1]).count()
+}
+
+// Random exploration switches threads far more often than the bug needs.
+fn random(shrink: bool) -> Builder {
+    let mut builder = Builder::new();
+    builder.random_iterations(1000).random_seed(1);
+    builder.shrink = shrink;
+    builder
+}
+
+#[test]
+fn shrink_finds_minimal_interleaving() {
+    let padded = random(false).try_check(lost_increment).unwrap_err();
+    let shrunk = expect_err!(random(true).try_check(lost_increment), InvariantViolated);
+
+    let message = shrunk.failure().message();
+    assert!(message.contains("lost an increment"), "{}", message);
+
+    assert!(switches(padded.failure().schedule()) > 4);
+
+    // Thread #1 loads, thread #2 runs to completion, then thread #1 stores
+    // the stale value.
+    let mut expected = vec![0, 0, 1, 1];
+    expected.extend([2; 9]);
+    expected.extend([1; 7]);
+    expected.extend([0; 4]);
+
+    assert_eq!(expected, shrunk.failure().schedule());
+}
+
+#[test]
+fn shrink_keeps_error_kind() {
+    let model = || {
+        // `Rc`, as dropping a loom `Arc` while the deadlock unwinds aborts.
+        let a = Rc::new(Mutex::new(()));
+        let b = Rc::new(Mutex::new(()));
+
+        let th = {
+            let (a, b) = (a.clone(), b.clone());
+            thread::spawn(move || {
+                let _b = b.lock().unwrap();
+                let _a = a.lock().unwrap();
+            })
+        };
+
+        {
+            let _a = a.lock().unwrap();
+            let _b = b.lock().unwrap();
+        }
+
+        th.join().unwrap();
+    };
+
+    let mut builder = Builder::new();
+    builder.shrink = true;
+
+    let err = expect_err!(builder.try_check(model), Deadlock);
+    let message = err.failure().message();
+    assert!(message.contains("deadlock"), "{}", message);
+}
+
+#[test]
+fn shrink_keeps_failing_assertion() {
+    // Every permutation fails, but only the ones that lose an increment fail
+    // the first assertion. The simplest permutation loses nothing.
+    let model = || {
+        let num = Arc::new(AtomicUsize::new(0));
+
+        let th = {
+            let num = num.clone();
+            thread::spawn(move || {
+                let n = num.load(SeqCst);
+                num.store(n + 1, SeqCst);
+            })
+        };
+
+        let n = num.load(SeqCst);
+        num.store(n + 1, SeqCst);
+        th.join().unwrap();
+
+        assert_ne!(1, num.load(SeqCst), "lost an increment");
+        panic!("no increment lost");
+    };
+
+    // With this seed, the first random permutation loses an increment.
+    let mut builder = Builder::new();
+    builder.random_iterations(1000).random_seed(2);
+    builder.shrink = true;
+
+    let err = expect_err!(builder.try_check(model), InvariantViolated);
+    let message = err.failure().message();
+    assert!(message.contains("lost an increment"), "{}", message);
+}
+
+#[test]
+fn shrink_stops_at_max_duration() {
+    let padded = random(false).try_check(lost_increment).unwrap_err();
+
+    let mut builder = random(true);
+    builder.max_duration = Some(std::time::Duration::from_nanos(1));
+    let shrunk = builder.try_check(lost_increment).unwrap_err();
+
+    // The check already ran out of time, so nothing is replayed.
+    assert_eq!(padded.failure().schedule(), shrunk.failure().schedule());
+}
@@ -0,0 +1,10 @@
+/// Unwraps the error of a `try_check` result, panicking unless it is the
+/// `LoomError` variant named `$variant`.
+macro_rules! expect_err {
+    ($res:expr, $variant:ident) => {
+        match $res {
+            Err(err @ loom::model::LoomError::$variant(..)) => err,
+            res => panic!("unexpected result: {:?}", res),
+        }
+    };
+}
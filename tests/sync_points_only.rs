@@ -0,0 +1,120 @@
+#![deny(warnings, rust_2018_idioms)]
+
+use loom::sync::atomic::AtomicUsize;
+use loom::sync::{Arc, Mutex};
+use loom::thread;
+
+use std::collections::HashSet;
+use std::sync::atomic::Ordering::SeqCst;
+
+fn observed_pairs(sync_points_only: bool) -> HashSet<(usize, usize)> {
+    let seen: &'static _ = Box::leak(Box::new(std::sync::Mutex::new(HashSet::new())));
+
+    let mut builder = loom::model::Builder::new();
+    builder.sync_points_only = sync_points_only;
+
+    builder.check(move || {
+        let num = Arc::new(AtomicUsize::new(0));
+        let lock = Arc::new(Mutex::new(()));
+
+        let th = {
+            let (num, lock) = (num.clone(), lock.clone());
+            thread::spawn(move || {
+                let pair = (num.fetch_add(1, SeqCst), num.fetch_add(1, SeqCst));
+                drop(lock.lock().unwrap());
+                pair
+            })
+        };
+
+        drop(lock.lock().unwrap());
+        num.fetch_add(1, SeqCst);
+
+        let pair = th.join().unwrap();
+        seen.lock().unwrap().insert(pair);
+    });
+
+    let seen = seen.lock().unwrap().clone();
+    seen
+}
+
+#[test]
+fn atomics_are_preemption_points_by_default() {
+    assert!(observed_pairs(false).contains(&(0, 2)));
+}
+
+#[test]
+fn atomics_are_not_preemption_points() {
+    let seen = observed_pairs(true);
+
+    assert!(!seen.contains(&(0, 2)));
+    assert!(seen.contains(&(0, 1)));
+    assert!(seen.contains(&(1, 2)));
+}
+
+#[test]
+#[should_panic]
+fn lock_race_is_found() {
+    let mut builder = loom::model::Builder::new();
+    builder.sync_points_only = true;
+
+    builder.check(|| {
+        let num = Arc::new(Mutex::new(0));
+
+        let ths: Vec<_> = (0..2)
+            .map(|_| {
+                let num = num.clone();
+                thread::spawn(move || {
+                    let curr = *num.lock().unwrap();
+                    *num.lock().unwrap() = curr + 1;
+                })
+            })
+            .collect();
+
+        for th in ths {
+            th.join().unwrap();
+        }
+
+        assert_eq!(2, *num.lock().unwrap());
+    });
+}
+
+#[test]
+fn racing_atomics_are_ordered_both_ways() {
+    let seen: &'static _ = Box::leak(Box::new(std::sync::Mutex::new(HashSet::new())));
+
+    let mut builder = loom::model::Builder::new();
+    builder.sync_points_only = true;
+
+    builder.check(move || {
+        let num = Arc::new(AtomicUsize::new(0));
+        let lock = Arc::new(Mutex::new(()));
+
+        let th = {
+            let num = num.clone();
+            thread::spawn(move || num.store(1, SeqCst))
+        };
+
+        drop(lock.lock().unwrap());
+        seen.lock().unwrap().insert(num.load(SeqCst));
+        th.join().unwrap();
+    });
+
+    // The spawned thread never touches the mutex. Its store can only run
+    // before the load if DPOR sees the two atomics race and backtracks to
+    // the lock.
+    assert_eq!(*seen.lock().unwrap(), HashSet::from([0, 1]));
+}
+
+#[test]
+#[should_panic(expected = "single permutation exceeded 100 operations")]
+fn atomics_count_toward_max_operations() {
+    let mut builder = loom::model::Builder::new();
+    builder.sync_points_only = true;
+    builder.max_operations = Some(100);
+
+    builder.check(|| {
+        let num = AtomicUsize::new(0);
+
+        while num.load(SeqCst) == 0 {}
+    });
+}
@@ -143,3 +143,208 @@ fn park_unpark_std() {
     std::thread::park();
     println!("it did not deadlock");
 }
+
+#[test]
+fn detached_thread_runs_to_completion() {
+    use loom::sync::atomic::AtomicUsize;
+    use loom::sync::Arc;
+    use std::collections::HashSet;
+    use std::sync::atomic::Ordering::{Relaxed, SeqCst};
+    use std::sync::Mutex;
+
+    static EXECUTIONS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+    static COMPLETED: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+    static SEEN: Mutex<Option<HashSet<usize>>> = Mutex::new(None);
+
+    loom::model(|| {
+        EXECUTIONS.fetch_add(1, Relaxed);
+
+        let num = Arc::new(AtomicUsize::new(0));
+
+        // Dropping the handle detaches the thread.
+        drop({
+            let num = num.clone();
+            thread::spawn(move || {
+                num.fetch_add(1, SeqCst);
+                COMPLETED.fetch_add(1, Relaxed);
+            })
+        });
+
+        let seen = num.load(SeqCst);
+        SEEN.lock()
+            .unwrap()
+            .get_or_insert_with(HashSet::new)
+            .insert(seen);
+    });
+
+    // Every execution ran the detached thread to termination, and the
+    // increment was explored both before and after the load.
+    assert_eq!(EXECUTIONS.load(Relaxed), COMPLETED.load(Relaxed));
+    let seen = SEEN.lock().unwrap().take().unwrap();
+    assert!(seen.contains(&0));
+    assert!(seen.contains(&1));
+}
+
+fn read_after_spawn(inherit_causality: bool) {
+    use loom::cell::UnsafeCell;
+    use loom::sync::Arc;
+
+    struct Cell(UnsafeCell<usize>);
+    unsafe impl Sync for Cell {}
+
+    loom::model(move || {
+        let cell = Arc::new(Cell(UnsafeCell::new(0)));
+        cell.0.with_mut(|ptr| unsafe { *ptr = 1 });
+
+        let th = {
+            let cell = cell.clone();
+            thread::Builder::new()
+                .inherit_causality(inherit_causality)
+                .spawn(move || cell.0.with(|ptr| unsafe { *ptr }))
+                .unwrap()
+        };
+
+        assert_eq!(th.join().unwrap(), 1);
+    });
+}
+
+#[test]
+fn spawn_inherits_causality() {
+    read_after_spawn(true);
+}
+
+#[test]
+#[should_panic(expected = "Causality violation")]
+fn spawn_without_causality_flags_pre_spawn_write() {
+    read_after_spawn(false);
+}
+
+fn park_until_ready(recheck: bool) {
+    use loom::model::Builder;
+    use loom::sync::atomic::AtomicBool;
+    use loom::sync::Arc;
+    use std::sync::atomic::Ordering::{Acquire, Release};
+
+    let mut builder = Builder::new();
+    builder.spurious_park = true;
+
+    builder.check(move || {
+        let ready = Arc::new(AtomicBool::new(false));
+        let parked = thread::current();
+
+        let th = {
+            let ready = ready.clone();
+            thread::spawn(move || {
+                ready.store(true, Release);
+                parked.unpark();
+            })
+        };
+
+        if recheck {
+            while !ready.load(Acquire) {
+                thread::park();
+            }
+        } else if !ready.load(Acquire) {
+            thread::park();
+        }
+
+        assert!(ready.load(Acquire), "woke up before ready");
+
+        th.join().unwrap();
+    });
+}
+
+#[test]
+fn park_in_loop_handles_spurious_wakeup() {
+    park_until_ready(true);
+}
+
+#[test]
+#[should_panic(expected = "woke up before ready")]
+fn park_without_loop_misses_spurious_wakeup() {
+    park_until_ready(false);
+}
+
+#[test]
+fn park_timeout_in_loop_terminates() {
+    use loom::sync::atomic::AtomicBool;
+    use loom::sync::Arc;
+    use std::sync::atomic::Ordering::{Acquire, Release};
+    use std::time::Duration;
+
+    loom::model(|| {
+        let ready = Arc::new(AtomicBool::new(false));
+        let parked = thread::current();
+
+        let th = {
+            let ready = ready.clone();
+            thread::spawn(move || {
+                ready.store(true, Release);
+                parked.unpark();
+            })
+        };
+
+        while !ready.load(Acquire) {
+            thread::park_timeout(Duration::from_millis(10));
+        }
+
+        th.join().unwrap();
+    });
+}
+
+#[test]
+fn unpark_while_yielding_is_saved() {
+    use loom::sync::atomic::AtomicBool;
+    use loom::sync::Arc;
+    use std::sync::atomic::Ordering::{Acquire, Release};
+
+    loom::model(|| {
+        let ready = Arc::new(AtomicBool::new(false));
+        let spinner = thread::current();
+
+        let th = {
+            let ready = ready.clone();
+            thread::spawn(move || {
+                ready.store(true, Release);
+                spinner.unpark();
+            })
+        };
+
+        while !ready.load(Acquire) {
+            thread::yield_now();
+        }
+
+        // The unpark happened before `ready` was seen, so it was saved even
+        // if this thread was yielding at the time.
+        thread::park();
+
+        th.join().unwrap();
+    });
+}
+
+#[test]
+fn join_returns_value() {
+    loom::model(|| {
+        let th = thread::spawn(|| (1..=4).product::<usize>());
+
+        assert_eq!(24, th.join().unwrap());
+    });
+}
+
+#[test]
+fn join_returns_panic() {
+    loom::model(|| {
+        let th = thread::spawn(|| -> usize { panic!("boom") });
+
+        let payload = th.join().unwrap_err();
+        assert_eq!(Some(&"boom"), payload.downcast_ref::<&str>());
+    });
+}
+
+#[test]
+#[should_panic(expected = "boom")]
+fn detached_panic_fails_model() {
+    loom::model(|| {
+        thread::spawn(|| panic!("boom"));
+    });
+}
@@ -107,3 +107,96 @@ fn drop() {
     // should also be dropped.
     assert_eq!(DROPS.load(Ordering::Acquire), 3);
 }
+
+#[test]
+fn drop_order_is_deterministic() {
+    use std::sync::Mutex;
+
+    static DROPPED: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+    struct Local(usize);
+
+    impl Drop for Local {
+        fn drop(&mut self) {
+            DROPPED.lock().unwrap().push(self.0);
+        }
+    }
+
+    loom::thread_local! {
+        static A: Local = Local(0);
+        static B: Local = Local(1);
+        static C: Local = Local(2);
+        static D: Local = Local(3);
+    }
+
+    // Hash order differs between processes, so each run of the model happens
+    // in its own child process and prints the drop order of every execution.
+    if std::env::var("LOOM_DROP_ORDER_CHILD").is_ok() {
+        let mut builder = loom::model::Builder::new();
+        builder.preemption_bound = Some(2);
+
+        builder.check(|| {
+            let th = thread::spawn(|| {
+                A.with(|_| {});
+                B.with(|_| {});
+                C.with(|_| {});
+                D.with(|_| {});
+            });
+            thread::yield_now();
+            th.join().unwrap();
+
+            println!(
+                "dropped: {:?}",
+                std::mem::take(&mut *DROPPED.lock().unwrap())
+            );
+        });
+
+        return;
+    }
+
+    let run = || {
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "drop_order_is_deterministic"])
+            .args(["--nocapture", "--test-threads=1"])
+            .env("LOOM_DROP_ORDER_CHILD", "1")
+            .output()
+            .unwrap();
+
+        assert!(output.status.success());
+
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .filter_map(|line| Some(line.split_once("dropped: ")?.1.to_owned()))
+            .collect::<Vec<_>>()
+    };
+
+    let first = run();
+    assert!(first.len() > 1);
+    assert_eq!(first, run());
+}
+
+#[test]
+fn independent_counters_reset_between_executions() {
+    use std::cell::Cell;
+
+    loom::thread_local! {
+        static COUNTER: Cell<usize> = Cell::new(0);
+    }
+
+    fn count_to(n: usize) {
+        for i in 0..n {
+            // Starts from zero in every thread and every execution.
+            assert_eq!(i, COUNTER.with(|c| c.replace(i + 1)));
+            thread::yield_now();
+        }
+    }
+
+    loom::model(|| {
+        let th = thread::spawn(|| count_to(2));
+        count_to(3);
+        th.join().unwrap();
+
+        COUNTER.with(|c| assert_eq!(3, c.get()));
+    });
+}
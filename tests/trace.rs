@@ -0,0 +1,53 @@
+#![deny(warnings, rust_2018_idioms)]
+
+use loom::sync::atomic::AtomicUsize;
+use loom::thread;
+
+use std::process::Command;
+use std::sync::atomic::Ordering::SeqCst;
+use std::sync::Arc;
+
+/// Fails once the spawned thread's store runs before the load.
+fn store_before_load() {
+    let num = Arc::new(AtomicUsize::new(0));
+
+    let th = {
+        let num = num.clone();
+        thread::spawn(move || num.store(1, SeqCst))
+    };
+
+    assert_eq!(0, num.load(SeqCst));
+    th.join().unwrap();
+}
+
+#[test]
+fn trace_lists_threads_in_activation_order() {
+    // The trace is printed to stderr, so the check runs in a child process.
+    if std::env::var("LOOM_TRACE_CHILD").is_ok() {
+        let mut builder = loom::model::Builder::new();
+        builder.trace = true;
+        builder.check(store_before_load);
+        return;
+    }
+
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "trace_lists_threads_in_activation_order"])
+        .args(["--nocapture", "--test-threads=1"])
+        .env("LOOM_TRACE_CHILD", "1")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let trace = &stderr[stderr.find("schedule trace:").expect(&stderr)..];
+
+    let store = trace.find("thread #1: Atomic(Store)").expect(trace);
+    let load = trace.find("thread #0: Atomic(Load)").expect(trace);
+    // The load names the store it read, by thread and that thread's clock.
+    let read = trace
+        .find("thread #0: read 1, written by thread #1 at op 2")
+        .expect(trace);
+
+    assert!(store < load && load < read, "{}", trace);
+}
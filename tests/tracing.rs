@@ -0,0 +1,202 @@
+#![deny(warnings, rust_2018_idioms)]
+
+use loom::sync::atomic::AtomicUsize;
+
+use std::fmt;
+use std::sync::atomic::Ordering::SeqCst;
+use std::sync::{Arc, Mutex};
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+/// Name and value of each field of an event.
+type Fields = Vec<(String, String)>;
+
+/// Records the fields of every event.
+#[derive(Clone, Default)]
+struct Events(Arc<Mutex<Vec<Fields>>>);
+
+struct Visitor<'a>(&'a mut Fields);
+
+impl Visit for Visitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .push((field.name().to_string(), format!("{:?}", value)));
+    }
+}
+
+impl<S: Subscriber> Layer<S> for Events {
+    fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+        let mut fields = Vec::new();
+        event.record(&mut Visitor(&mut fields));
+        self.0.lock().unwrap().push(fields);
+    }
+}
+
+impl Events {
+    /// Returns the value of `field` for each event with the given message.
+    fn field(&self, message: &str, field: &str) -> Vec<String> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|fields| fields.contains(&("message".to_string(), message.to_string())))
+            .flat_map(|fields| fields.iter().filter(|(name, _)| name == field))
+            .map(|(_, value)| value.clone())
+            .collect()
+    }
+
+    /// Returns the message of each event containing `pattern`.
+    fn messages(&self, pattern: &str) -> Vec<String> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .flat_map(|fields| fields.iter().filter(|(name, _)| name == "message"))
+            .filter(|(_, value)| value.contains(pattern))
+            .map(|(_, value)| value.clone())
+            .collect()
+    }
+}
+
+/// Checks `f` with `report_redundant_sync`, returning the reported sites.
+fn redundant_acquires<F>(f: F) -> Vec<String>
+where
+    F: Fn() + Sync + Send + 'static,
+{
+    let events = Events::default();
+    let subscriber = tracing_subscriber::registry().with(events.clone());
+
+    tracing::subscriber::with_default(subscriber, || {
+        let mut builder = loom::model::Builder::new();
+        builder.report_redundant_sync = true;
+        builder.check(f);
+    });
+
+    events.messages("never synchronized")
+}
+
+/// Returns `true` if one of `reports` names a site on `line` of this file.
+fn reported(reports: &[String], line: u32) -> bool {
+    reported_as(reports, "", line)
+}
+
+/// Returns `true` if one of `reports` names a site on `line` of this file
+/// with the given `ordering`.
+fn reported_as(reports: &[String], ordering: &str, line: u32) -> bool {
+    let site = format!("{} at {}:{}:", ordering, file!(), line);
+    reports.iter().any(|report| report.contains(&site))
+}
+
+#[test]
+fn store_events_record_store_index() {
+    let events = Events::default();
+    let subscriber = tracing_subscriber::registry().with(events.clone());
+
+    tracing::subscriber::with_default(subscriber, || {
+        loom::model::Builder::new().check(|| {
+            let num = AtomicUsize::new(0);
+            num.store(1, SeqCst);
+            num.store(2, SeqCst);
+            assert_eq!(2, num.load(SeqCst));
+        });
+    });
+
+    // The initial value is store 0.
+    assert_eq!(vec!["1", "2"], events.field("Atomic::store", "store_index"));
+    assert_eq!(vec!["2"], events.field("Atomic::load", "store_index"));
+    assert!(!events.field("schedule", "path_pos").is_empty());
+}
+
+#[test]
+fn report_redundant_sync() {
+    use loom::thread;
+    use std::sync::atomic::Ordering::{Acquire, Release};
+
+    /// Line of each load in the model, recorded as the model runs.
+    static LINES: Mutex<[u32; 2]> = Mutex::new([0; 2]);
+
+    let reports = redundant_acquires(|| {
+        let num = Arc::new(AtomicUsize::new(0));
+        let num2 = num.clone();
+
+        let th = thread::spawn(move || num2.store(1, Release));
+
+        // Load-bearing until the join below.
+        let (line, _) = (line!(), num.load(Acquire));
+        LINES.lock().unwrap()[0] = line;
+        th.join().unwrap();
+
+        // Redundant: the join already synchronized with the store.
+        let (line, value) = (line!(), num.load(Acquire));
+        LINES.lock().unwrap()[1] = line;
+        assert_eq!(value, 1);
+    });
+
+    let [first, second] = *LINES.lock().unwrap();
+
+    assert_eq!(1, reports.len(), "{:?}", reports);
+    assert!(!reported(&reports, first), "{:?}", reports);
+    assert!(reported_as(&reports, "Acquire", second), "{:?}", reports);
+}
+
+#[test]
+fn report_redundant_sync_covers_acq_rel_and_seq_cst() {
+    use std::sync::atomic::Ordering::AcqRel;
+
+    static LINES: Mutex<[u32; 2]> = Mutex::new([0; 2]);
+
+    // Nothing else stores, so no acquire can synchronize.
+    let reports = redundant_acquires(|| {
+        let num = AtomicUsize::new(0);
+
+        let (line, _) = (line!(), num.load(SeqCst));
+        LINES.lock().unwrap()[0] = line;
+        let (line, _) = (line!(), num.fetch_add(1, AcqRel));
+        LINES.lock().unwrap()[1] = line;
+    });
+
+    let [load, rmw] = *LINES.lock().unwrap();
+
+    assert_eq!(2, reports.len(), "{:?}", reports);
+    assert!(reported_as(&reports, "SeqCst", load), "{:?}", reports);
+    assert!(reported_as(&reports, "AcqRel", rmw), "{:?}", reports);
+}
+
+#[test]
+fn thread_switches_log_reason() {
+    use loom::thread;
+
+    let events = Events::default();
+    let subscriber = tracing_subscriber::registry().with(events.clone());
+
+    tracing::subscriber::with_default(subscriber, || {
+        loom::model::Builder::new().check(|| {
+            let num = Arc::new(AtomicUsize::new(0));
+
+            let th = {
+                let num = num.clone();
+                thread::spawn(move || {
+                    num.store(1, SeqCst);
+                    thread::yield_now();
+                })
+            };
+
+            num.load(SeqCst);
+            th.join().unwrap();
+        });
+    });
+
+    let switches = events.messages("~~~~~~~~ THREAD");
+
+    for reason in ["terminated", "blocked", "yielded", "preempted"] {
+        let logged = format!(" {}) ~~~~~~~~", reason);
+        assert!(
+            switches.iter().any(|switch| switch.ends_with(&logged)),
+            "{}: {:?}",
+            reason,
+            switches
+        );
+    }
+}
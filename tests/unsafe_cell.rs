@@ -1,4 +1,5 @@
 #![deny(warnings, rust_2018_idioms)]
+#![allow(clippy::arc_with_non_send_sync)]
 
 use loom::cell::UnsafeCell;
 use loom::sync::atomic::AtomicUsize;
@@ -333,3 +334,80 @@ fn unsafe_cell_access_after_sync() {
         }
     });
 }
+
+#[test]
+fn unsafe_cell_mutex_ok() {
+    use loom::sync::Mutex;
+
+    loom::model(|| {
+        let s1 = Arc::new((Mutex::new(()), Data::new(0)));
+        let s2 = s1.clone();
+
+        let th = thread::spawn(move || {
+            let _guard = s1.0.lock().unwrap();
+            s1.1.inc();
+        });
+
+        {
+            let _guard = s2.0.lock().unwrap();
+            s2.1.inc();
+        }
+
+        th.join().unwrap();
+
+        assert_eq!(2, s2.1.get());
+    });
+}
+
+#[test]
+#[should_panic]
+fn mut_ptr_overlapping_threads() {
+    loom::model(|| {
+        let x = Arc::new(UnsafeCell::new(0));
+        let y = x.clone();
+
+        let th = thread::spawn(move || {
+            let ptr = x.get_mut();
+            // Gives the other thread a chance to run while `ptr` is live.
+            thread::yield_now();
+            unsafe { ptr.with(|v| *v += 1) };
+        });
+
+        y.get_mut().with(|v| unsafe { *v += 1 });
+
+        th.join().unwrap();
+    });
+}
+
+#[test]
+#[should_panic]
+fn mut_ptr_aliased_on_one_thread() {
+    loom::model(|| {
+        let x = UnsafeCell::new(0);
+
+        let ptr = x.get_mut();
+        let _aliased = x.get();
+        drop(ptr);
+    });
+}
+
+#[test]
+fn ptr_guards_dropped_between_accesses() {
+    loom::model(|| {
+        let x = UnsafeCell::new(0);
+
+        let ptr = x.get_mut();
+        ptr.with(|v| unsafe { *v = 1 });
+        drop(ptr);
+
+        // Shared access may overlap.
+        let a = x.get();
+        let b = x.get();
+        assert_eq!(1, a.with(|v| unsafe { *v }));
+        assert_eq!(1, b.with(|v| unsafe { *v }));
+        drop((a, b));
+
+        x.get_mut().with(|v| unsafe { *v = 2 });
+        assert_eq!(2, x.with(|v| unsafe { *v }));
+    });
+}
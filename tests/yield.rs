@@ -1,5 +1,8 @@
 #![deny(warnings, rust_2018_idioms)]
 
+#[macro_use]
+mod support;
+
 use loom::sync::atomic::AtomicUsize;
 use loom::thread;
 
@@ -27,3 +30,243 @@ fn yield_completes() {
         }
     });
 }
+
+#[test]
+fn spin_loop_handshake_completes() {
+    use loom::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering::{Acquire, Release};
+
+    fn spin_until(flag: &AtomicBool) {
+        while !flag.load(Acquire) {
+            thread::yield_now();
+        }
+    }
+
+    loom::model(|| {
+        let flags = Arc::new([AtomicBool::new(false), AtomicBool::new(false)]);
+
+        let th = {
+            let flags = flags.clone();
+            thread::spawn(move || {
+                flags[0].store(true, Release);
+                spin_until(&flags[1]);
+            })
+        };
+
+        spin_until(&flags[0]);
+        flags[1].store(true, Release);
+
+        th.join().unwrap();
+    });
+}
+
+/// A test-and-set spinlock guarding a non-atomic counter.
+struct SpinLock {
+    locked: loom::sync::atomic::AtomicBool,
+    count: loom::cell::UnsafeCell<usize>,
+}
+
+// `count` is only accessed while holding the lock.
+unsafe impl Sync for SpinLock {}
+
+impl SpinLock {
+    fn increment(&self) {
+        use std::sync::atomic::Ordering::{Acquire, Release};
+
+        while self
+            .locked
+            .compare_exchange(false, true, Acquire, Relaxed)
+            .is_err()
+        {
+            loom::hint::spin_loop();
+        }
+
+        self.count.with_mut(|count| unsafe { *count += 1 });
+        self.locked.store(false, Release);
+    }
+}
+
+#[test]
+fn spin_loop_lock_completes() {
+    loom::model(|| {
+        let lock = Arc::new(SpinLock {
+            locked: loom::sync::atomic::AtomicBool::new(false),
+            count: loom::cell::UnsafeCell::new(0),
+        });
+
+        let th = {
+            let lock = lock.clone();
+            thread::spawn(move || lock.increment())
+        };
+
+        lock.increment();
+        th.join().unwrap();
+
+        assert_eq!(2, lock.count.with(|count| unsafe { *count }));
+    });
+}
+
+#[test]
+fn spin_loop_waiting_on_busy_producer_completes() {
+    use loom::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering::{Acquire, Release};
+
+    loom::model(|| {
+        let work = Arc::new(AtomicUsize::new(0));
+        let ready = Arc::new(AtomicBool::new(false));
+
+        let th = {
+            let (work, ready) = (work.clone(), ready.clone());
+
+            // More operations than `max_spins` before publishing, letting the
+            // consumer spin after each one.
+            thread::spawn(move || {
+                for _ in 0..150 {
+                    work.fetch_add(1, Relaxed);
+                    thread::yield_now();
+                }
+
+                ready.store(true, Release);
+            })
+        };
+
+        // The flag reads the same every time, but the producer keeps going.
+        while !ready.load(Acquire) {
+            loom::hint::spin_loop();
+        }
+
+        assert_eq!(150, work.load(Relaxed));
+        th.join().unwrap();
+    });
+}
+
+#[test]
+fn spin_loop_without_progress_is_livelock() {
+    use loom::model::Builder;
+
+    let mut builder = Builder::new();
+    builder.location = true;
+
+    let res = builder.try_check(|| {
+        let flag = Arc::new(AtomicUsize::new(0));
+
+        let th = {
+            let flag = flag.clone();
+
+            // Nothing ever sets the flag.
+            thread::spawn(move || {
+                while flag.load(Relaxed) == 0 {
+                    loom::hint::spin_loop();
+                }
+            })
+        };
+
+        th.join().unwrap();
+    });
+
+    let err = expect_err!(res, Livelock);
+    let message = err.failure().message();
+    assert!(message.contains("Livelock: thread #1"));
+    assert!(message.contains(file!()), "{}", message);
+}
+
+#[test]
+fn spin_lock_missing_release_is_livelock() {
+    use loom::model::Builder;
+    use loom::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering::Acquire;
+
+    /// Acquires the lock and never releases it.
+    fn lock_and_forget(locked: &AtomicBool) {
+        while locked
+            .compare_exchange(false, true, Acquire, Relaxed)
+            .is_err()
+        {
+            loom::hint::spin_loop();
+        }
+    }
+
+    let mut builder = Builder::new();
+    builder.max_spins(10);
+
+    let res = builder.try_check(|| {
+        let locked = Arc::new(AtomicBool::new(false));
+
+        let th = {
+            let locked = locked.clone();
+            thread::spawn(move || lock_and_forget(&locked))
+        };
+
+        lock_and_forget(&locked);
+        th.join().unwrap();
+    });
+
+    expect_err!(res, Livelock);
+}
+
+#[test]
+fn repeated_yields_are_not_livelock() {
+    loom::model(|| {
+        for _ in 0..150 {
+            thread::yield_now();
+        }
+    });
+}
+
+#[test]
+fn spin_loop_polling_mutex_makes_progress() {
+    use loom::model::Builder;
+    use loom::sync::Mutex;
+
+    let mut builder = Builder::new();
+    builder.max_spins(1);
+
+    builder.check(|| {
+        let state = Arc::new(Mutex::new((0, false)));
+
+        let th = {
+            let state = state.clone();
+            thread::spawn(move || {
+                for _ in 0..2 {
+                    state.lock().unwrap().0 += 1;
+                }
+
+                state.lock().unwrap().1 = true;
+            })
+        };
+
+        // Locking the mutex is progress, even though no atomic is read.
+        while !state.lock().unwrap().1 {
+            loom::hint::spin_loop();
+        }
+
+        th.join().unwrap();
+    });
+}
+
+#[test]
+fn spin_loop_polling_mutex_never_set_is_livelock() {
+    use loom::model::Builder;
+    use loom::sync::Mutex;
+
+    let mut builder = Builder::new();
+    builder.max_spins(10);
+
+    let res = builder.try_check(|| {
+        let flag = Arc::new(Mutex::new(false));
+
+        // Exits without setting the flag.
+        let th = {
+            let flag = flag.clone();
+            thread::spawn(move || drop(flag.lock().unwrap()))
+        };
+
+        while !*flag.lock().unwrap() {
+            loom::hint::spin_loop();
+        }
+
+        th.join().unwrap();
+    });
+
+    expect_err!(res, Livelock);
+}